@@ -0,0 +1,297 @@
+//! Acids and bases among the species of a reaction, and the pH left after
+//! solutions of them are mixed. Needs `std` for logarithms.
+
+use crate::chemical::{parse_chemical, Chemical};
+use crate::dataset::Dissociation;
+use crate::i18n::I18n;
+use crate::reaction::Reaction;
+use alloc::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Acid,
+    Base,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strength {
+    Strong,
+    Weak,
+}
+
+/// A known acid or base, with the H⁺ or OH⁻ each formula unit gives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcidBase {
+    pub formula: &'static str,
+    pub role: Role,
+    pub strength: Strength,
+    pub equivalents: u32,
+}
+
+macro_rules! acids_and_bases {
+    ($(($formula:expr, $role:ident, $strength:ident, $equivalents:expr)),* $(,)?) => {
+        [$(AcidBase {
+            formula: $formula,
+            role: Role::$role,
+            strength: Strength::$strength,
+            equivalents: $equivalents,
+        }),*]
+    };
+}
+
+/// Sulfuric acid counts as fully dissociating both of its protons.
+pub const ACIDS_AND_BASES: [AcidBase; 25] = acids_and_bases![
+    ("HCl", Acid, Strong, 1),
+    ("HBr", Acid, Strong, 1),
+    ("HI", Acid, Strong, 1),
+    ("HNO3", Acid, Strong, 1),
+    ("HClO3", Acid, Strong, 1),
+    ("HClO4", Acid, Strong, 1),
+    ("H2SO4", Acid, Strong, 2),
+    ("HF", Acid, Weak, 1),
+    ("HCN", Acid, Weak, 1),
+    ("HNO2", Acid, Weak, 1),
+    ("HClO", Acid, Weak, 1),
+    ("CH3COOH", Acid, Weak, 1),
+    ("HCOOH", Acid, Weak, 1),
+    ("H2CO3", Acid, Weak, 2),
+    ("H3PO4", Acid, Weak, 3),
+    ("LiOH", Base, Strong, 1),
+    ("NaOH", Base, Strong, 1),
+    ("KOH", Base, Strong, 1),
+    ("RbOH", Base, Strong, 1),
+    ("CsOH", Base, Strong, 1),
+    ("Ca(OH)2", Base, Strong, 2),
+    ("Sr(OH)2", Base, Strong, 2),
+    ("Ba(OH)2", Base, Strong, 2),
+    ("NH3", Base, Weak, 1),
+    ("CH3NH2", Base, Weak, 1),
+];
+
+/// The acid or base `chemical` is, written in any order.
+pub fn find_acid_base(chemical: &Chemical) -> Option<&'static AcidBase> {
+    let formula = chemical.canonical_formula();
+    ACIDS_AND_BASES.iter().find(|known| {
+        parse_chemical(known.formula).is_some_and(|known| known.canonical_formula() == formula)
+    })
+}
+
+/// Whether the reaction neutralizes an acid with a base: there is one of each
+/// among the reagents, and water among the products.
+pub fn is_acid_base(reaction: &Reaction) -> bool {
+    let has = |role| {
+        reaction
+            .reagents
+            .iter()
+            .any(|chemical| find_acid_base(chemical).is_some_and(|known| known.role == role))
+    };
+    let water = reaction
+        .products
+        .iter()
+        .any(|chemical| chemical.canonical_formula() == "H2O");
+    has(Role::Acid) && has(Role::Base) && water
+}
+
+/// A solution of one species of a reaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solution {
+    /// Index of the species, reagents first.
+    pub species: usize,
+    /// In mol/L.
+    pub concentration: f64,
+    /// In L.
+    pub volume: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhError {
+    /// Weak acids and bases need a dissociation constant.
+    Weak {
+        formula: String,
+    },
+    NotAcidOrBase {
+        formula: String,
+    },
+    NoVolume,
+}
+
+impl PhError {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            PhError::Weak { .. } => "ph.weak",
+            PhError::NotAcidOrBase { .. } => "ph.not_acid_or_base",
+            PhError::NoVolume => "ph.no_volume",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            PhError::Weak { formula } | PhError::NotAcidOrBase { formula } => {
+                i18n.format(self.message_key(), &[formula])
+            }
+            PhError::NoVolume => i18n.t(self.message_key()).into(),
+        }
+    }
+}
+
+/// pH at 25 °C of the solutions mixed together, once the strong acids and
+/// bases among them have neutralized each other.
+pub fn mixture_ph(reaction: &Reaction, solutions: &[Solution]) -> Result<f64, PhError> {
+    let mut hydrogen = 0.0;
+    let mut volume = 0.0;
+    for solution in solutions {
+        let chemical = match reaction.species_at(solution.species) {
+            Some(chemical) => chemical,
+            None => continue,
+        };
+        let known = find_acid_base(chemical).ok_or_else(|| PhError::NotAcidOrBase {
+            formula: chemical.display.clone(),
+        })?;
+        if known.strength == Strength::Weak {
+            return Err(PhError::Weak {
+                formula: chemical.display.clone(),
+            });
+        }
+        let moles = solution.concentration * solution.volume * known.equivalents as f64;
+        match known.role {
+            Role::Acid => hydrogen += moles,
+            Role::Base => hydrogen -= moles,
+        }
+        volume += solution.volume;
+    }
+    if volume <= 0.0 {
+        return Err(PhError::NoVolume);
+    }
+    Ok(ph_of_excess(hydrogen / volume))
+}
+
+/// pH of a solution with `excess` mol/L of H⁺ over OH⁻, negative when OH⁻ is
+/// in excess, counting the ions of water itself.
+pub fn ph_of_excess(excess: f64) -> f64 {
+    const KW: f64 = 1e-14;
+    // [H+] - [OH-] = excess and [H+][OH-] = Kw
+    let root = (excess * excess + 4.0 * KW).sqrt();
+    let hydrogen = if excess < 0.0 {
+        // [OH-] first, as subtracting nearly equal numbers loses [H+] for a strong base
+        KW / ((root - excess) / 2.0)
+    } else {
+        (excess + root) / 2.0
+    };
+    -hydrogen.log10()
+}
+
+/// Concentrations in mol/L of the weak species, the H⁺ or OH⁻ it gives and
+/// its conjugate, in that order, before and at equilibrium.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IceTable {
+    pub initial: [f64; 3],
+    pub change: [f64; 3],
+    pub equilibrium: [f64; 3],
+}
+
+/// A weak acid or base alone in water at equilibrium.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeakEquilibrium {
+    pub ice: IceTable,
+    pub ph: f64,
+    /// Share of the species that dissociated, in percent.
+    pub percent: f64,
+}
+
+/// Solves `x² / (concentration - x) = K` exactly rather than assuming `x` is
+/// small, which fails for dilute or stronger weak acids. The ions of water
+/// itself are left out.
+pub fn weak_equilibrium(constant: Dissociation, concentration: f64) -> WeakEquilibrium {
+    let k = match constant {
+        Dissociation::Ka(k) | Dissociation::Kb(k) => k,
+    };
+    let x = (-k + (k * k + 4.0 * k * concentration).sqrt()) / 2.0;
+    let ph = match constant {
+        Dissociation::Ka(_) => -x.log10(),
+        Dissociation::Kb(_) => 14.0 + x.log10(),
+    };
+    WeakEquilibrium {
+        ice: IceTable {
+            initial: [concentration, 0.0, 0.0],
+            change: [-x, x, x],
+            equilibrium: [concentration - x, x, x],
+        },
+        ph,
+        percent: x / concentration * 100.0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn neutralization(acid: &str, base: &str, coefficients: Vec<i64>) -> Reaction {
+        Reaction::new(
+            vec![parse_chemical(acid).unwrap(), parse_chemical(base).unwrap()],
+            vec![
+                parse_chemical("NaCl").unwrap(),
+                parse_chemical("H2O").unwrap(),
+            ],
+            coefficients,
+        )
+    }
+
+    #[test]
+    fn strong_acid_and_base() {
+        let reaction = neutralization("HCl", "NaOH", vec![1, 1, 1, 1]);
+        assert!(is_acid_base(&reaction));
+        let solutions = |acid, base| {
+            [
+                Solution {
+                    species: 0,
+                    concentration: 0.1,
+                    volume: acid,
+                },
+                Solution {
+                    species: 1,
+                    concentration: 0.1,
+                    volume: base,
+                },
+            ]
+        };
+        let ph = |acid, base| mixture_ph(&reaction, &solutions(acid, base)).unwrap();
+        assert!((ph(0.05, 0.05) - 7.0).abs() < 1e-6);
+        // 0.001 mol of H+ left in 0.1 L
+        assert!((ph(0.055, 0.045) - 2.0).abs() < 1e-6);
+        assert!((ph(0.045, 0.055) - 12.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn weak_equilibria() {
+        let acetic = weak_equilibrium(Dissociation::Ka(1.8e-5), 0.1);
+        assert!((acetic.ph - 2.875).abs() < 1e-3);
+        assert!((acetic.ice.equilibrium[0] + acetic.ice.equilibrium[1] - 0.1).abs() < 1e-12);
+        assert!((acetic.percent - 1.33).abs() < 1e-2);
+        let ammonia = weak_equilibrium(Dissociation::Kb(1.8e-5), 0.1);
+        assert!((ammonia.ph - 11.125).abs() < 1e-3);
+    }
+
+    #[test]
+    fn weak_acid_is_flagged() {
+        let reaction = neutralization("CH3COOH", "NaOH", vec![1, 1, 1, 1]);
+        assert!(is_acid_base(&reaction));
+        let solution = Solution {
+            species: 0,
+            concentration: 0.1,
+            volume: 0.01,
+        };
+        assert_eq!(
+            Err(PhError::Weak {
+                formula: "CH3COOH".into()
+            }),
+            mixture_ph(&reaction, &[solution])
+        );
+        assert!(!is_acid_base(&neutralization(
+            "NaCl",
+            "NaOH",
+            vec![1, 1, 1, 1]
+        )));
+    }
+}
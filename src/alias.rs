@@ -0,0 +1,267 @@
+//! User-defined names for groups or whole species, such as `Ph = C6H5`,
+//! expanded into their formulas before a formula is parsed.
+
+use crate::chemical::parse_chemical;
+use crate::element::find_element;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Abbreviations of common organic groups. `Ac` and `Pr` are also elements,
+/// so groups named like an element are only expanded when asked for.
+pub const ORGANIC_GROUPS: [(&str, &str); 6] = [
+    ("Me", "CH3"),
+    ("Et", "C2H5"),
+    ("Pr", "C3H7"),
+    ("Ph", "C6H5"),
+    ("Ac", "C2H3O"),
+    ("Bu", "C4H9"),
+];
+
+/// Whether `name` is also the symbol of an element.
+pub fn collides_with_element(name: &str) -> bool {
+    find_element(name).is_some()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Alias {
+    /// An uppercase letter followed by letters, like `Ph` or `EtOH`.
+    pub name: String,
+    pub formula: String,
+}
+
+impl Alias {
+    /// Reads a definition written as `name = formula`.
+    pub fn parse(definition: &str) -> Option<Self> {
+        let (name, formula) = definition.split_once('=')?;
+        let (name, formula) = (name.trim(), formula.trim());
+        let valid_name = name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name.chars().all(|c| c.is_ascii_alphabetic());
+        if !valid_name {
+            return None;
+        }
+        parse_chemical(formula)?;
+        Some(Self {
+            name: name.to_string(),
+            formula: formula.to_string(),
+        })
+    }
+
+    /// Whether this is one of `ORGANIC_GROUPS` as built in.
+    pub fn is_organic_group(&self) -> bool {
+        ORGANIC_GROUPS
+            .iter()
+            .any(|&(name, formula)| self.name == name && self.formula == formula)
+    }
+}
+
+/// How a name that is both an alias and an element symbol is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reading {
+    Element,
+    Alias,
+}
+
+/// A name in the input that could be an element or an alias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambiguity {
+    pub name: String,
+    /// Name of the element, like `악티늄`.
+    pub element: &'static str,
+    /// What the alias expands to.
+    pub formula: String,
+    /// Byte range of its first use in the input.
+    pub span: Range<usize>,
+}
+
+/// Aliases in the order they were defined.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Aliases {
+    pub entries: Vec<Alias>,
+    /// Readings chosen for names that are also elements. Until one is chosen
+    /// such a name is ambiguous rather than silently read either way.
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub readings: BTreeMap<String, Reading>,
+}
+
+impl Aliases {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the alias, replacing one of the same name.
+    pub fn insert(&mut self, alias: Alias) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.name == alias.name)
+        {
+            Some(entry) => *entry = alias,
+            None => self.entries.push(alias),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries.retain(|entry| entry.name != name);
+    }
+
+    /// These aliases with `ORGANIC_GROUPS` added, where an alias of the same
+    /// name wins. Groups that are also elements are added only with `element_collisions`.
+    pub fn with_organic_groups(&self, element_collisions: bool) -> Aliases {
+        let mut merged = self.clone();
+        for &(name, formula) in ORGANIC_GROUPS.iter() {
+            let defined = self.entries.iter().any(|alias| alias.name == name);
+            if !defined && (element_collisions || !collides_with_element(name)) {
+                merged.entries.push(Alias {
+                    name: name.to_string(),
+                    formula: formula.to_string(),
+                });
+            }
+        }
+        merged
+    }
+
+    /// The alias used at the start of `rest`, if any. A name only matches
+    /// where no lowercase letter follows it, as the next letter would
+    /// otherwise belong to it, and the longest name wins.
+    fn alias_at(&self, rest: &str) -> Option<&Alias> {
+        if !rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return None;
+        }
+        self.entries
+            .iter()
+            .filter(|alias| {
+                rest.starts_with(alias.name.as_str())
+                    && !rest[alias.name.len()..].starts_with(|c: char| c.is_ascii_lowercase())
+            })
+            .max_by_key(|alias| alias.name.len())
+    }
+
+    /// Names in `input` that are both an alias and an element and have no
+    /// reading chosen yet, once each.
+    pub fn ambiguities(&self, input: &str) -> Vec<Ambiguity> {
+        let mut ambiguities: Vec<Ambiguity> = Vec::new();
+        for (index, _) in input.char_indices() {
+            let alias = match self.alias_at(&input[index..]) {
+                Some(alias) => alias,
+                None => continue,
+            };
+            let element = match find_element(&alias.name) {
+                Some((_, element)) => element,
+                None => continue,
+            };
+            let known = self.readings.contains_key(&alias.name)
+                || ambiguities
+                    .iter()
+                    .any(|ambiguity| ambiguity.name == alias.name);
+            if !known {
+                ambiguities.push(Ambiguity {
+                    name: alias.name.clone(),
+                    element: element.name,
+                    formula: alias.formula.clone(),
+                    span: index..index + alias.name.len(),
+                });
+            }
+        }
+        ambiguities
+    }
+
+    /// Replaces each alias in `input` with its formula in parentheses, except
+    /// those chosen to be read as elements.
+    pub fn expand<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        if self.entries.is_empty() {
+            return Cow::Borrowed(input);
+        }
+        let mut expanded = String::with_capacity(input.len());
+        let mut changed = false;
+        let mut index = 0;
+        while let Some(c) = input[index..].chars().next() {
+            let alias = self
+                .alias_at(&input[index..])
+                .filter(|alias| self.readings.get(&alias.name) != Some(&Reading::Element));
+            match alias {
+                Some(alias) => {
+                    expanded.push('(');
+                    expanded.push_str(&alias.formula);
+                    expanded.push(')');
+                    index += alias.name.len();
+                    changed = true;
+                }
+                _ => {
+                    expanded.push(c);
+                    index += c.len_utf8();
+                }
+            }
+        }
+        if changed {
+            Cow::Owned(expanded)
+        } else {
+            Cow::Borrowed(input)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical_with;
+
+    #[test]
+    fn expand_aliases() {
+        let mut aliases = Aliases::default();
+        aliases.insert(Alias::parse("Ph = C6H5").unwrap());
+        aliases.insert(Alias::parse("EtOH=C2H5OH").unwrap());
+        assert_eq!(None, Alias::parse("ph = C6H5"));
+        assert_eq!(None, Alias::parse("Ph = C6H5("));
+
+        assert_eq!("(C6H5)2O", aliases.expand("Ph2O"));
+        assert_eq!("(C2H5OH)", aliases.expand("EtOH"));
+        // `Pho` would be an element of its own
+        assert_eq!("Pho", aliases.expand("Pho"));
+
+        let phenol = parse_chemical_with("PhOH", &aliases).unwrap();
+        assert_eq!("C6H6O", phenol.canonical_formula());
+        assert_eq!("PhOH", phenol.display);
+
+        aliases.remove("Ph");
+        assert_eq!("PhOH", aliases.expand("PhOH"));
+    }
+
+    #[test]
+    fn organic_groups() {
+        let groups = Aliases::default().with_organic_groups(false);
+        let ester = parse_chemical_with("PhCOOEt", &groups).unwrap();
+        assert_eq!("C9H10O2", ester.canonical_formula());
+        // Actinium unless acetyl is asked for
+        assert_eq!("AcOH", groups.expand("AcOH"));
+        let groups = Aliases::default().with_organic_groups(true);
+        let acetic = parse_chemical_with("AcOH", &groups).unwrap();
+        assert_eq!("C2H4O2", acetic.canonical_formula());
+
+        let mut aliases = Aliases::default();
+        aliases.insert(Alias::parse("Me = CH3O").unwrap());
+        assert_eq!("(CH3O)", aliases.with_organic_groups(false).expand("Me"));
+    }
+
+    #[test]
+    fn ambiguous_names() {
+        let mut groups = Aliases::default().with_organic_groups(true);
+        let ambiguities = groups.ambiguities("AcOH + PrOH + Ac2O");
+        let names: Vec<&str> = ambiguities.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["Ac", "Pr"], names);
+        assert_eq!("악티늄", ambiguities[0].element);
+        assert_eq!(7..9, ambiguities[1].span);
+        assert!(groups.ambiguities("PhOH").is_empty());
+
+        groups.readings.insert("Ac".into(), Reading::Element);
+        groups.readings.insert("Pr".into(), Reading::Alias);
+        assert!(groups.ambiguities("AcOH + PrOH").is_empty());
+        assert_eq!("AcOH + (C3H7)OH", groups.expand("AcOH + PrOH"));
+    }
+}
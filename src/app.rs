@@ -0,0 +1,4906 @@
+use crate::acid_base::{find_acid_base, is_acid_base, mixture_ph, Solution};
+use crate::alias::{Alias, Aliases, Reading};
+use crate::assignment::{self, Assignment, Grade, Race, RACE_SIZE};
+use crate::balancer::Balancer;
+use crate::charge::{charge_tally, format_charge, format_total, UnwrittenCharge};
+use crate::chemical::*;
+use crate::corpus;
+use crate::dataset::{Dataset, Datasets};
+use crate::diagram::{
+    electron_transfer_diagram, element_bar_chart, line_chart, mass_balance_scale, network_graph,
+    particle_diagram, Series,
+};
+use crate::editor::{self, Selection};
+use crate::element::find_element;
+use crate::equation::{
+    append_species, parse_equation, parse_equation_with_config, swap_sides, tidy, Equation,
+    ParseError, Warning,
+};
+use crate::error_code::ERROR_CODES;
+use crate::failure::FailureLog;
+use crate::format::{
+    format_chemicals, format_fragments, format_input, format_reaction, CoefficientStyle,
+    FormattedChemical, OutputStyle, ScriptStyle,
+};
+use crate::glossary::{mark_terms, Segment, GLOSSARY};
+use crate::half_reaction::{Recognized, HALF_REACTIONS};
+use crate::hint::{analyze, Analysis};
+use crate::i18n::{Catalog, I18n};
+use crate::idb::{self, IndexedDb};
+use crate::local_storage::LocalStorage;
+use crate::network::{find_links, linked_steps, overall_reaction, Overall};
+use crate::oxidation::{oxidation_report, redox_kind};
+use crate::page::{Page, Route, ENTRY_ANCHOR};
+use crate::palette;
+use crate::paste::clean_pasted;
+use crate::practice::{Challenge, Practice, TIME_LIMITS};
+use crate::protocol::{HostMessage, HostRequest};
+use crate::quantity::{Quantity, QuantityError};
+use crate::query::{self, percent_encode};
+use crate::reaction::{self, Reaction, Side};
+use crate::recording::{Action, Recording};
+use crate::renderer::{subscript, superscript, unicode_species};
+use crate::schema::BalanceResult;
+use crate::service::{perform_balance, BalanceError, BalanceOutcome, Balanced, History};
+use crate::session::{self, HistoryRecord, Session, SyncConfig};
+use crate::solver::{Diagnostics, SolverStrategy};
+use crate::stats::{compound_usage, Stats};
+use crate::storage::AppStorage;
+use crate::tag::{add_tags, parse_tags, tag_counts};
+use crate::template::{builtin_templates, Template, TemplatePart};
+use crate::theme::{Theme, THEMES};
+use crate::thermochemistry::{
+    reaction_thermochemistry, ReactionThermochemistry, STANDARD_TEMPERATURE,
+};
+use crate::token::{self, TokenKind, Tokenized};
+use crate::tool::{self, Tool, ToolContext, ToolMsg};
+use crate::worksheet::{self, Problem};
+use seed::{prelude::*, *};
+use std::collections::{BTreeMap, BTreeSet};
+use wasm_bindgen::JsCast;
+
+/// Number of element buttons above the input.
+const RECENT_ELEMENT_COUNT: usize = 8;
+/// Number of compounds listed under my compounds.
+const MY_COMPOUND_COUNT: usize = 20;
+/// Number of history entries revealed at a time.
+const HISTORY_PAGE_SIZE: usize = 20;
+/// Temperature range in kelvin of the ΔG° and ln K plot until one is typed.
+const PLOT_LOWEST_TEMPERATURE: f64 = 200.0;
+const PLOT_HIGHEST_TEMPERATURE: f64 = 2000.0;
+/// Local storage key of the persisted `Stats`.
+const STATS_STORAGE_KEY: &str = "chemef-stats";
+const FAILURE_LOG_STORAGE_KEY: &str = "chemef-failures";
+/// Local storage key of the user's `Aliases`.
+const ALIASES_STORAGE_KEY: &str = "chemef-aliases";
+/// Local storage key of whether organic groups are expanded, `"false"` when they
+/// are not, read only until a `ParserConfig` is saved.
+const ORGANIC_GROUPS_STORAGE_KEY: &str = "chemef-organic-groups";
+/// Local storage key of the persisted `ParserConfig`.
+const PARSER_CONFIG_STORAGE_KEY: &str = "chemef-parser-config";
+/// Local storage key of whether groups named like an element are expanded, `"true"` when they are.
+const ELEMENT_GROUPS_STORAGE_KEY: &str = "chemef-element-groups";
+/// Local storage key of the selected theme id.
+const THEME_STORAGE_KEY: &str = "chemef-theme";
+/// Local storage key of the selected `TextSize`.
+const TEXT_SIZE_STORAGE_KEY: &str = "chemef-text-size";
+/// Local storage key of whether brackets close automatically, `"true"` when they do.
+const AUTO_CLOSE_STORAGE_KEY: &str = "chemef-auto-close";
+/// Local storage key of the persisted `OutputStyle`.
+const OUTPUT_STYLE_STORAGE_KEY: &str = "chemef-output-style";
+/// Local storage key of the selected locale.
+const LOCALE_STORAGE_KEY: &str = "chemef-locale";
+/// Local storage key of the `SyncConfig`, kept after the sync link is opened once.
+const SYNC_STORAGE_KEY: &str = "chemef-sync";
+/// Key of the last recording, kept to replay on later visits.
+const RECORDING_STORAGE_KEY: &str = "chemef-recording";
+/// Locales with a catalog at `locales/<locale>.json`, listed as a JSON array.
+const CATALOG_INDEX_URL: &str = "locales/index.json";
+/// Delay between revealing each coefficient of a new result, in milliseconds.
+const REVEAL_INTERVAL: u32 = 300;
+/// Milliseconds between updates of the practice timer.
+const PRACTICE_TICK: u32 = 1000;
+
+struct Model {
+    /// Page shown, following the URL fragment.
+    pub page: Page,
+    /// Calculators of the tools page.
+    pub tools: Vec<Box<dyn Tool>>,
+    pub input: String,
+    /// Selection in the input, kept so text can be inserted where the user is.
+    pub selection: Selection,
+    input_ref: ElRef<web_sys::HtmlInputElement>,
+    /// The result shown in place of the input, scrolled into view when it appears.
+    result_ref: ElRef<web_sys::Element>,
+    /// Species typed to add to the current result, and the side it goes on.
+    pub append_formula: String,
+    pub append_side: Side,
+    /// Tokens of `input`, brought up to date after every message.
+    pub tokens: Tokenized,
+    /// Typing `(` also inserts the `)` after the caret.
+    pub auto_close: bool,
+    /// How coefficients and scripts of balanced reactions are written.
+    pub output_style: OutputStyle,
+    /// Message key of a short note about the input, such as a cleaned paste.
+    pub notice: Option<&'static str>,
+    /// Earlier history entry the last result repeats.
+    pub duplicate_of: Option<usize>,
+    /// History entry jumped to, marked until the next result.
+    pub highlighted: Option<usize>,
+    /// Conditions entered in their own field, used when the input has no `;` clause.
+    pub conditions: String,
+    pub result: Option<Vec<FormattedChemical>>,
+    /// Notes about how the input of the current result was read.
+    pub warnings: Vec<Warning>,
+    /// Species of the current result that look like ions written without a charge.
+    pub unwritten_charges: Vec<UnwrittenCharge>,
+    pub error: Option<String>,
+    /// Stable code of `error`, linking to its help.
+    pub error_code: Option<&'static str>,
+    /// Code whose help was opened from an error, kept open and highlighted.
+    pub help_code: Option<&'static str>,
+    /// Library half-reaction the input was recognized as, shown instead of a result.
+    pub half_reaction: Option<Recognized>,
+    /// Library half-reaction chosen to pair with it into a cell reaction.
+    pub half_partner: Option<usize>,
+    /// Concentration and volume typed for each acid or base of the result,
+    /// keyed by species index. Bare numbers are in M and mL.
+    pub solutions: BTreeMap<usize, (String, String)>,
+    /// Temperature in kelvin typed for the thermodynamic summary.
+    pub temperature: String,
+    /// Lowest and highest temperature in kelvin typed for the ΔG° and ln K plot.
+    pub temperature_range: (String, String),
+    pub history: Vec<HistoryEntry>,
+    /// Number of newest history entries to render.
+    pub history_shown: usize,
+    /// Indices of the history entries selected for bulk actions.
+    pub history_selection: BTreeSet<usize>,
+    /// Entry clicked last, from which a shift-click selects a range.
+    pub history_anchor: Option<usize>,
+    /// The selected entries added up by Hess's law, and their indices.
+    pub combined: Option<(Vec<usize>, Overall)>,
+    /// Comma-separated tags typed to add to the selected entries.
+    pub tag_draft: String,
+    /// Only entries with this tag are shown, when set.
+    pub tag_filter: Option<String>,
+    /// Entry whose note is being edited, and the note as typed so far.
+    pub note_editing: Option<(usize, String)>,
+    /// When the history was last saved, for last-write-wins syncing.
+    pub session_saved_at: f64,
+    pub sync: Option<SyncConfig>,
+    /// Endpoint offered by a `?sync=` link, waiting for the user to accept it.
+    pub offered_sync: Option<SyncConfig>,
+    pub sync_status: Option<SyncStatus>,
+    /// Session JSON prepared for download, cleared when the history changes.
+    pub session_export: Option<String>,
+    /// Set when a published session is opened with `?session=<url>`.
+    gallery: Option<Gallery>,
+    pub show_network: bool,
+    /// The most recently balanced reaction.
+    pub reaction: Option<Reaction>,
+    pub selected_species: Option<usize>,
+    /// Indices of the two species whose mole ratio is shown with the result.
+    pub mole_ratio: (usize, usize),
+    /// Number of species whose coefficient is shown in the current result.
+    pub revealed_coefficients: usize,
+    /// Incremented per result so that ticks of an earlier reveal are ignored.
+    pub reveal_generation: usize,
+    pub balancer: Balancer,
+    /// Set with `?solver=` to reveal the solver choice for comparing strategies.
+    pub show_solver: bool,
+    /// Milliseconds the last balance took, shown next to the solver choice.
+    pub solve_time: Option<f64>,
+    /// Rank and nullity of the last equation, computed only while the solver choice is shown.
+    pub diagnostics: Option<Diagnostics>,
+    /// Corpus lines collected from results in dev builds, to be added to `corpus.txt`.
+    pub corpus_additions: Vec<String>,
+    /// Fill-in-the-blank template being filled, if any.
+    pub template: Option<Template>,
+    pub template_values: Vec<Option<String>>,
+    pub selected_blank: usize,
+    /// Problems of the assignment opened from a link, if any.
+    pub assignment: Option<Vec<AssignmentProblem>>,
+    /// The input being balanced by hand, if practicing.
+    pub practice: Option<Practice>,
+    pub practice_clock: PracticeClock,
+    /// The challenge run, if practicing against a time limit.
+    pub challenge: Option<Challenge>,
+    /// Equations a teacher is collecting for a new assignment link, one per line.
+    pub assignment_draft: String,
+    /// The race the assignment was drawn for, if opened from a race link.
+    pub race: Option<Race>,
+    /// When the race started, in milliseconds of `now()`.
+    pub race_started: f64,
+    /// Seconds taken to get every problem of the race right.
+    pub race_time: Option<f64>,
+    /// A race created to share with a class.
+    pub race_link: Option<Race>,
+    /// When the recording being made started, in milliseconds of `now()`.
+    pub recording_started: Option<f64>,
+    /// The recording being made, or else the last one made.
+    pub recording: Recording,
+    /// The command palette, while open.
+    pub palette: Option<Palette>,
+    palette_ref: ElRef<web_sys::HtmlInputElement>,
+    /// Index of the next step to replay, while replaying.
+    pub playback: Option<usize>,
+    /// Steps of an earlier replay carry an older generation and are dropped.
+    pub playback_generation: usize,
+    pub stats: Stats,
+    pub show_stats: bool,
+    pub show_compounds: bool,
+    pub failures: FailureLog,
+    pub show_failures: bool,
+    pub aliases: Aliases,
+    /// Syntax accepted in the input, including whether built-in organic
+    /// groups such as `Ph` are expanded along with `aliases`.
+    pub parser_config: ParserConfig,
+    /// Groups named like an element, such as `Ac`, may be read as groups too.
+    pub element_groups: bool,
+    /// How each name that is both an alias and an element was chosen to be read
+    /// this session.
+    pub readings: BTreeMap<String, Reading>,
+    /// Definition being typed as `name = formula`, and whether adding it failed.
+    pub alias_draft: String,
+    pub alias_invalid: bool,
+    pub theme: &'static Theme,
+    pub text_size: TextSize,
+    pub i18n: I18n,
+    pub datasets: Datasets,
+    /// Datasets being fetched or that could not be, absent once loaded.
+    pub dataset_loading: BTreeMap<Dataset, Loading>,
+    pub worksheet_size: usize,
+    /// Worksheet shown in the print view, if any.
+    pub worksheet: Option<Vec<Problem>>,
+    /// Set when the page is embedded in another with `?embed`.
+    pub embed: Option<Embed>,
+    /// Set once a host page has sent a request, after which results are posted to it.
+    pub host_connected: bool,
+    /// Id of the host request being balanced, echoed back with its result.
+    pub host_request_id: Option<String>,
+}
+
+/// Embed mode shows only the calculator and posts each result to the parent frame.
+pub struct Embed {
+    /// Keeps the preset equation of `?equation=` from being edited, as with `?embed&lock`.
+    pub locked: bool,
+    /// Origin of the parent page, from `?origin=` or else the referrer, which results are posted to
+    /// and host requests are only accepted from.
+    pub origin: Option<String>,
+}
+
+impl Embed {
+    fn from_query(search: &str) -> Option<Self> {
+        query::param(search, "embed")?;
+        let origin = query::param(search, "origin")
+            .filter(|origin| query::origin(origin) == Some(origin.as_str()))
+            .or_else(|| {
+                let referrer = seed::document().referrer();
+                query::origin(&referrer).map(String::from)
+            });
+        Some(Self {
+            locked: query::param(search, "lock").is_some(),
+            origin,
+        })
+    }
+}
+
+/// Text size applied as a class on the root element, which every size in the stylesheet is relative to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl TextSize {
+    const ALL: [TextSize; 3] = [TextSize::Small, TextSize::Medium, TextSize::Large];
+
+    fn class(self) -> &'static str {
+        match self {
+            TextSize::Small => "text-small",
+            TextSize::Medium => "text-medium",
+            TextSize::Large => "text-large",
+        }
+    }
+
+    fn from_class(class: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|size| size.class() == class)
+    }
+
+    fn message_key(self) -> &'static str {
+        match self {
+            TextSize::Small => "text_size.small",
+            TextSize::Medium => "text_size.medium",
+            TextSize::Large => "text_size.large",
+        }
+    }
+}
+
+/// A published session shown read-only in place of the calculator.
+enum Gallery {
+    Loading,
+    Failed,
+    Loaded(Vec<HistoryEntry>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncStatus {
+    Syncing,
+    Synced,
+    Failed,
+}
+
+impl SyncStatus {
+    fn message_key(self) -> &'static str {
+        match self {
+            SyncStatus::Syncing => "sync.syncing",
+            SyncStatus::Synced => "sync.synced",
+            SyncStatus::Failed => "sync.failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loading {
+    Pending,
+    /// Neither the network nor the cache had it, as when first used offline.
+    Failed,
+}
+
+struct AssignmentProblem {
+    pub input: String,
+    pub equation: Result<Equation, ParseError>,
+    /// Coefficients typed by the student, where an empty one means 1.
+    pub answers: Vec<String>,
+    pub grade: Option<Grade>,
+    pub analysis: Option<Analysis>,
+    /// Number of hints revealed so far, which only ever grows.
+    pub hints_shown: usize,
+}
+
+impl AssignmentProblem {
+    fn new(input: String) -> Self {
+        let equation = parse_equation(&input);
+        let species_count = equation.as_ref().map_or(0, |equation| {
+            equation.reagents.len() + equation.products.len()
+        });
+        let analysis = equation
+            .as_ref()
+            .ok()
+            .and_then(|equation| analyze(&equation.reagents, &equation.products));
+        Self {
+            input,
+            equation,
+            answers: vec![String::new(); species_count],
+            grade: None,
+            analysis,
+            hints_shown: 0,
+        }
+    }
+
+    /// The reaction with the student's coefficients, where an invalid coefficient becomes 0.
+    fn answer(&self) -> Option<Reaction> {
+        let equation = self.equation.as_ref().ok()?;
+        let coefficients = self
+            .answers
+            .iter()
+            .map(|answer| match answer.trim() {
+                "" => 1,
+                answer => answer.parse().unwrap_or(0),
+            })
+            .collect();
+        let mut reaction = Reaction::new(
+            equation.reagents.clone(),
+            equation.products.clone(),
+            coefficients,
+        );
+        reaction.catalyst = equation.catalyst.clone();
+        reaction.conditions = equation.conditions.clone();
+        Some(reaction)
+    }
+}
+
+/// Search typed into the command palette and the match picked with the arrow keys.
+#[derive(Debug, Clone, Default)]
+struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+/// Time spent on the equation being practiced.
+#[derive(Debug, Clone, Default)]
+struct PracticeClock {
+    /// When the practice started, in milliseconds of `now()`.
+    pub started: f64,
+    /// Seconds taken so far, or to solve it once finished.
+    pub elapsed: f64,
+    /// Whether it was solved, revealed or ran out of time.
+    pub finished: bool,
+    /// Whether it was solved faster than ever before at its difficulty.
+    pub new_best: bool,
+    /// Points the challenge gave for it.
+    pub points: Option<u32>,
+    /// Ticks of earlier practices carry an older generation and are dropped.
+    pub generation: usize,
+}
+
+/// The history as the balancing service sees it, rendering each new entry
+/// with its kinds as localized tags.
+struct EntryHistory<'a> {
+    entries: &'a mut Vec<HistoryEntry>,
+    i18n: &'a I18n,
+    style: &'a OutputStyle,
+}
+
+impl History for EntryHistory<'_> {
+    fn reactions(&self) -> Vec<&Reaction> {
+        self.entries.iter().map(|entry| &entry.reaction).collect()
+    }
+
+    fn push(&mut self, balanced: &Balanced) {
+        let mut entry = HistoryEntry::new(
+            self.entries.len(),
+            balanced.source.clone(),
+            format_input(&balanced.source),
+            format_reaction(&balanced.reaction),
+            balanced.reaction.clone(),
+            self.style,
+        );
+        // Tagged by kind so that the tag filter is useful from the start
+        entry.tags = balanced
+            .kinds
+            .iter()
+            .map(|kind| self.i18n.t(kind.message_key()).to_string())
+            .collect();
+        self.entries.push(entry);
+    }
+}
+
+struct HistoryEntry {
+    /// The equation as typed, kept so the session can be saved.
+    pub source: String,
+    pub input: Vec<FormattedChemical>,
+    pub output: Vec<FormattedChemical>,
+    pub reaction: Reaction,
+    pub tags: Vec<String>,
+    pub note: String,
+    /// Rendered once on creation so that re-rendering a long history stays cheap.
+    node: Node<Msg>,
+}
+
+impl HistoryEntry {
+    fn new(
+        index: usize,
+        source: String,
+        input: Vec<FormattedChemical>,
+        output: Vec<FormattedChemical>,
+        reaction: Reaction,
+        style: &OutputStyle,
+    ) -> Self {
+        let node = Self::render(index, &input, &output, style);
+        Self {
+            source,
+            input,
+            output,
+            reaction,
+            tags: Vec::new(),
+            note: String::new(),
+            node,
+        }
+    }
+
+    /// Renders the entry again after the output style changed.
+    fn restyle(&mut self, index: usize, style: &OutputStyle) {
+        self.node = Self::render(index, &self.input, &self.output, style);
+    }
+
+    fn render(
+        index: usize,
+        input: &[FormattedChemical],
+        output: &[FormattedChemical],
+        style: &OutputStyle,
+    ) -> Node<Msg> {
+        details! {
+            summary! {
+                header! {
+                    a! {
+                        attrs! { At::Href => format!("#{}", entry_id(index)) },
+                        format!("Out[{}]", index)
+                    },
+                    " : "
+                },
+                section! {
+                    output.iter().map(|part| part.styled_node(style))
+                }
+            },
+            div! {
+                class!["input"],
+                header! {
+                    format!("In[{}] : ", index)
+                },
+                section! {
+                    input.iter().map(FormattedChemical::node)
+                }
+            }
+        }
+    }
+
+    fn from_record(index: usize, record: HistoryRecord, style: &OutputStyle) -> Option<Self> {
+        let reaction = record.result.to_reaction()?;
+        let input = format_input(&record.input);
+        let output = format_reaction(&reaction);
+        let mut entry = Self::new(index, record.input, input, output, reaction, style);
+        entry.tags = record.tags;
+        entry.note = record.note;
+        Some(entry)
+    }
+
+    fn to_record(&self) -> HistoryRecord {
+        HistoryRecord {
+            input: self.source.clone(),
+            result: BalanceResult::new(&self.reaction),
+            tags: self.tags.clone(),
+            note: self.note.clone(),
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            page: Page::Calculator,
+            tools: tool::registry(),
+            input: String::new(),
+            selection: Selection::default(),
+            input_ref: ElRef::default(),
+            result_ref: ElRef::default(),
+            append_formula: String::new(),
+            append_side: Side::Product,
+            tokens: Tokenized::default(),
+            auto_close: false,
+            output_style: OutputStyle::default(),
+            notice: None,
+            duplicate_of: None,
+            highlighted: None,
+            conditions: String::new(),
+            result: None,
+            warnings: Vec::new(),
+            unwritten_charges: Vec::new(),
+            error: None,
+            error_code: None,
+            help_code: None,
+            half_reaction: None,
+            half_partner: None,
+            solutions: BTreeMap::new(),
+            temperature: String::new(),
+            temperature_range: (String::new(), String::new()),
+            history: Vec::new(),
+            history_shown: HISTORY_PAGE_SIZE,
+            history_selection: BTreeSet::new(),
+            history_anchor: None,
+            combined: None,
+            tag_draft: String::new(),
+            tag_filter: None,
+            note_editing: None,
+            session_saved_at: 0.0,
+            sync: None,
+            offered_sync: None,
+            sync_status: None,
+            session_export: None,
+            gallery: None,
+            show_network: false,
+            reaction: None,
+            selected_species: None,
+            mole_ratio: (0, 1),
+            revealed_coefficients: 0,
+            reveal_generation: 0,
+            balancer: Balancer::default(),
+            show_solver: false,
+            solve_time: None,
+            diagnostics: None,
+            corpus_additions: Vec::new(),
+            template: None,
+            template_values: Vec::new(),
+            selected_blank: 0,
+            assignment: None,
+            practice: None,
+            practice_clock: PracticeClock::default(),
+            challenge: None,
+            assignment_draft: String::new(),
+            race: None,
+            race_started: 0.0,
+            race_time: None,
+            race_link: None,
+            recording_started: None,
+            recording: Recording::default(),
+            playback: None,
+            palette: None,
+            palette_ref: ElRef::default(),
+            playback_generation: 0,
+            stats: Stats::default(),
+            show_stats: false,
+            show_compounds: false,
+            failures: FailureLog::default(),
+            show_failures: false,
+            aliases: Aliases::default(),
+            parser_config: ParserConfig::default(),
+            element_groups: false,
+            readings: BTreeMap::new(),
+            alias_draft: String::new(),
+            alias_invalid: false,
+            theme: &THEMES[0],
+            text_size: TextSize::Medium,
+            i18n: I18n::default(),
+            datasets: Datasets::bundled(),
+            dataset_loading: BTreeMap::new(),
+            worksheet_size: 10,
+            worksheet: None,
+            embed: None,
+            host_connected: false,
+            host_request_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Msg {
+    Balance,
+    InputKeyDown(String),
+    SetInput(String),
+    PasteCleaned(String),
+    SetSelection(Selection),
+    InsertText(String),
+    TypeBracket(char, Selection),
+    ToggleAutoClose,
+    SetOutputStyle(OutputStyle),
+    Tidy,
+    SwapSides,
+    SetAppendFormula(String),
+    SetAppendSide(String),
+    AppendSpecies,
+    SetConditions(String),
+    SelectSpecies(usize),
+    /// Chooses the species at the first (0) or second (1) place of the mole ratio.
+    SetRatioSpecies(usize, String),
+    ShowMoreHistory,
+    ShowErrorHelp(&'static str),
+    ToggleNetwork,
+    RevealCoefficient(usize),
+    Reset,
+    OpenTemplate(usize),
+    CloseTemplate,
+    SelectBlank(usize),
+    FillBlank(String),
+    SetAnswer(usize, usize, String),
+    ShowHint(usize),
+    StartPractice,
+    StepCoefficient(usize, i64),
+    RevealAnswer,
+    EndPractice,
+    PracticeTick(usize),
+    SetTimeLimit(String),
+    GradeProblem(usize),
+    SetAssignmentDraft(String),
+    CreateRace,
+    SelectHistoryEntry(usize, bool),
+    ClearHistorySelection,
+    DeleteSelected,
+    ExportSelected,
+    CombineSelected,
+    CloseCombined,
+    WorksheetFromSelected,
+    SetTagDraft(String),
+    TagSelected,
+    RemoveTag(usize, String),
+    SetTagFilter(String),
+    EditNote(usize),
+    SetNoteDraft(String),
+    SaveNote,
+    CancelNote,
+    JumpToEntry(usize),
+    Navigate(Route),
+    Tool(usize, ToolMsg),
+    StartRecording,
+    StopRecording,
+    StartPlayback,
+    StopPlayback,
+    PlaybackStep(usize),
+    OpenPalette,
+    ClosePalette,
+    SetPaletteQuery(String),
+    PaletteKeyDown(String),
+    RunCommand(usize),
+    DraftFromHistory,
+    ToggleStats,
+    ToggleCompounds,
+    ToggleFailures,
+    ToggleFailureLogging,
+    ClearFailures,
+    SetHalfPartner(String),
+    SetConcentration(usize, String),
+    SetVolume(usize, String),
+    SetTemperature(String),
+    SetLowestTemperature(String),
+    SetHighestTemperature(String),
+    SetAliasDraft(String),
+    AddAlias,
+    RemoveAlias(String),
+    SetParserConfig(ParserConfig),
+    ToggleElementGroups,
+    ChooseReading(String, Reading),
+    UseExample(String),
+    SetWorksheetSize(String),
+    GenerateWorksheet,
+    PrintWorksheet,
+    CloseWorksheet,
+    /// A request from the parent frame, with the origin it was sent from.
+    HostRequest(HostRequest, String),
+    SetTheme(String),
+    SetTextSize(TextSize),
+    SetSolver(String),
+    AddToCorpus,
+    CatalogIndexFetched(Vec<String>),
+    CatalogFetched(Catalog),
+    DatasetFetched(Dataset, Option<String>),
+    SessionLoaded(Session),
+    SessionPulled(Option<Session>),
+    SyncFinished(bool),
+    SyncNow,
+    StopSync,
+    AcceptSync,
+    DeclineSync,
+    ExportSession,
+    GalleryFetched(Option<Session>),
+    SetLocale(String),
+}
+
+/// Logs what a message does to the input while recording, so that only
+/// the outcome of typing, pasting or filling a template is replayed.
+fn update(msg: Msg, model: &mut Model, order: &mut impl Orders<Msg>) {
+    let started = match (model.recording_started, model.playback) {
+        (Some(started), None) => started,
+        _ => return handle_msg(msg, model, order),
+    };
+    let action = match msg {
+        Msg::Balance => Some(Action::Balance),
+        Msg::Reset => Some(Action::Reset),
+        _ => None,
+    };
+    if let Some(action) = action {
+        model.recording.push(now() - started, action);
+        return handle_msg(msg, model, order);
+    }
+    let input = model.input.clone();
+    let conditions = model.conditions.clone();
+    handle_msg(msg, model, order);
+    if model.input != input {
+        let action = Action::SetInput(model.input.clone());
+        model.recording.push(now() - started, action);
+    }
+    if model.conditions != conditions {
+        let action = Action::SetConditions(model.conditions.clone());
+        model.recording.push(now() - started, action);
+    }
+}
+
+fn handle_msg(msg: Msg, model: &mut Model, order: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::InputKeyDown(key_string) => {
+            if key_string == "Enter" {
+                order.skip();
+                order.send_msg(Msg::Balance);
+            }
+        }
+        Msg::Balance => {
+            model.error = None;
+            model.notice = None;
+            model.duplicate_of = None;
+            model.highlighted = None;
+            model.half_reaction = None;
+            model.half_partner = None;
+            let input = full_input(model);
+            let aliases = parser_aliases(model);
+            let started = now();
+            let outcome = perform_balance(
+                &input,
+                &aliases,
+                &model.parser_config,
+                &model.balancer,
+                &mut EntryHistory {
+                    entries: &mut model.history,
+                    i18n: &model.i18n,
+                    style: &model.output_style,
+                },
+            );
+            match outcome {
+                BalanceOutcome::HalfReaction(recognized) => {
+                    model.half_reaction = Some(recognized);
+                    model.result = None;
+                    return;
+                }
+                BalanceOutcome::Balanced {
+                    balanced,
+                    duplicate_of,
+                } => {
+                    model.solve_time = Some(now() - started);
+                    let reaction = balanced.reaction;
+                    model.warnings = balanced.warnings;
+                    model.unwritten_charges = balanced.unwritten_charges;
+                    model.duplicate_of = duplicate_of;
+                    model.solutions.clear();
+                    if is_acid_base(&reaction) {
+                        request_dataset(model, order, Dataset::DissociationConstants);
+                    }
+                    request_dataset(model, order, Dataset::Thermochemistry);
+                    model.stats.record_balanced(reaction.coefficients.len());
+                    model.stats.record_elements(&reaction);
+                    save_stats(&model.stats);
+                    model.result = model.history.last().map(|entry| entry.output.clone());
+                    model.session_export = None;
+                    if model.embed.is_none() {
+                        model.session_saved_at = js_sys::Date::now();
+                        save(model, order);
+                    }
+                    if !model.embed.as_ref().map_or(false, |embed| embed.locked) {
+                        model.input.clear();
+                        model.conditions.clear();
+                    }
+                    // The first reagent and the first product, to begin with
+                    model.mole_ratio = (0, reaction.reagents.len());
+                    model.reaction = Some(reaction);
+                    model.selected_species = None;
+                    model.revealed_coefficients = 0;
+                    model.reveal_generation += 1;
+                    let generation = model.reveal_generation;
+                    order.perform_cmd(cmds::timeout(REVEAL_INTERVAL, move || {
+                        Msg::RevealCoefficient(generation)
+                    }));
+                    let result = model.result_ref.clone();
+                    order.after_next_render(move |_| {
+                        if let Some(result) = result.get() {
+                            scroll_into_view(&result);
+                        }
+                    });
+                }
+                BalanceOutcome::Failed(error) => {
+                    if let BalanceError::Reaction(_) = error {
+                        model.solve_time = Some(now() - started);
+                    }
+                    model.failures.record(&input, error.message_key());
+                    model.error = Some(error.localize(&model.i18n));
+                    model.error_code = Some(error.code());
+                }
+            }
+            if model.show_solver {
+                model.diagnostics =
+                    parse_equation_with_config(&input, &aliases, &model.parser_config)
+                        .ok()
+                        .and_then(|equation| {
+                            reaction::diagnose(&equation.reagents, &equation.products).ok()
+                        });
+            }
+            if model.error.is_some() {
+                model.stats.record_failed();
+                save_stats(&model.stats);
+                save_failures(&model.failures);
+            }
+            if model.embed.is_some() || model.host_connected {
+                let id = model.host_request_id.take();
+                let message = match (&model.error, &model.reaction) {
+                    (Some(message), _) => HostMessage::Error {
+                        message: message.clone(),
+                        id,
+                    },
+                    (None, Some(reaction)) => HostMessage::Result {
+                        result: BalanceResult::new(reaction),
+                        id,
+                    },
+                    (None, None) => unreachable!("a balance either fails or leaves a reaction"),
+                };
+                if let Some(origin) = model.embed.as_ref().and_then(|embed| embed.origin.as_ref()) {
+                    post_to_parent(&message, origin);
+                }
+            }
+        }
+        Msg::SetInput(input) => {
+            model.input = input;
+            model.notice = None;
+        }
+        Msg::SetSelection(selection) => model.selection = selection,
+        Msg::InsertText(text) => insert_text(model, order, &text),
+        Msg::PasteCleaned(text) => {
+            insert_text(model, order, &text);
+            model.notice = Some("paste.cleaned");
+        }
+        Msg::TypeBracket(bracket, selection) => {
+            model.selection = selection;
+            let next = &model.input[selection.byte_range(&model.input).end..];
+            if bracket == '(' && (model.auto_close || !selection.is_caret()) {
+                editor::wrap(&mut model.input, &mut model.selection, "(", ")");
+            } else if bracket == ')'
+                && model.auto_close
+                && selection.is_caret()
+                && next.starts_with(')')
+            {
+                // Step over the bracket closed automatically
+                model.selection = Selection::caret(selection.end + 1);
+            } else {
+                editor::insert(&mut model.input, &mut model.selection, &bracket.to_string());
+            }
+            restore_selection(model, order);
+        }
+        Msg::ToggleAutoClose => {
+            model.auto_close = !model.auto_close;
+            save_item(AUTO_CLOSE_STORAGE_KEY, &model.auto_close.to_string());
+        }
+        Msg::SetOutputStyle(style) => {
+            model.output_style = style;
+            for (index, entry) in model.history.iter_mut().enumerate() {
+                entry.restyle(index, &style);
+            }
+            if let Ok(json) = serde_json::to_string(&style) {
+                save_item(OUTPUT_STYLE_STORAGE_KEY, &json);
+            }
+        }
+        Msg::SetConditions(conditions) => model.conditions = conditions,
+        Msg::Tidy => match tidy(&model.input, &parser_aliases(model), &model.parser_config) {
+            Ok(tidied) => {
+                model.error = None;
+                model.selection = Selection::end_of(&tidied);
+                model.input = tidied;
+                restore_selection(model, order);
+            }
+            Err(error) => {
+                model.error = Some(error.localize(&model.i18n));
+                model.error_code = Some(error.code());
+            }
+        },
+        Msg::SetAppendFormula(formula) => model.append_formula = formula,
+        Msg::SetAppendSide(side) => {
+            model.append_side = if side == "reagent" {
+                Side::Reagent
+            } else {
+                Side::Product
+            }
+        }
+        Msg::AppendSpecies => {
+            if let Some(reaction) = &model.reaction {
+                match append_species(reaction, &model.append_formula, model.append_side) {
+                    Ok(input) => {
+                        model.input = input;
+                        model.conditions.clear();
+                        model.append_formula.clear();
+                        order.send_msg(Msg::Balance);
+                    }
+                    Err(error) => {
+                        model.error = Some(error.localize(&model.i18n));
+                        model.error_code = Some(error.code());
+                    }
+                }
+            }
+        }
+        Msg::StartPractice => {
+            model.error = None;
+            let practice = parse_equation_with_config(
+                &full_input(model),
+                &parser_aliases(model),
+                &model.parser_config,
+            )
+            .map_err(|error| (error.localize(&model.i18n), error.code()))
+            .and_then(|equation| {
+                Practice::new(equation).map_err(|error| (error.localize(&model.i18n), error.code()))
+            });
+            match practice {
+                Ok(practice) => {
+                    model.practice = Some(practice);
+                    let generation = model.practice_clock.generation + 1;
+                    model.practice_clock = PracticeClock {
+                        started: now(),
+                        generation,
+                        ..PracticeClock::default()
+                    };
+                    order.perform_cmd(cmds::timeout(PRACTICE_TICK, move || {
+                        Msg::PracticeTick(generation)
+                    }));
+                }
+                Err((error, code)) => {
+                    model.error = Some(error);
+                    model.error_code = Some(code);
+                }
+            }
+        }
+        Msg::StepCoefficient(species, delta) => {
+            let practice = match &mut model.practice {
+                Some(practice) if !model.practice_clock.finished => practice,
+                _ => return,
+            };
+            practice.step(species, delta);
+            if practice.grade() == Grade::Correct {
+                let clock = &mut model.practice_clock;
+                clock.elapsed = (now() - clock.started) / 1000.0;
+                clock.finished = true;
+                clock.new_best = model
+                    .stats
+                    .record_practice_time(practice.difficulty.id(), clock.elapsed);
+                clock.points = model
+                    .challenge
+                    .as_mut()
+                    .map(|challenge| challenge.record_solved(clock.elapsed));
+                save_stats(&model.stats);
+            }
+        }
+        Msg::RevealAnswer => {
+            if let Some(practice) = &mut model.practice {
+                practice.reveal();
+                model.practice_clock.finished = true;
+                if let Some(challenge) = &mut model.challenge {
+                    challenge.record_missed();
+                }
+            }
+        }
+        Msg::EndPractice => model.practice = None,
+        Msg::PracticeTick(generation) => {
+            let clock = &mut model.practice_clock;
+            if generation != clock.generation || clock.finished || model.practice.is_none() {
+                order.skip();
+                return;
+            }
+            clock.elapsed = (now() - clock.started) / 1000.0;
+            let expired = model
+                .challenge
+                .as_ref()
+                .map_or(false, |challenge| challenge.is_expired(clock.elapsed));
+            if expired {
+                order.send_msg(Msg::RevealAnswer);
+            } else {
+                order.perform_cmd(cmds::timeout(PRACTICE_TICK, move || {
+                    Msg::PracticeTick(generation)
+                }));
+            }
+        }
+        Msg::SetTimeLimit(limit) => {
+            model.challenge = limit.parse().ok().map(Challenge::new);
+        }
+        Msg::SwapSides => {
+            if let Some(swapped) = swap_sides(&model.input) {
+                model.selection = Selection::end_of(&swapped);
+                model.input = swapped;
+                restore_selection(model, order);
+            }
+        }
+        Msg::SelectSpecies(index) => {
+            // Selecting the species already shown closes the panel
+            if model.selected_species == Some(index) {
+                model.selected_species = None;
+            } else {
+                model.selected_species = Some(index);
+                request_dataset(model, order, Dataset::CompoundNames);
+            }
+        }
+        Msg::SetRatioSpecies(place, index) => {
+            if let Ok(index) = index.parse() {
+                if place == 0 {
+                    model.mole_ratio.0 = index;
+                } else {
+                    model.mole_ratio.1 = index;
+                }
+            }
+        }
+        Msg::ShowMoreHistory => model.history_shown += HISTORY_PAGE_SIZE,
+        Msg::ShowErrorHelp(code) => {
+            model.help_code = Some(code);
+            navigate(model, Route::Page(Page::About));
+            order.after_next_render(move |_| {
+                if let Some(help) = seed::document().get_element_by_id(&help_id(code)) {
+                    scroll_into_view(&help);
+                }
+            });
+        }
+        Msg::ToggleNetwork => model.show_network = !model.show_network,
+        Msg::RevealCoefficient(generation) => {
+            if generation != model.reveal_generation {
+                order.skip();
+                return;
+            }
+            model.revealed_coefficients += 1;
+            let species_count = model
+                .reaction
+                .as_ref()
+                .map_or(0, |reaction| reaction.coefficients.len());
+            if model.revealed_coefficients < species_count {
+                order.perform_cmd(cmds::timeout(REVEAL_INTERVAL, move || {
+                    Msg::RevealCoefficient(generation)
+                }));
+            }
+        }
+        Msg::Reset => {
+            model.result = None;
+            model.error = None;
+            model.selected_species = None;
+        }
+        Msg::OpenTemplate(index) => {
+            let template = builtin_templates().swap_remove(index);
+            model.template_values = vec![None; template.blank_count()];
+            model.template = Some(template);
+            model.selected_blank = 0;
+            model.result = None;
+        }
+        Msg::SetAnswer(problem, species, answer) => {
+            if let Some(problem) = model
+                .assignment
+                .as_mut()
+                .and_then(|problems| problems.get_mut(problem))
+            {
+                problem.answers[species] = answer;
+                problem.grade = None;
+            }
+        }
+        Msg::ShowHint(problem) => {
+            if let Some(problem) = model
+                .assignment
+                .as_mut()
+                .and_then(|problems| problems.get_mut(problem))
+            {
+                problem.hints_shown += 1;
+            }
+        }
+        Msg::GradeProblem(problem) => {
+            if let Some(problem) = model
+                .assignment
+                .as_mut()
+                .and_then(|problems| problems.get_mut(problem))
+            {
+                problem.grade = problem.answer().as_ref().map(assignment::grade);
+                if let Some(grade) = problem.grade {
+                    model.stats.record_graded(grade == Grade::Correct);
+                    save_stats(&model.stats);
+                }
+            }
+            let all_correct = model.assignment.as_ref().map_or(false, |problems| {
+                problems
+                    .iter()
+                    .all(|problem| problem.grade == Some(Grade::Correct))
+            });
+            if model.race.is_some() && model.race_time.is_none() && all_correct {
+                model.race_time = Some((now() - model.race_started) / 1000.0);
+            }
+        }
+        Msg::UseExample(example) => {
+            navigate(model, Route::Page(Page::Calculator));
+            model.input = example;
+            model.result = None;
+            model.error = None;
+        }
+        Msg::SetWorksheetSize(size) => {
+            if let Ok(size) = size.parse() {
+                model.worksheet_size = size;
+            }
+        }
+        Msg::GenerateWorksheet => {
+            // Milliseconds since the page loaded are enough to vary the problems between worksheets
+            let seed = now();
+            model.worksheet = Some(worksheet::generate(model.worksheet_size, seed as u64));
+        }
+        Msg::PrintWorksheet => {
+            let _ = seed::window().print();
+        }
+        Msg::CloseWorksheet => model.worksheet = None,
+        Msg::HostRequest(HostRequest::Balance { equation, id }, origin) => {
+            // Without a known origin, results go back to the parent that asked for them
+            if let Some(embed) = &mut model.embed {
+                embed.origin.get_or_insert(origin);
+            }
+            model.host_connected = true;
+            model.host_request_id = id;
+            model.input = equation;
+            model.result = None;
+            order.send_msg(Msg::Balance);
+        }
+        Msg::ToggleStats => model.show_stats = !model.show_stats,
+        Msg::ToggleCompounds => model.show_compounds = !model.show_compounds,
+        Msg::ToggleFailures => model.show_failures = !model.show_failures,
+        Msg::ToggleFailureLogging => {
+            model.failures.enabled = !model.failures.enabled;
+            save_failures(&model.failures);
+        }
+        Msg::ClearFailures => {
+            model.failures.clear();
+            save_failures(&model.failures);
+        }
+        Msg::SetHalfPartner(index) => model.half_partner = index.parse().ok(),
+        Msg::SetConcentration(index, concentration) => {
+            model.solutions.entry(index).or_default().0 = concentration;
+        }
+        Msg::SetVolume(index, volume) => model.solutions.entry(index).or_default().1 = volume,
+        Msg::SetTemperature(temperature) => model.temperature = temperature,
+        Msg::SetLowestTemperature(temperature) => model.temperature_range.0 = temperature,
+        Msg::SetHighestTemperature(temperature) => model.temperature_range.1 = temperature,
+        Msg::SetAliasDraft(draft) => {
+            model.alias_draft = draft;
+            model.alias_invalid = false;
+        }
+        Msg::AddAlias => match Alias::parse(&model.alias_draft) {
+            Some(alias) => {
+                model.aliases.insert(alias);
+                model.alias_draft.clear();
+                save_aliases(&model.aliases);
+            }
+            None => model.alias_invalid = true,
+        },
+        Msg::RemoveAlias(name) => {
+            model.aliases.remove(&name);
+            save_aliases(&model.aliases);
+        }
+        Msg::SetParserConfig(config) => {
+            model.parser_config = config;
+            if let Ok(json) = serde_json::to_string(&config) {
+                save_item(PARSER_CONFIG_STORAGE_KEY, &json);
+            }
+        }
+        Msg::ToggleElementGroups => {
+            model.element_groups = !model.element_groups;
+            save_item(
+                ELEMENT_GROUPS_STORAGE_KEY,
+                &model.element_groups.to_string(),
+            );
+        }
+        Msg::ChooseReading(name, reading) => {
+            model.readings.insert(name, reading);
+            if model.error.is_some() {
+                order.send_msg(Msg::Balance);
+            }
+        }
+        Msg::SetTheme(id) => {
+            if let Some(theme) = Theme::find(&id) {
+                model.theme = theme;
+                apply_theme(theme);
+                save_item(THEME_STORAGE_KEY, theme.id);
+            }
+        }
+        Msg::CatalogIndexFetched(locales) => {
+            for locale in locales {
+                order.perform_cmd(fetch_catalog(locale));
+            }
+        }
+        Msg::CatalogFetched(catalog) => {
+            let preferred =
+                load_item(LOCALE_STORAGE_KEY).as_deref() == Some(catalog.locale.as_str());
+            let locale = catalog.locale.clone();
+            model.i18n.register(catalog);
+            if preferred {
+                model.i18n.set_locale(&locale);
+            }
+        }
+        Msg::DatasetFetched(dataset, json) => {
+            let loaded = json.map_or(false, |json| {
+                model.datasets.load_json(dataset, &json).is_ok()
+            });
+            if loaded {
+                model.dataset_loading.remove(&dataset);
+            } else {
+                model.dataset_loading.insert(dataset, Loading::Failed);
+            }
+        }
+        Msg::SessionLoaded(session) => {
+            // Equations balanced while the session was loading come after the saved ones
+            let recent = std::mem::take(&mut model.history);
+            let records: Vec<HistoryRecord> = session
+                .history
+                .into_iter()
+                .chain(recent.iter().map(HistoryEntry::to_record))
+                .collect();
+            restore_history(model, records);
+            model.session_saved_at = model.session_saved_at.max(session.saved_at);
+            // A link to an entry could not be followed before the history was there
+            if let Route::Entry(index) = current_route() {
+                order.send_msg(Msg::JumpToEntry(index));
+            }
+            order.send_msg(Msg::SyncNow);
+        }
+        Msg::SyncNow => {
+            if let Some(config) = model.sync.clone() {
+                model.sync_status = Some(SyncStatus::Syncing);
+                order.perform_cmd(pull_session(config));
+            }
+        }
+        Msg::SessionPulled(remote) => {
+            let local = current_session(model);
+            let newest = session::latest(local.clone(), remote.unwrap_or_default());
+            if newest == local {
+                // The endpoint is behind, so bring it up to date
+                save(model, order);
+            } else {
+                restore_history(model, newest.history);
+                model.session_saved_at = newest.saved_at;
+                model.sync_status = Some(SyncStatus::Synced);
+                order.perform_cmd(save_session(current_session(model).to_json(), None));
+            }
+        }
+        Msg::SyncFinished(synced) => {
+            model.sync_status = Some(if synced {
+                SyncStatus::Synced
+            } else {
+                SyncStatus::Failed
+            });
+        }
+        Msg::ExportSession => model.session_export = Some(current_session(model).to_json()),
+        Msg::GalleryFetched(session) => {
+            model.gallery = Some(match session {
+                Some(session) => Gallery::Loaded(
+                    session
+                        .history
+                        .into_iter()
+                        .enumerate()
+                        .filter_map(|(index, record)| {
+                            HistoryEntry::from_record(index, record, &model.output_style)
+                        })
+                        .collect(),
+                ),
+                None => Gallery::Failed,
+            });
+        }
+        Msg::StopSync => {
+            model.sync = None;
+            model.sync_status = None;
+            remove_item(SYNC_STORAGE_KEY);
+        }
+        Msg::AcceptSync => {
+            if let Some(config) = model.offered_sync.take() {
+                if let Ok(json) = serde_json::to_string(&config) {
+                    save_item(SYNC_STORAGE_KEY, &json);
+                }
+                model.sync = Some(config);
+                order.send_msg(Msg::SyncNow);
+            }
+        }
+        Msg::DeclineSync => model.offered_sync = None,
+        Msg::SetLocale(locale) => {
+            if model.i18n.set_locale(&locale) {
+                save_item(LOCALE_STORAGE_KEY, &locale);
+            }
+        }
+        Msg::SetTextSize(size) => {
+            model.text_size = size;
+            apply_text_size(size);
+            save_item(TEXT_SIZE_STORAGE_KEY, size.class());
+        }
+        Msg::SetSolver(id) => {
+            if let Some(strategy) = SolverStrategy::from_id(&id) {
+                model.balancer.set_strategy(strategy);
+                model.solve_time = None;
+            }
+        }
+        Msg::AddToCorpus => {
+            if let Some(reaction) = &model.reaction {
+                model.corpus_additions.push(corpus::entry_line(reaction));
+            }
+        }
+        Msg::SetAssignmentDraft(draft) => model.assignment_draft = draft,
+        Msg::CreateRace => {
+            model.race_link = Some(Race {
+                // Milliseconds since the page loaded are enough to vary the races
+                seed: now() as u64,
+                count: RACE_SIZE,
+            });
+        }
+        Msg::StartRecording => {
+            stop_playback(model);
+            model.recording = Recording::default();
+            model.recording_started = Some(now());
+        }
+        Msg::StopRecording => {
+            model.recording_started = None;
+            if let Ok(json) = serde_json::to_string(&model.recording) {
+                save_item(RECORDING_STORAGE_KEY, &json);
+            }
+        }
+        Msg::StartPlayback => {
+            model.recording_started = None;
+            stop_playback(model);
+            if let Some(delay) = model.recording.delay_before(0) {
+                model.playback = Some(0);
+                let generation = model.playback_generation;
+                order.perform_cmd(cmds::timeout(delay, move || Msg::PlaybackStep(generation)));
+            }
+        }
+        Msg::StopPlayback => stop_playback(model),
+        Msg::PlaybackStep(generation) => {
+            let index = match model.playback {
+                Some(index) if generation == model.playback_generation => index,
+                _ => {
+                    order.skip();
+                    return;
+                }
+            };
+            if let Some(step) = model.recording.steps.get(index) {
+                order.send_msg(action_msg(step.action.clone()));
+            }
+            model.playback = match model.recording.delay_before(index + 1) {
+                Some(delay) => {
+                    order.perform_cmd(cmds::timeout(delay, move || Msg::PlaybackStep(generation)));
+                    Some(index + 1)
+                }
+                None => None,
+            };
+        }
+        Msg::OpenPalette => {
+            model.palette = Some(Palette::default());
+            let input = model.palette_ref.clone();
+            order.after_next_render(move |_| {
+                if let Some(input) = input.get() {
+                    let _ = input.focus();
+                }
+            });
+        }
+        Msg::ClosePalette => model.palette = None,
+        Msg::SetPaletteQuery(query) => {
+            if let Some(palette) = &mut model.palette {
+                palette.query = query;
+                palette.selected = 0;
+            }
+        }
+        Msg::PaletteKeyDown(key) => {
+            let count = palette_matches(model).len();
+            if let Some(palette) = &mut model.palette {
+                match key.as_str() {
+                    "ArrowDown" => {
+                        palette.selected = (palette.selected + 1).min(count.saturating_sub(1))
+                    }
+                    "ArrowUp" => palette.selected = palette.selected.saturating_sub(1),
+                    "Enter" => {
+                        order.send_msg(Msg::RunCommand(palette.selected));
+                    }
+                    "Escape" => {
+                        order.send_msg(Msg::ClosePalette);
+                    }
+                    _ => {
+                        order.skip();
+                    }
+                }
+            }
+        }
+        Msg::RunCommand(index) => {
+            let command = palette_matches(model).into_iter().nth(index);
+            model.palette = None;
+            if let Some((_, msg)) = command {
+                if let Some(page) = msg_page(&msg) {
+                    navigate(model, Route::Page(page));
+                }
+                order.send_msg(msg);
+            }
+        }
+        Msg::SelectHistoryEntry(index, range) => {
+            match model.history_anchor.filter(|_| range) {
+                Some(anchor) => model
+                    .history_selection
+                    .extend(anchor.min(index)..=anchor.max(index)),
+                None => {
+                    if !model.history_selection.remove(&index) {
+                        model.history_selection.insert(index);
+                    }
+                }
+            }
+            model.history_anchor = Some(index);
+        }
+        Msg::ClearHistorySelection => {
+            model.history_selection.clear();
+            model.history_anchor = None;
+        }
+        Msg::DeleteSelected => {
+            let records = model
+                .history
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !model.history_selection.contains(index))
+                .map(|(_, entry)| entry.to_record())
+                .collect();
+            restore_history(model, records);
+            model.session_saved_at = js_sys::Date::now();
+            save(model, order);
+        }
+        Msg::ExportSelected => {
+            let session = Session {
+                saved_at: model.session_saved_at,
+                history: selected_entries(model)
+                    .map(|(_, entry)| entry.to_record())
+                    .collect(),
+                ..Session::default()
+            };
+            model.session_export = Some(session.to_json());
+        }
+        Msg::CombineSelected => {
+            let (indices, steps): (Vec<usize>, Vec<&Reaction>) = selected_entries(model)
+                .map(|(index, entry)| (index, &entry.reaction))
+                .unzip();
+            let overall = overall_reaction(&steps);
+            model.combined = Some((indices, overall));
+        }
+        Msg::CloseCombined => model.combined = None,
+        Msg::WorksheetFromSelected => {
+            navigate(model, Route::Page(Page::Tools));
+            let problems: Vec<Problem> = selected_entries(model)
+                .map(|(_, entry)| Problem {
+                    note: entry.note.clone(),
+                    ..Problem::from_reaction(&entry.reaction)
+                })
+                .collect();
+            model
+                .worksheet
+                .get_or_insert_with(Vec::new)
+                .extend(problems);
+        }
+        Msg::SetTagDraft(draft) => model.tag_draft = draft,
+        Msg::TagSelected => {
+            let tags = parse_tags(&model.tag_draft);
+            if tags.is_empty() {
+                return;
+            }
+            for &index in &model.history_selection {
+                if let Some(entry) = model.history.get_mut(index) {
+                    add_tags(&mut entry.tags, tags.iter().cloned());
+                }
+            }
+            model.tag_draft.clear();
+            model.session_export = None;
+            model.session_saved_at = js_sys::Date::now();
+            save(model, order);
+        }
+        Msg::RemoveTag(index, tag) => {
+            if let Some(entry) = model.history.get_mut(index) {
+                entry.tags.retain(|other| *other != tag);
+            }
+            if !model.history.iter().any(|entry| entry.tags.contains(&tag)) {
+                model.tag_filter = model.tag_filter.take().filter(|filter| *filter != tag);
+            }
+            model.session_export = None;
+            model.session_saved_at = js_sys::Date::now();
+            save(model, order);
+        }
+        Msg::SetTagFilter(tag) => model.tag_filter = Some(tag).filter(|tag| !tag.is_empty()),
+        Msg::EditNote(index) => {
+            let note = model
+                .history
+                .get(index)
+                .map(|entry| entry.note.clone())
+                .unwrap_or_default();
+            model.note_editing = Some((index, note));
+        }
+        Msg::SetNoteDraft(draft) => {
+            if let Some((_, note)) = &mut model.note_editing {
+                *note = draft;
+            }
+        }
+        Msg::SaveNote => {
+            if let Some((index, note)) = model.note_editing.take() {
+                if let Some(entry) = model.history.get_mut(index) {
+                    entry.note = note.trim().to_string();
+                }
+                model.session_export = None;
+                model.session_saved_at = js_sys::Date::now();
+                save(model, order);
+            }
+        }
+        Msg::CancelNote => model.note_editing = None,
+        Msg::Tool(index, msg) => {
+            if let Some(tool) = model.tools.get_mut(index) {
+                tool.update(msg);
+            }
+        }
+        Msg::Navigate(route) => {
+            navigate(model, route);
+            if model.page == Page::Tools {
+                request_dataset(model, order, Dataset::DissociationConstants);
+            }
+            if let Route::Entry(index) = route {
+                order.send_msg(Msg::JumpToEntry(index));
+            }
+        }
+        Msg::JumpToEntry(index) => {
+            let entry = match model.history.get(index) {
+                Some(entry) => entry,
+                None => return,
+            };
+            if !is_shown(model, entry) {
+                model.tag_filter = None;
+            }
+            model.page = Page::History;
+            // Render enough of the newest entries to reach it
+            let newer = model
+                .history
+                .iter()
+                .skip(index)
+                .filter(|entry| is_shown(model, entry))
+                .count();
+            model.history_shown = model.history_shown.max(newer);
+            model.highlighted = Some(index);
+            order.after_next_render(move |_| {
+                if let Some(element) = seed::document().get_element_by_id(&entry_id(index)) {
+                    scroll_into_view(&element);
+                }
+            });
+        }
+        Msg::DraftFromHistory => {
+            let equations: Vec<String> = model
+                .history
+                .iter()
+                .map(|entry| {
+                    let mut unbalanced = entry.reaction.clone();
+                    unbalanced.coefficients = vec![1; unbalanced.coefficients.len()];
+                    unbalanced.to_string()
+                })
+                .collect();
+            model.assignment_draft = equations.join("\n");
+        }
+        Msg::CloseTemplate => model.template = None,
+        Msg::SelectBlank(index) => model.selected_blank = index,
+        Msg::FillBlank(species) => {
+            if let Some(value) = model.template_values.get_mut(model.selected_blank) {
+                *value = Some(species);
+            }
+            // Move on to the next empty blank so that tapping species alone fills the template
+            if let Some(next) = model.template_values.iter().position(Option::is_none) {
+                model.selected_blank = next;
+            }
+            let equation = model
+                .template
+                .as_ref()
+                .and_then(|template| template.fill(&model.template_values));
+            if let Some(equation) = equation {
+                model.input = equation;
+                model.template = None;
+                order.send_msg(Msg::Balance);
+            }
+        }
+    }
+    // The input changes in many places, so its tokens follow it here
+    model.tokens.update(&model.input);
+}
+
+/// Every action of the command palette with its label.
+fn commands(model: &Model) -> Vec<(String, Msg)> {
+    let i18n = &model.i18n;
+    let recording = if model.recording_started.is_some() {
+        ("recording.stop", Msg::StopRecording)
+    } else {
+        ("recording.start", Msg::StartRecording)
+    };
+    let mut commands: Vec<(String, Msg)> = vec![
+        ("palette.balance", Msg::Balance),
+        ("palette.tidy", Msg::Tidy),
+        ("palette.swap_sides", Msg::SwapSides),
+        ("palette.practice", Msg::StartPractice),
+        ("palette.network", Msg::ToggleNetwork),
+        ("palette.stats", Msg::ToggleStats),
+        ("palette.compounds", Msg::ToggleCompounds),
+        ("palette.failures", Msg::ToggleFailures),
+        ("palette.worksheet", Msg::GenerateWorksheet),
+        ("palette.race", Msg::CreateRace),
+        ("palette.export_session", Msg::ExportSession),
+        ("palette.auto_close", Msg::ToggleAutoClose),
+        recording,
+        ("recording.play", Msg::StartPlayback),
+    ]
+    .into_iter()
+    .map(|(key, msg)| (i18n.t(key).to_string(), msg))
+    .collect();
+    for theme in THEMES.iter() {
+        let name = i18n
+            .get(&format!("theme.{}", theme.id))
+            .unwrap_or(theme.name);
+        commands.push((
+            i18n.format("palette.theme", &[&name]),
+            Msg::SetTheme(theme.id.to_string()),
+        ));
+    }
+    for &page in Page::ALL.iter() {
+        commands.push((
+            i18n.format("palette.go_to", &[&i18n.t(page.message_key())]),
+            Msg::Navigate(Route::Page(page)),
+        ));
+    }
+    for &size in TextSize::ALL.iter() {
+        commands.push((
+            i18n.format("palette.text_size", &[&i18n.t(size.message_key())]),
+            Msg::SetTextSize(size),
+        ));
+    }
+    commands
+}
+
+/// Page showing what a command of the palette does, gone to when it runs.
+fn msg_page(msg: &Msg) -> Option<Page> {
+    match msg {
+        Msg::Balance | Msg::Tidy | Msg::SwapSides | Msg::StartPractice | Msg::CreateRace => {
+            Some(Page::Calculator)
+        }
+        Msg::ToggleNetwork | Msg::ExportSession => Some(Page::History),
+        Msg::ToggleStats
+        | Msg::ToggleCompounds
+        | Msg::ToggleFailures
+        | Msg::GenerateWorksheet
+        | Msg::StartRecording
+        | Msg::StopRecording
+        | Msg::StartPlayback => Some(Page::Tools),
+        _ => None,
+    }
+}
+
+/// Commands matching the search of the open palette, the best match first.
+fn palette_matches(model: &Model) -> Vec<(String, Msg)> {
+    match &model.palette {
+        Some(palette) => palette::rank(&palette.query, commands(model)),
+        None => Vec::new(),
+    }
+}
+
+/// Opens the command palette on Ctrl+P, or ⌘P on a Mac, instead of printing.
+fn palette_shortcut_handler(event: web_sys::Event) -> Option<Msg> {
+    let event = event.dyn_ref::<web_sys::KeyboardEvent>()?;
+    if (event.ctrl_key() || event.meta_key()) && event.key().eq_ignore_ascii_case("p") {
+        event.prevent_default();
+        return Some(Msg::OpenPalette);
+    }
+    None
+}
+
+/// Ends any replay, so that its pending steps are dropped.
+fn stop_playback(model: &mut Model) {
+    model.playback = None;
+    model.playback_generation += 1;
+}
+
+/// The message that replays a recorded action.
+fn action_msg(action: Action) -> Msg {
+    match action {
+        Action::SetInput(input) => Msg::SetInput(input),
+        Action::SetConditions(conditions) => Msg::SetConditions(conditions),
+        Action::Balance => Msg::Balance,
+        Action::Reset => Msg::Reset,
+    }
+}
+
+/// The input with the conditions field as its `;` clause, unless it already has one.
+fn full_input(model: &Model) -> String {
+    if model.input.contains(';') || model.conditions.trim().is_empty() {
+        model.input.clone()
+    } else {
+        format!("{}; {}", model.input, model.conditions)
+    }
+}
+
+fn load_item(key: &str) -> Option<String> {
+    let storage = seed::window().local_storage().ok()??;
+    storage.get_item(key).ok()?
+}
+
+/// Milliseconds since the page loaded, or 0 where timing is unavailable.
+fn now() -> f64 {
+    seed::window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
+
+/// Stored settings are best effort, so a full or disabled storage is ignored.
+fn save_item(key: &str, value: &str) {
+    if let Ok(Some(storage)) = seed::window().local_storage() {
+        let _ = storage.set_item(key, value);
+    }
+}
+
+fn remove_item(key: &str) {
+    if let Ok(Some(storage)) = seed::window().local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
+
+fn load_stats() -> Option<Stats> {
+    serde_json::from_str(&load_item(STATS_STORAGE_KEY)?).ok()
+}
+
+fn save_stats(stats: &Stats) {
+    if let Ok(json) = serde_json::to_string(stats) {
+        save_item(STATS_STORAGE_KEY, &json);
+    }
+}
+
+fn load_failures() -> Option<FailureLog> {
+    serde_json::from_str(&load_item(FAILURE_LOG_STORAGE_KEY)?).ok()
+}
+
+fn save_failures(failures: &FailureLog) {
+    if let Ok(json) = serde_json::to_string(failures) {
+        save_item(FAILURE_LOG_STORAGE_KEY, &json);
+    }
+}
+
+/// The user's aliases with the built-in organic groups, which the parser
+/// leaves out unless `parser_config` allows them, and the readings chosen so far.
+fn parser_aliases(model: &Model) -> Aliases {
+    let mut aliases = model.aliases.with_organic_groups(model.element_groups);
+    aliases.readings = model.readings.clone();
+    aliases
+}
+
+fn load_aliases() -> Option<Aliases> {
+    serde_json::from_str(&load_item(ALIASES_STORAGE_KEY)?).ok()
+}
+
+fn save_aliases(aliases: &Aliases) {
+    if let Ok(json) = serde_json::to_string(aliases) {
+        save_item(ALIASES_STORAGE_KEY, &json);
+    }
+}
+
+/// Exposes the theme colors to the stylesheet as custom properties on the root element.
+fn apply_theme(theme: &Theme) {
+    if let Some(root) = seed::document().document_element() {
+        let _ = root.set_attribute("style", &theme.css_variables());
+    }
+}
+
+/// Turns a `message` event carrying a JSON `HostRequest` into a message, ignoring anything else posted to the window,
+/// and anything not sent by the parent frame from `origin`.
+fn host_request_handler(event: web_sys::Event, origin: Option<&str>) -> Option<Msg> {
+    let event = event.dyn_ref::<web_sys::MessageEvent>()?;
+    let parent = seed::window().parent().ok()??;
+    if !js_sys::Object::is(event.source()?.as_ref(), parent.as_ref()) {
+        return None;
+    }
+    if origin.map_or(false, |origin| origin != event.origin()) {
+        return None;
+    }
+    let data = event.data().as_string()?;
+    let request = HostRequest::from_json(&data).ok()?;
+    Some(Msg::HostRequest(request, event.origin()))
+}
+
+fn post_to_parent(message: &HostMessage, origin: &str) {
+    if let Ok(Some(parent)) = seed::window().parent() {
+        let _ = parent.post_message(&JsValue::from_str(&message.to_json()), origin);
+    }
+}
+
+async fn fetch_catalog_index() -> Option<Msg> {
+    let response = fetch(CATALOG_INDEX_URL).await.ok()?.check_status().ok()?;
+    Some(Msg::CatalogIndexFetched(response.json().await.ok()?))
+}
+
+/// Catalogs that fail to load are skipped, leaving their language unavailable.
+async fn fetch_catalog(locale: String) -> Option<Msg> {
+    let url = format!("locales/{}.json", locale);
+    let response = fetch(url).await.ok()?.check_status().ok()?;
+    Some(Msg::CatalogFetched(response.json().await.ok()?))
+}
+
+/// Starts loading a dataset the first time it is needed.
+fn request_dataset(model: &mut Model, order: &mut impl Orders<Msg>, dataset: Dataset) {
+    if model.datasets.is_loaded(dataset) || model.dataset_loading.contains_key(&dataset) {
+        return;
+    }
+    model.dataset_loading.insert(dataset, Loading::Pending);
+    order.perform_cmd(load_dataset(dataset));
+}
+
+/// Reads a dataset from the IndexedDB cache, fetching and caching it when missing.
+async fn load_dataset(dataset: Dataset) -> Option<Msg> {
+    if let Some(json) = IndexedDb.load(idb::DATASETS, dataset.id()).await {
+        return Some(Msg::DatasetFetched(dataset, Some(json)));
+    }
+    let json = fetch_dataset(dataset).await;
+    if let Some(json) = &json {
+        // Caching is best effort, the dataset is usable either way
+        let _ = IndexedDb.save(idb::DATASETS, dataset.id(), json).await;
+    }
+    Some(Msg::DatasetFetched(dataset, json))
+}
+
+/// Replaces the history with saved records, skipping any that no longer parse.
+fn restore_history(model: &mut Model, records: Vec<HistoryRecord>) {
+    model.history.clear();
+    model.session_export = None;
+    // Indices no longer point at the same entries
+    model.duplicate_of = None;
+    model.highlighted = None;
+    model.history_selection.clear();
+    model.history_anchor = None;
+    model.combined = None;
+    for record in records {
+        if let Some(entry) =
+            HistoryEntry::from_record(model.history.len(), record, &model.output_style)
+        {
+            model.history.push(entry);
+        }
+    }
+}
+
+/// Selected history entries with their indices, oldest first.
+fn selected_entries(model: &Model) -> impl Iterator<Item = (usize, &HistoryEntry)> + '_ {
+    model
+        .history_selection
+        .iter()
+        .filter_map(move |&index| Some((index, model.history.get(index)?)))
+}
+
+fn current_session(model: &Model) -> Session {
+    Session {
+        saved_at: model.session_saved_at,
+        history: model.history.iter().map(HistoryEntry::to_record).collect(),
+        ..Session::default()
+    }
+}
+
+/// Saves the session on the device and, when syncing, to the endpoint.
+fn save(model: &mut Model, order: &mut impl Orders<Msg>) {
+    if model.sync.is_some() {
+        model.sync_status = Some(SyncStatus::Syncing);
+    }
+    order.perform_cmd(save_session(
+        current_session(model).to_json(),
+        model.sync.clone(),
+    ));
+}
+
+/// An unreadable or newer session is read as empty, and replaced on the next balance.
+async fn load_session() -> Option<Msg> {
+    let json = match IndexedDb.load(idb::SESSIONS, idb::CURRENT_SESSION).await {
+        Some(json) => Some(json),
+        None => LocalStorage.load(idb::SESSIONS, idb::CURRENT_SESSION).await,
+    };
+    let session = json.and_then(|json| Session::from_json(&json).ok());
+    Some(Msg::SessionLoaded(session.unwrap_or_default()))
+}
+
+/// Falls back to local storage where IndexedDB is unavailable, as in some
+/// private browsing modes. A failure of both only loses history on the next visit.
+async fn save_session(json: String, sync: Option<SyncConfig>) -> Option<Msg> {
+    if IndexedDb
+        .save(idb::SESSIONS, idb::CURRENT_SESSION, &json)
+        .await
+        .is_err()
+    {
+        let _ = LocalStorage
+            .save(idb::SESSIONS, idb::CURRENT_SESSION, &json)
+            .await;
+    }
+    let config = sync?;
+    Some(Msg::SyncFinished(
+        push_session(&config, json).await.is_some(),
+    ))
+}
+
+async fn fetch_gallery(url: String) -> Option<Msg> {
+    let fetched = async {
+        let response = fetch(url).await.ok()?.check_status().ok()?;
+        Session::from_json(&response.text().await.ok()?).ok()
+    };
+    Some(Msg::GalleryFetched(fetched.await))
+}
+
+fn sync_request(config: &SyncConfig) -> Request {
+    let request = Request::new(config.url.as_str());
+    if config.token.is_empty() {
+        request
+    } else {
+        request.header(Header::bearer(&config.token))
+    }
+}
+
+/// The endpoint answers 404 until a session is first pushed to it.
+async fn pull_session(config: SyncConfig) -> Option<Msg> {
+    let pulled = async {
+        let response = fetch(sync_request(&config)).await.ok()?;
+        if response.status().code == 404 {
+            return Some(None);
+        }
+        let json = response.check_status().ok()?.text().await.ok()?;
+        Session::from_json(&json).ok().map(Some)
+    };
+    Some(match pulled.await {
+        Some(remote) => Msg::SessionPulled(remote),
+        None => Msg::SyncFinished(false),
+    })
+}
+
+async fn push_session(config: &SyncConfig, json: String) -> Option<()> {
+    let request = sync_request(config)
+        .method(Method::Put)
+        .header(Header::content_type("application/json"))
+        .text(json);
+    fetch(request).await.ok()?.check_status().ok()?;
+    Some(())
+}
+
+async fn fetch_dataset(dataset: Dataset) -> Option<String> {
+    let response = fetch(dataset.url()).await.ok()?.check_status().ok()?;
+    response.text().await.ok()
+}
+
+fn apply_text_size(size: TextSize) {
+    if let Some(root) = seed::document().document_element() {
+        root.set_class_name(size.class());
+    }
+}
+
+impl FormattedChemical {
+    fn node(&self) -> Node<Msg> {
+        self.styled_node(&OutputStyle::default())
+    }
+
+    /// Renders the part with its coefficients and scripts in `style`.
+    fn styled_node(&self, style: &OutputStyle) -> Node<Msg> {
+        match self {
+            FormattedChemical::Coefficient(_, coefficient) => {
+                let written = style.coefficients.write(*coefficient);
+                if written.is_empty() {
+                    empty![]
+                } else if style.coefficients == CoefficientStyle::Bold {
+                    b! { written }
+                } else {
+                    Node::new_text(written)
+                }
+            }
+            FormattedChemical::Text(s) => Node::new_text(s.clone()),
+            FormattedChemical::Sub(s) => match style.scripts {
+                ScriptStyle::Markup => sub! { s },
+                ScriptStyle::Unicode => {
+                    Node::new_text(s.chars().map(subscript).collect::<String>())
+                }
+            },
+            FormattedChemical::Sup(s) => match style.scripts {
+                ScriptStyle::Markup => sup! { s },
+                ScriptStyle::Unicode => {
+                    Node::new_text(s.chars().map(superscript).collect::<String>())
+                }
+            },
+            FormattedChemical::Fragment(inner) => span! {
+                class!["fragment"],
+                inner.iter().map(|part| part.styled_node(style))
+            },
+            FormattedChemical::Species(_, inner) => span! {
+                class!["species"],
+                inner.iter().map(|part| part.styled_node(style))
+            },
+            FormattedChemical::Arrow(None, None) => Node::new_text(" = "),
+            FormattedChemical::Arrow(over, under) => span! {
+                class!["arrow"],
+                small! { over.clone().unwrap_or_default() },
+                span! { "=" },
+                small! { under.clone().unwrap_or_default() },
+            },
+        }
+    }
+
+    /// Renders the current result, where species are selectable and
+    /// coefficients of species from `revealed` onwards are still hidden.
+    fn result_node(&self, revealed: usize, style: &OutputStyle) -> Node<Msg> {
+        match self {
+            FormattedChemical::Coefficient(index, _) if *index >= revealed => {
+                b! { class!["pending"], "?" }
+            }
+            FormattedChemical::Species(index, inner) => {
+                let index = *index;
+                span![
+                    class!["species", "selectable"],
+                    inner.iter().map(|part| part.styled_node(style)),
+                    ev(Ev::Click, move |event| {
+                        event.stop_propagation();
+                        Msg::SelectSpecies(index)
+                    }),
+                ]
+            }
+            _ => self.styled_node(style),
+        }
+    }
+}
+
+fn how_to_view(model: &Model) -> Node<Msg> {
+    const BASICS: [&str; 3] = ["how_to.equation", "how_to.method", "how_to.state"];
+    const SYNTAX: [(&str, &str); 9] = [
+        ("how_to.syntax.formula", "H2O"),
+        ("how_to.syntax.parentheses", "Ca(OH)2"),
+        ("how_to.syntax.complex", "K3[Fe(CN)6] + [Cu(NH3)4]2+"),
+        ("how_to.syntax.condensed", "CH3CH2OH"),
+        ("how_to.syntax.hydrate", "CuSO4·5H2O"),
+        ("how_to.syntax.charge", "Fe^3+ + SO4^2-"),
+        ("how_to.syntax.electron", "Fe = Fe^3+ + e-"),
+        ("how_to.smiles", "smiles:CCO"),
+        ("how_to.catalyst", "2H2O2 =[MnO2]= 2H2O + O2"),
+        ("how_to.conditions", "CaCO3 = CaO + CO2; Δ"),
+    ];
+    const EXAMPLES: [&str; 5] = [
+        "H2 + O2 = H2O",
+        "CH4 + O2 = CO2 + H2O",
+        "Fe2O3 + CO = Fe + CO2",
+        "KMnO4 + HCl = KCl + MnCl2 + H2O + Cl2",
+        "N2 + H2 =[Fe]= NH3; 450°C, 200 atm",
+    ];
+    const FEATURES: [&str; 2] = ["how_to.stats", "how_to.template"];
+    const TROUBLES: [(&str, &str); 3] = [
+        ("how_to.trouble.formula", "how_to.trouble.formula.fix"),
+        ("how_to.trouble.elements", "how_to.trouble.elements.fix"),
+        ("how_to.trouble.infinite", "how_to.trouble.infinite.fix"),
+    ];
+
+    let lines = |keys: &[&str]| {
+        let mut lines = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            if index > 0 {
+                lines.push(br! {});
+            }
+            lines.extend(glossary_text(model, model.i18n.t(key)));
+        }
+        lines
+    };
+    header! {
+        attrs! {
+            At::Id => "how-to",
+        },
+        h1! { model.i18n.t("how_to.title") },
+        p! { lines(&BASICS[..]) },
+        details! {
+            summary! { model.i18n.t("how_to.syntax") },
+            table! {
+                SYNTAX.iter().map(|(key, example)| tr! {
+                    td! { glossary_text(model, model.i18n.t(key)) },
+                    td! { code! { *example } },
+                })
+            },
+        },
+        details! {
+            summary! { model.i18n.t("how_to.examples") },
+            div! {
+                class!["examples"],
+                EXAMPLES.iter().map(|&example| button! {
+                    example,
+                    simple_ev(Ev::Click, Msg::UseExample(example.into())),
+                })
+            },
+        },
+        details! {
+            summary! { model.i18n.t("how_to.features") },
+            p! { lines(&FEATURES[..]) },
+        },
+        details! {
+            summary! { model.i18n.t("how_to.troubleshooting") },
+            dl! {
+                TROUBLES.iter().flat_map(|(problem, fix)| vec![
+                    dt! { glossary_text(model, model.i18n.t(problem)) },
+                    dd! { glossary_text(model, model.i18n.t(fix)) },
+                ])
+            },
+        },
+        details! {
+            attrs! { At::Open => model.help_code.is_some().as_at_value() },
+            summary! { model.i18n.t("how_to.error_codes") },
+            dl! {
+                class!["error-codes"],
+                ERROR_CODES.iter().flat_map(|&(code, help)| vec![
+                    dt! {
+                        attrs! { At::Id => help_id(code) },
+                        class!["highlighted" => model.help_code == Some(code)],
+                        code,
+                    },
+                    dd! { glossary_text(model, model.i18n.t(help)) },
+                ])
+            },
+        },
+    }
+}
+
+/// Id of the help listed for an error code.
+fn help_id(code: &str) -> String {
+    format!("help-{}", code)
+}
+
+/// Takes over a paste only when cleaning changes the text, leaving plain pastes to the browser.
+/// Inserts text at the caret of the input, replacing any selection.
+fn insert_text(model: &mut Model, order: &mut impl Orders<Msg>, text: &str) {
+    editor::insert(&mut model.input, &mut model.selection, text);
+    restore_selection(model, order);
+}
+
+/// Puts the DOM selection back once the changed input has rendered, which
+/// would otherwise leave the caret at the end.
+fn restore_selection(model: &Model, order: &mut impl Orders<Msg>) {
+    let input = model.input_ref.clone();
+    let selection = model.selection;
+    order.after_next_render(move |_| {
+        if let Some(input) = input.get() {
+            let _ = input.focus();
+            let _ = input.set_selection_range(selection.start as u32, selection.end as u32);
+        }
+    });
+}
+
+fn input_selection(input: &web_sys::HtmlInputElement) -> Option<Selection> {
+    Some(Selection {
+        start: input.selection_start().ok()?? as usize,
+        end: input.selection_end().ok()?? as usize,
+    })
+}
+
+fn selection_handler(event: web_sys::Event) -> Option<Msg> {
+    let input = event
+        .target()?
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .ok()?;
+    input_selection(&input).map(Msg::SetSelection)
+}
+
+/// Brackets are typed through the model, so that `(` can wrap the selection
+/// or close itself. `Ctrl+Shift+S` swaps the sides of the equation.
+fn input_key_handler(event: web_sys::KeyboardEvent) -> Msg {
+    let key = event.key();
+    if event.ctrl_key() && event.shift_key() && key.eq_ignore_ascii_case("s") {
+        event.prevent_default();
+        return Msg::SwapSides;
+    }
+    let bracket = match key.as_str() {
+        "(" => Some('('),
+        ")" => Some(')'),
+        _ => None,
+    };
+    let selection = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+        .and_then(|input| input_selection(&input));
+    if let (Some(bracket), Some(selection)) = (bracket, selection) {
+        event.prevent_default();
+        return Msg::TypeBracket(bracket, selection);
+    }
+    Msg::InputKeyDown(key)
+}
+
+fn paste_handler(event: web_sys::Event) -> Option<Msg> {
+    let text = event
+        .dyn_ref::<web_sys::ClipboardEvent>()?
+        .clipboard_data()?
+        .get_data("text")
+        .ok()?;
+    let cleaned = clean_pasted(&text);
+    if cleaned == text.trim() {
+        return None;
+    }
+    event.prevent_default();
+    Some(Msg::PasteCleaned(cleaned))
+}
+
+/// Renders the text with glossary terms marked, explaining each on hover.
+fn glossary_text(model: &Model, text: &str) -> Vec<Node<Msg>> {
+    let term_key = |id: &str| format!("glossary.{}.term", id);
+    let terms: Vec<(&str, &str)> = GLOSSARY
+        .iter()
+        .map(|entry| {
+            (
+                entry.id,
+                model.i18n.get(&term_key(entry.id)).unwrap_or(entry.term),
+            )
+        })
+        .collect();
+    mark_terms(text, &terms)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => Node::new_text(text.to_string()),
+            Segment::Term(id, word) => {
+                let explanation = GLOSSARY.iter().find(|entry| entry.id == id).map(|entry| {
+                    model
+                        .i18n
+                        .get(&format!("glossary.{}", id))
+                        .unwrap_or(entry.explanation)
+                });
+                abbr![
+                    class!["term"],
+                    attrs! { At::Title => explanation.unwrap_or_default() },
+                    word,
+                ]
+            }
+        })
+        .collect()
+}
+
+fn input_view(model: &Model) -> Node<Msg> {
+    let locked = model.embed.as_ref().map_or(false, |embed| embed.locked);
+    let expression_view = if let Some(ref result) = model.result {
+        div![
+            id!["result"],
+            class!["result"],
+            el_ref(&model.result_ref),
+            result.iter().map(|formatted| {
+                formatted.result_node(model.revealed_coefficients, &model.output_style)
+            }),
+            simple_ev(Ev::Click, Msg::Reset),
+        ]
+    } else {
+        div![
+            class!["editor"],
+            input![
+                attrs! {
+                    At::Id => "expression-input",
+                    At::Name => "expression",
+                    At::Type => "text",
+                    At::Placeholder => "H2O = H2 + O2",
+                    At::Value => model.input,
+                    At::Custom("autofocus".into()) => "",
+                    At::ReadOnly => locked.as_at_value(),
+                },
+                el_ref(&model.input_ref),
+                keyboard_ev("keydown", input_key_handler),
+                input_ev(Ev::Input, Msg::SetInput),
+                ev(Ev::Input, selection_handler),
+                ev(Ev::KeyUp, selection_handler),
+                ev(Ev::Click, selection_handler),
+                ev(Ev::Select, selection_handler),
+                ev(Ev::Paste, paste_handler),
+            ],
+            input_overlay_view(model),
+        ]
+    };
+    let conditions_view = if model.result.is_none() && !locked {
+        input![
+            class!["conditions"],
+            attrs! {
+                At::Name => "conditions",
+                At::Type => "text",
+                At::Placeholder => "Δ, 450°C, 200 atm",
+                At::Value => model.conditions,
+            },
+            keyboard_ev("keydown", |ev| Msg::InputKeyDown(ev.key())),
+            input_ev(Ev::Input, Msg::SetConditions)
+        ]
+    } else {
+        empty![]
+    };
+
+    div![
+        class![
+            "expression",
+            "error" => model.error.is_some(),
+            "ok" => model.error.is_none() && model.result.is_some(),
+        ],
+        recent_elements_view(model),
+        expression_view,
+        conditions_view,
+        preview_view(model),
+        model.notice.map_or_else(
+            || empty![],
+            |key| label![class!["notice"], model.i18n.t(key)]
+        ),
+        duplicate_view(model),
+        warnings_view(model),
+        append_view(model),
+    ]
+}
+
+/// Points out that the result was already balanced, without stopping it
+/// from being added to the history.
+fn duplicate_view(model: &Model) -> Node<Msg> {
+    let index = match model.duplicate_of {
+        Some(index) => index,
+        None => return empty![],
+    };
+    label![
+        class!["notice"],
+        model
+            .i18n
+            .format("history.duplicate", &[&format!("Out[{}]", index)]),
+        button![
+            model.i18n.t("history.jump"),
+            simple_ev(Ev::Click, Msg::Navigate(Route::Entry(index))),
+        ],
+    ]
+}
+
+/// The elements used most, inserted at the caret with one tap.
+fn recent_elements_view(model: &Model) -> Node<Msg> {
+    let locked = model.embed.as_ref().map_or(false, |embed| embed.locked);
+    if model.result.is_some() || locked {
+        return empty![];
+    }
+    let elements = model.stats.top_elements(RECENT_ELEMENT_COUNT);
+    if elements.is_empty() {
+        return empty![];
+    }
+    div![
+        class!["recent-elements"],
+        attrs! { At::Title => model.i18n.t("input.recent_elements") },
+        elements.into_iter().map(|symbol| {
+            let text = symbol.to_string();
+            button![
+                symbol,
+                // Keeps the input focused so the caret stays where it was
+                ev(Ev::MouseDown, |event| event.prevent_default()),
+                simple_ev(Ev::Click, Msg::InsertText(text)),
+            ]
+        }),
+    ]
+}
+
+/// The input formatted as it will be read, shown while typing so mistakes
+/// are visible before balancing. Operators and bracket pairs are colored, and
+/// unknown elements and the formula a parse error points at are marked.
+/// Molar mass of the species under the caret, so a typo shows up as a mass
+/// that is obviously wrong, or as none at all.
+fn mass_badge_view(model: &Model) -> Node<Msg> {
+    let text = model.tokens.text();
+    if !model.selection.is_caret() {
+        return empty![];
+    }
+    let caret = model.selection.byte_range(text).end;
+    let formula = match token::species_at(model.tokens.tokens(), caret) {
+        Some(range) => &text[range],
+        None => return empty![],
+    };
+    let mass = parse_chemical_with_config(formula, &parser_aliases(model), &model.parser_config)
+        .and_then(|chemical| chemical.molar_mass());
+    span![
+        class!["mass-badge", "no-mass" => mass.is_none()],
+        attrs! { At::Title => formula },
+        match mass {
+            Some(mass) => format!("{} g/mol", model.i18n.number_format().format(mass, 2)),
+            None => "? g/mol".into(),
+        },
+    ]
+}
+
+fn preview_view(model: &Model) -> Node<Msg> {
+    if model.result.is_some() || model.input.trim().is_empty() {
+        return empty![];
+    }
+    let input = full_input(model);
+    let error_span =
+        parse_equation_with_config(&input, &parser_aliases(model), &model.parser_config)
+            .err()
+            .and_then(|error| error.span());
+    let text = model.tokens.text();
+    let tokens = model.tokens.tokens();
+    let brackets = token::brackets(text, tokens);
+    let nodes = tokens.iter().zip(&brackets).map(|(token, bracket)| {
+        let content = &text[token.range.clone()];
+        let invalid = error_span.as_ref().map_or(false, |span| {
+            span.start < token.range.end && token.range.start < span.end
+        });
+        match token.kind {
+            TokenKind::Count => sub![class!["invalid" => invalid], content],
+            TokenKind::Coefficient => b![class!["invalid" => invalid], content],
+            TokenKind::Element => span![
+                class![
+                    "element",
+                    "unknown" => find_element(content).is_none(),
+                    "invalid" => invalid,
+                ],
+                content,
+            ],
+            TokenKind::Open | TokenKind::Close => {
+                let bracket = bracket.expect("brackets are paired for every bracket token");
+                let depth = format!("depth-{}", bracket.depth % 3);
+                span![
+                    class![
+                        "bracket",
+                        depth.as_str(),
+                        "unmatched" => bracket.partner.is_none(),
+                    ],
+                    content,
+                ]
+            }
+            TokenKind::Plus | TokenKind::Arrow => span![class!["operator"], content],
+            _ => span![
+                class![token_class(token.kind), "invalid" => invalid],
+                content,
+            ],
+        }
+    });
+    // Conditions from their own field are not part of the tokenized input
+    let conditions = &input[model.input.len()..];
+    div![
+        class!["preview"],
+        nodes.collect::<Vec<_>>(),
+        if conditions.is_empty() {
+            empty![]
+        } else {
+            span![class!["conditions"], conditions]
+        },
+        mass_badge_view(model),
+        button![
+            class!["tidy"],
+            attrs! { At::Title => model.i18n.t("input.tidy_description") },
+            model.i18n.t("input.tidy"),
+            simple_ev(Ev::Click, Msg::Tidy),
+        ],
+        button![
+            class!["swap"],
+            attrs! { At::Title => model.i18n.t("input.swap_description") },
+            model.i18n.t("input.swap"),
+            simple_ev(Ev::Click, Msg::SwapSides),
+        ],
+        button![
+            class!["practice"],
+            attrs! { At::Title => model.i18n.t("practice.description") },
+            model.i18n.t("practice.start"),
+            simple_ev(Ev::Click, Msg::StartPractice),
+        ],
+    ]
+}
+
+fn token_class(kind: TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Element => "element",
+        TokenKind::Count => "count",
+        TokenKind::Coefficient => "coefficient",
+        TokenKind::Open => "open",
+        TokenKind::Close => "close",
+        TokenKind::Plus => "plus",
+        TokenKind::Arrow => "arrow",
+        TokenKind::Conditions => "conditions",
+        TokenKind::Space => "space",
+        TokenKind::Other => "other",
+    }
+}
+
+/// Draws the input's tokens over its transparent text, so counts show as
+/// subscripts while typing. Every glyph keeps its width to stay under the caret.
+fn input_overlay_view(model: &Model) -> Node<Msg> {
+    let text = model.tokens.text();
+    let tokens = model.tokens.tokens();
+    let matched = caret_bracket(model);
+    div![
+        class!["input-overlay"],
+        attrs! { At::Custom("aria-hidden".into()) => "true" },
+        tokens.iter().enumerate().map(|(index, token)| {
+            let content = &text[token.range.clone()];
+            if token.kind == TokenKind::Count {
+                sub![content]
+            } else {
+                span![
+                    class![
+                        token_class(token.kind),
+                        "matched" => matched.map_or(false, |(bracket, partner)| {
+                            index == bracket || index == partner
+                        }),
+                    ],
+                    content,
+                ]
+            }
+        }),
+    ]
+}
+
+/// The bracket next to the caret and its partner, as token indices, preferring
+/// the bracket before the caret.
+fn caret_bracket(model: &Model) -> Option<(usize, usize)> {
+    if !model.selection.is_caret() {
+        return None;
+    }
+    let text = model.tokens.text();
+    let tokens = model.tokens.tokens();
+    let caret = model.selection.byte_range(text).start;
+    let brackets = token::brackets(text, tokens);
+    let before = tokens.iter().position(|token| token.range.end == caret);
+    let after = tokens.iter().position(|token| token.range.start == caret);
+    before
+        .into_iter()
+        .chain(after)
+        .find_map(|index| Some((index, brackets[index]?.partner?)))
+}
+
+fn warnings_view(model: &Model) -> Node<Msg> {
+    if model.result.is_none() || (model.warnings.is_empty() && model.unwritten_charges.is_empty()) {
+        return empty![];
+    }
+    ul![
+        class!["warnings"],
+        model
+            .warnings
+            .iter()
+            .map(|warning| li![warning.localize(&model.i18n)]),
+        model
+            .unwritten_charges
+            .iter()
+            .map(|advice| li![advice.localize(&model.i18n)]),
+    ]
+}
+
+/// Adds a forgotten species to the current result and balances again.
+fn append_view(model: &Model) -> Node<Msg> {
+    if model.result.is_none() || model.embed.is_some() {
+        return empty![];
+    }
+    let side_option = |side: Side, value: &str, key: &str| {
+        option![
+            attrs! {
+                At::Value => value,
+                At::Selected => (model.append_side == side).as_at_value(),
+            },
+            model.i18n.t(key),
+        ]
+    };
+    div![
+        class!["append"],
+        select![
+            side_option(Side::Reagent, "reagent", "append.reagent"),
+            side_option(Side::Product, "product", "append.product"),
+            input_ev(Ev::Change, Msg::SetAppendSide),
+        ],
+        input![
+            attrs! {
+                At::Type => "text",
+                At::Placeholder => "H2O",
+                At::Value => model.append_formula,
+            },
+            input_ev(Ev::Input, Msg::SetAppendFormula),
+            keyboard_ev("keydown", |event| {
+                if event.key() == "Enter" {
+                    Some(Msg::AppendSpecies)
+                } else {
+                    None
+                }
+            }),
+        ],
+        button![
+            model.i18n.t("append.add"),
+            simple_ev(Ev::Click, Msg::AppendSpecies),
+        ],
+    ]
+}
+
+fn template_view(model: &Model) -> Node<Msg> {
+    let template = match &model.template {
+        Some(template) => template,
+        None => {
+            return div![
+                class!["templates"],
+                builtin_templates()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, template)| {
+                        let pattern: String = template
+                            .parts
+                            .iter()
+                            .map(|part| match part {
+                                TemplatePart::Text(text) => text.as_str(),
+                                TemplatePart::Blank(_) => "□",
+                            })
+                            .collect();
+                        button![pattern, simple_ev(Ev::Click, Msg::OpenTemplate(index))]
+                    }),
+            ]
+        }
+    };
+
+    let parts = template.parts.iter().map(|part| match part {
+        TemplatePart::Text(text) => {
+            span![format_chemicals(text).iter().map(FormattedChemical::node)]
+        }
+        TemplatePart::Blank(index) => button![
+            class!["blank", "selected" => *index == model.selected_blank],
+            match &model.template_values[*index] {
+                Some(value) => format_chemicals(value)
+                    .iter()
+                    .map(FormattedChemical::node)
+                    .collect(),
+                None => vec![Node::new_text("□")],
+            },
+            simple_ev(Ev::Click, Msg::SelectBlank(*index)),
+        ],
+    });
+    let choices = template.choices.iter().map(|choice| {
+        button![
+            format_chemicals(choice).iter().map(FormattedChemical::node),
+            simple_ev(Ev::Click, Msg::FillBlank(choice.clone())),
+        ]
+    });
+    div![
+        class!["template"],
+        div![class!["result"], parts],
+        div![class!["choices"], choices],
+        button![
+            model.i18n.t("common.close"),
+            simple_ev(Ev::Click, Msg::CloseTemplate)
+        ],
+    ]
+}
+
+fn diagram_view(model: &Model) -> Node<Msg> {
+    match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) if model.revealed_coefficients >= reaction.coefficients.len() => {
+            div![
+                class!["diagram"],
+                raw!(&particle_diagram(reaction, model.theme)),
+                raw!(&element_bar_chart(reaction, model.theme, &model.i18n)),
+                mass_balance_scale(
+                    reaction,
+                    model.theme,
+                    &model.i18n.number_format(),
+                    &model.i18n,
+                )
+                .map_or_else(Vec::new, |svg| raw!(&svg)),
+            ]
+        }
+        _ => empty![],
+    }
+}
+
+/// Table and bridge diagram of the oxidation state changes of a redox result.
+fn oxidation_view(model: &Model) -> Node<Msg> {
+    let (reaction, changes) = match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) => (reaction, oxidation_report(reaction).unwrap_or_default()),
+        _ => return empty![],
+    };
+    if changes.is_empty() {
+        return empty![];
+    }
+    let i18n = &model.i18n;
+    let table = table![
+        caption![
+            i18n.t("oxidation.title"),
+            match redox_kind(&changes) {
+                Some(kind) => span![class!["redox-kind"], i18n.t(kind.message_key())],
+                None => empty![],
+            },
+        ],
+        tr![
+            th![i18n.t("oxidation.element")],
+            th![i18n.t("oxidation.before")],
+            th![i18n.t("oxidation.after")],
+            th![i18n.t("oxidation.per_atom")],
+            th![i18n.t("oxidation.total")],
+        ],
+        changes.iter().map(|change| {
+            let key = if change.is_oxidation() {
+                "oxidation.loses"
+            } else {
+                "oxidation.gains"
+            };
+            tr![
+                class![if change.is_oxidation() {
+                    "oxidized"
+                } else {
+                    "reduced"
+                }],
+                th![change.element.clone()],
+                td![change.before.to_string()],
+                td![change.after.to_string()],
+                td![i18n.format(key, &[&change.electrons_per_atom().abs()])],
+                td![i18n.format(key, &[&change.electrons().abs()])],
+            ]
+        }),
+    ];
+    section![
+        id!["oxidation"],
+        table,
+        electron_transfer_diagram(reaction, &changes, i18n).map_or_else(Vec::new, |svg| raw!(&svg)),
+    ]
+}
+
+/// Charge carried by each side of an ionic result, species by species.
+fn charge_view(model: &Model) -> Node<Msg> {
+    let (reaction, tally) = match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) => match charge_tally(reaction) {
+            Some(tally) => (reaction, tally),
+            None => return empty![],
+        },
+        _ => return empty![],
+    };
+    let i18n = &model.i18n;
+    let conserved = tally.is_conserved();
+    section![
+        id!["charges"],
+        table![
+            caption![i18n.t("charges.title")],
+            tr![
+                th![i18n.t("charges.species")],
+                th![i18n.t("charges.reagents")],
+                th![i18n.t("charges.products")],
+            ],
+            tally.shares.iter().filter_map(|share| {
+                let chemical = reaction.species_at(share.index)?;
+                let cell = td![format!(
+                    "{} × ({}) = {}",
+                    share.coefficient,
+                    format_charge(share.charge),
+                    format_total(share.total())
+                )];
+                Some(tr![
+                    th![format_fragments(chemical)
+                        .iter()
+                        .map(FormattedChemical::node)],
+                    if share.side == Side::Reagent {
+                        vec![cell, td![]]
+                    } else {
+                        vec![td![], cell]
+                    },
+                ])
+            }),
+            tr![
+                class!["sum", "conserved" => conserved],
+                th![i18n.t("charges.total")],
+                td![format_total(tally.reagents)],
+                td![format_total(tally.products)],
+            ],
+        ],
+        p![
+            class!["status", "conserved" => conserved],
+            i18n.t(if conserved {
+                "charges.conserved"
+            } else {
+                "charges.not_conserved"
+            }),
+        ],
+    ]
+}
+
+/// Mole ratio of any two species of the result, e.g. `O₂ : H₂O = 1 : 2`.
+fn mole_ratio_view(model: &Model) -> Node<Msg> {
+    let reaction = match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) if model.revealed_coefficients >= reaction.coefficients.len() => {
+            reaction
+        }
+        _ => return empty![],
+    };
+    let (first, second) = model.mole_ratio;
+    let ratio = match reaction.mole_ratio(first, second) {
+        Some(ratio) if reaction.coefficients.len() >= 2 => ratio,
+        _ => return empty![],
+    };
+    let names: Vec<String> = reaction
+        .reagents
+        .iter()
+        .chain(&reaction.products)
+        .map(unicode_species)
+        .collect();
+    let species_select = |place: usize, selected: usize| {
+        select![
+            attrs! { At::Title => model.i18n.t("ratio.species") },
+            names.iter().enumerate().map(|(index, name)| {
+                option![
+                    attrs! {
+                        At::Value => index,
+                        At::Selected => (index == selected).as_at_value(),
+                    },
+                    name.as_str(),
+                ]
+            }),
+            input_ev(Ev::Change, move |index| Msg::SetRatioSpecies(place, index)),
+        ]
+    };
+    section![
+        id!["mole-ratio"],
+        h3![model.i18n.t("ratio.title")],
+        p![
+            species_select(0, first),
+            " : ",
+            species_select(1, second),
+            " = ",
+            strong![ratio.to_string()],
+        ],
+    ]
+}
+
+/// pH calculator for an acid–base result, from the concentration and volume
+/// of each acid and base mixed.
+fn ph_view(model: &Model) -> Node<Msg> {
+    let reaction = match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) if is_acid_base(reaction) => reaction,
+        _ => return empty![],
+    };
+    let i18n = &model.i18n;
+    let species: Vec<(usize, &Chemical)> = reaction
+        .reagents
+        .iter()
+        .enumerate()
+        .filter(|(_, chemical)| find_acid_base(chemical).is_some())
+        .collect();
+    let solutions: Option<Vec<Result<Solution, QuantityError>>> = species
+        .iter()
+        .map(|&(index, _)| {
+            let (concentration, volume) = model.solutions.get(&index)?;
+            if concentration.trim().is_empty() || volume.trim().is_empty() {
+                return None;
+            }
+            Some(typed_solution(index, concentration, volume))
+        })
+        .collect();
+    let solutions = solutions.map(|solutions| solutions.into_iter().collect::<Result<Vec<_>, _>>());
+    let result = match solutions
+        .map(|solutions| solutions.map(|solutions| mixture_ph(reaction, &solutions)))
+    {
+        Some(Err(error)) => p![class!["error"], error.localize(i18n)],
+        Some(Ok(Ok(ph))) => {
+            let numbers = i18n.number_format();
+            p![
+                class!["ph-result"],
+                i18n.format(
+                    "ph.result",
+                    &[&numbers.format(ph, 2), &numbers.format(14.0 - ph, 2)]
+                ),
+            ]
+        }
+        Some(Ok(Err(error))) => p![class!["error"], error.localize(i18n)],
+        None => p![i18n.t("ph.hint")],
+    };
+    section![
+        id!["ph"],
+        h3![i18n.t("ph.title")],
+        species.iter().map(|&(index, chemical)| {
+            let (concentration, volume) = model.solutions.get(&index).cloned().unwrap_or_default();
+            div![
+                code![chemical.display.clone()],
+                input![
+                    attrs! {
+                        At::Type => "text",
+                        At::Value => concentration,
+                        At::Placeholder => "0.1 M",
+                    },
+                    input_ev(Ev::Input, move |value| Msg::SetConcentration(index, value)),
+                ],
+                input![
+                    attrs! {
+                        At::Type => "text",
+                        At::Value => volume,
+                        At::Placeholder => "25 mL",
+                    },
+                    input_ev(Ev::Input, move |value| Msg::SetVolume(index, value)),
+                ],
+            ]
+        }),
+        result,
+    ]
+}
+
+/// A solution from its typed concentration and volume, in M and mL unless
+/// another unit is typed.
+fn typed_solution(
+    index: usize,
+    concentration: &str,
+    volume: &str,
+) -> Result<Solution, QuantityError> {
+    Ok(Solution {
+        species: index,
+        concentration: Quantity::parse_or(concentration, "M")?.value_in("M")?,
+        volume: Quantity::parse_or(volume, "mL")?.value_in("L")?,
+    })
+}
+
+/// A typed temperature in kelvin, in K unless another unit is typed, or
+/// `default` when it is missing or not above absolute zero.
+fn typed_temperature(typed: &str, default: f64) -> f64 {
+    Quantity::parse_or(typed, "K")
+        .and_then(|temperature| temperature.value_in("K"))
+        .ok()
+        .filter(|temperature| *temperature > 0.0)
+        .unwrap_or(default)
+}
+
+/// ΔH°, ΔS° and ΔG° of the result at the typed temperature, and when it is
+/// spontaneous.
+fn thermochemistry_view(model: &Model) -> Node<Msg> {
+    let reaction = match (&model.result, &model.reaction) {
+        (Some(_), Some(reaction)) => reaction,
+        _ => return empty![],
+    };
+    if !model.datasets.is_loaded(Dataset::Thermochemistry) {
+        return empty![];
+    }
+    let i18n = &model.i18n;
+    let numbers = i18n.number_format();
+    let thermo = match reaction_thermochemistry(reaction, &model.datasets) {
+        Ok(thermo) => thermo,
+        Err(missing) => {
+            return section![
+                id!["thermochemistry"],
+                h3![i18n.t("thermo.title")],
+                p![i18n.format("thermo.missing", &[&missing.join(", ")])],
+            ]
+        }
+    };
+    let temperature = typed_temperature(&model.temperature, STANDARD_TEMPERATURE);
+    let gibbs = thermo.gibbs_energy(temperature);
+    let spontaneity = thermo.spontaneity();
+    section![
+        id!["thermochemistry"],
+        h3![i18n.t("thermo.title")],
+        label![
+            i18n.t("thermo.temperature"),
+            input![
+                attrs! {
+                    At::Type => "text",
+                    At::Value => model.temperature,
+                    At::Placeholder => format!("{} K", STANDARD_TEMPERATURE),
+                },
+                input_ev(Ev::Input, Msg::SetTemperature),
+            ],
+        ],
+        table![
+            tr![
+                th!["ΔH°"],
+                td![format!("{} kJ/mol", numbers.format(thermo.enthalpy, 1))],
+            ],
+            tr![
+                th!["ΔS°"],
+                td![format!("{} J/(mol·K)", numbers.format(thermo.entropy, 1))],
+            ],
+            tr![
+                th!["ΔG°"],
+                td![format!("{} kJ/mol", numbers.format(gibbs, 1))],
+            ],
+        ],
+        p![
+            class!["spontaneity", "spontaneous" => spontaneity.at(temperature)],
+            i18n.t(if spontaneity.at(temperature) {
+                "thermo.spontaneous"
+            } else {
+                "thermo.not_spontaneous"
+            }),
+            " ",
+            spontaneity.localize(i18n),
+        ],
+        temperature_plot_view(model, &thermo),
+    ]
+}
+
+/// ΔG° and ln K over the typed temperature range, with the crossover marked.
+fn temperature_plot_view(model: &Model, thermo: &ReactionThermochemistry) -> Node<Msg> {
+    const SAMPLES: usize = 50;
+    let i18n = &model.i18n;
+    let lowest = typed_temperature(&model.temperature_range.0, PLOT_LOWEST_TEMPERATURE);
+    let highest = typed_temperature(&model.temperature_range.1, PLOT_HIGHEST_TEMPERATURE);
+    let temperatures: Vec<f64> = (0..=SAMPLES)
+        .map(|index| lowest + (highest - lowest) * index as f64 / SAMPLES as f64)
+        .collect();
+    let sample = |value: &dyn Fn(f64) -> f64| -> Vec<(f64, f64)> {
+        temperatures
+            .iter()
+            .map(|&temperature| (temperature, value(temperature)))
+            .collect()
+    };
+    let markers: Vec<f64> = thermo.crossover_temperature().into_iter().collect();
+    let numbers = i18n.number_format();
+    let gibbs = line_chart(
+        &[Series {
+            label: "ΔG°",
+            color: model.theme.reagent,
+            points: sample(&|temperature| thermo.gibbs_energy(temperature)),
+        }],
+        "T (K)",
+        "ΔG° (kJ/mol)",
+        &markers,
+        &numbers,
+    );
+    let ln_k = line_chart(
+        &[Series {
+            label: "ln K",
+            color: model.theme.product,
+            points: sample(&|temperature| thermo.ln_equilibrium_constant(temperature)),
+        }],
+        "T (K)",
+        "ln K",
+        &markers,
+        &numbers,
+    );
+    let range_input = |value: &str, placeholder: f64, msg: fn(String) -> Msg| {
+        input![
+            attrs! {
+                At::Type => "text",
+                At::Value => value,
+                At::Placeholder => format!("{} K", placeholder),
+            },
+            input_ev(Ev::Input, msg),
+        ]
+    };
+    div![
+        class!["temperature-plot"],
+        label![
+            i18n.t("thermo.range"),
+            range_input(
+                &model.temperature_range.0,
+                PLOT_LOWEST_TEMPERATURE,
+                Msg::SetLowestTemperature
+            ),
+            "–",
+            range_input(
+                &model.temperature_range.1,
+                PLOT_HIGHEST_TEMPERATURE,
+                Msg::SetHighestTemperature
+            ),
+        ],
+        gibbs.map_or_else(Vec::new, |svg| raw!(&svg)),
+        ln_k.map_or_else(Vec::new, |svg| raw!(&svg)),
+    ]
+}
+
+fn compound_name_view(model: &Model, chemical: &Chemical) -> Node<Msg> {
+    if let Some(name) = model.datasets.compound_name(&chemical.canonical_formula()) {
+        return p! { class!["compound-name"], name };
+    }
+    match model.dataset_loading.get(&Dataset::CompoundNames) {
+        Some(Loading::Pending) => progress! {
+            attrs! { At::Title => model.i18n.t("datasets.loading") },
+        },
+        Some(Loading::Failed) => p! { class!["compound-name"], model.i18n.t("datasets.offline") },
+        None => empty![],
+    }
+}
+
+fn species_panel_view(model: &Model) -> Node<Msg> {
+    let (index, chemical) = match (model.selected_species, &model.reaction) {
+        (Some(index), Some(reaction)) => (index, reaction.species_at(index).unwrap()),
+        _ => return empty![],
+    };
+
+    let charge = (chemical.charge != 0).then(|| {
+        p! { model.i18n.format("species.charge", &[&format_total(chemical.charge as i64)]) }
+    });
+    let phase = chemical.phase.map(|phase| {
+        p! { model.i18n.format("species.phase", &[&model.i18n.t(phase.message_key())]) }
+    });
+    let close =
+        button! { model.i18n.t("common.close"), simple_ev(Ev::Click, Msg::SelectSpecies(index)) };
+
+    // An electron has no elements to break down or look up
+    if chemical.is_electron() {
+        return aside! {
+            id! { "species-panel" },
+            h2! { format_fragments(chemical).iter().map(FormattedChemical::node) },
+            compound_name_view(model, chemical),
+            charge,
+            phase,
+            close,
+        };
+    }
+
+    let numbers = model.i18n.number_format();
+    let composition = if let Some(percents) = chemical.mass_percents() {
+        percents
+            .iter()
+            .map(|(name, percent)| {
+                tr! {
+                    td! { name },
+                    td! { chemical.parts[name].to_string() },
+                    td! { format!("{}%", numbers.format(*percent, 2)) },
+                }
+            })
+            .collect()
+    } else {
+        vec![tr! { td! {
+            attrs! { At::ColSpan => "3" },
+            model.i18n.t("species.unknown_element")
+        } }]
+    };
+    let molar_mass = chemical.molar_mass().map_or_else(
+        || model.i18n.t("species.molar_mass_unknown").to_string(),
+        |mass| {
+            model
+                .i18n
+                .format("species.molar_mass", &[&numbers.format(mass, 3)])
+        },
+    );
+    let unsaturation = chemical.degree_of_unsaturation().map(|degree| {
+        // Half-integer degrees only arise from odd atom counts, so one decimal is enough
+        let decimals = if degree.fract() == 0.0 { 0 } else { 1 };
+        p! { model.i18n.format("species.unsaturation", &[&numbers.format(degree, decimals)]) }
+    });
+    aside! {
+        id! { "species-panel" },
+        h2! { format_fragments(chemical).iter().map(FormattedChemical::node) },
+        compound_name_view(model, chemical),
+        charge,
+        phase,
+        table! {
+            tr! {
+                th! { model.i18n.t("species.element") },
+                th! { model.i18n.t("species.count") },
+                th! { model.i18n.t("species.mass_percent") },
+            },
+            composition,
+        },
+        p! { molar_mass },
+        unsaturation,
+        a! {
+            attrs! {
+                At::Href => format!(
+                    "https://pubchem.ncbi.nlm.nih.gov/#query={}",
+                    hill_formula(&chemical.parts)
+                ),
+                At::Target => "_blank",
+                At::Rel => "noopener noreferrer",
+            },
+            model.i18n.t("species.pubchem"),
+        },
+        close,
+    }
+}
+
+fn network_view(model: &Model) -> Node<Msg> {
+    let toggle = button! {
+        model.i18n.t(if model.show_network { "network.close" } else { "network.open" }),
+        simple_ev(Ev::Click, Msg::ToggleNetwork),
+    };
+    if !model.show_network {
+        return section! { id!["network"], toggle };
+    }
+
+    let steps: Vec<&Reaction> = model.history.iter().map(|entry| &entry.reaction).collect();
+    let links = find_links(&steps);
+    if links.is_empty() {
+        return section! {
+            id!["network"],
+            toggle,
+            p! { model.i18n.t("network.empty") },
+        };
+    }
+    let shown = linked_steps(&links);
+    let overall = overall_reaction(&shown.iter().map(|&step| steps[step]).collect::<Vec<_>>());
+    let factors = shown
+        .iter()
+        .zip(&overall.factors)
+        .map(|(step, factor)| format!("Out[{}] × {}", step, factor))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let leftover = if overall.leftover.is_empty() {
+        empty![]
+    } else {
+        p! { model.i18n.format("network.leftover", &[&overall.leftover.join(", ")]) }
+    };
+    section! {
+        id!["network"],
+        toggle,
+        raw!(&network_graph(&steps, &shown, &links)),
+        p! { factors },
+        p! { model.i18n.format("network.overall", &[&overall.reaction]) },
+        p! { model.i18n.format("network.cancelled", &[&overall.cancelled.join(", ")]) },
+        leftover,
+    }
+}
+
+fn theme_view(model: &Model) -> Node<Msg> {
+    select![
+        id!["theme"],
+        attrs! { At::Title => model.i18n.t("settings.theme") },
+        THEMES.iter().map(|theme| {
+            let name = model
+                .i18n
+                .get(&format!("theme.{}", theme.id))
+                .unwrap_or(theme.name);
+            option![
+                attrs! {
+                    At::Value => theme.id,
+                    At::Selected => (theme.id == model.theme.id).as_at_value(),
+                },
+                name,
+            ]
+        }),
+        input_ev(Ev::Change, Msg::SetTheme),
+    ]
+}
+
+fn locale_view(model: &Model) -> Node<Msg> {
+    // Nothing to choose until a catalog besides the built-in one has loaded
+    if model.i18n.catalogs().len() < 2 {
+        return empty![];
+    }
+    select![
+        id!["locale"],
+        attrs! { At::Title => model.i18n.t("settings.locale") },
+        model.i18n.catalogs().iter().map(|catalog| {
+            option![
+                attrs! {
+                    At::Value => catalog.locale,
+                    At::Selected => (catalog.locale == model.i18n.locale()).as_at_value(),
+                },
+                catalog.name,
+            ]
+        }),
+        input_ev(Ev::Change, Msg::SetLocale),
+    ]
+}
+
+fn text_size_view(model: &Model) -> Node<Msg> {
+    div![
+        id!["text-size"],
+        TextSize::ALL.iter().map(|&size| {
+            button![
+                class![size.class(), "selected" => size == model.text_size],
+                attrs! { At::Title => model.i18n.t(size.message_key()) },
+                "가",
+                simple_ev(Ev::Click, Msg::SetTextSize(size)),
+            ]
+        }),
+    ]
+}
+
+fn auto_close_view(model: &Model) -> Node<Msg> {
+    button![
+        id!["auto-close"],
+        class!["selected" => model.auto_close],
+        attrs! { At::Title => model.i18n.t("settings.auto_close") },
+        "( )",
+        simple_ev(Ev::Click, Msg::ToggleAutoClose),
+    ]
+}
+
+fn output_style_view(model: &Model) -> Node<Msg> {
+    let style = model.output_style;
+    div![
+        id!["output-style"],
+        select![
+            attrs! { At::Title => model.i18n.t("settings.coefficients") },
+            CoefficientStyle::ALL.iter().map(|&coefficients| {
+                option![
+                    attrs! {
+                        At::Value => coefficients.id(),
+                        At::Selected => (coefficients == style.coefficients).as_at_value(),
+                    },
+                    model
+                        .i18n
+                        .t(&format!("settings.coefficients.{}", coefficients.id())),
+                ]
+            }),
+            input_ev(Ev::Change, move |id| {
+                let coefficients = CoefficientStyle::from_id(&id).unwrap_or_default();
+                Msg::SetOutputStyle(OutputStyle {
+                    coefficients,
+                    ..style
+                })
+            }),
+        ],
+        select![
+            attrs! { At::Title => model.i18n.t("settings.scripts") },
+            ScriptStyle::ALL.iter().map(|&scripts| {
+                option![
+                    attrs! {
+                        At::Value => scripts.id(),
+                        At::Selected => (scripts == style.scripts).as_at_value(),
+                    },
+                    model.i18n.t(&format!("settings.scripts.{}", scripts.id())),
+                ]
+            }),
+            input_ev(Ev::Change, move |id| {
+                let scripts = ScriptStyle::from_id(&id).unwrap_or_default();
+                Msg::SetOutputStyle(OutputStyle { scripts, ..style })
+            }),
+        ],
+    ]
+}
+
+/// Switches for the syntax accepted in the input, so a class can be held to plain formulas.
+fn parser_view(model: &Model) -> Node<Msg> {
+    let config = model.parser_config;
+    let options = [
+        (
+            "settings.parser.phases",
+            config.allow_phases,
+            ParserConfig {
+                allow_phases: !config.allow_phases,
+                ..config
+            },
+        ),
+        (
+            "settings.parser.charges",
+            config.allow_charges,
+            ParserConfig {
+                allow_charges: !config.allow_charges,
+                ..config
+            },
+        ),
+        (
+            "settings.parser.names",
+            config.allow_names,
+            ParserConfig {
+                allow_names: !config.allow_names,
+                ..config
+            },
+        ),
+        (
+            "settings.parser.lowercase",
+            config.allow_lowercase,
+            ParserConfig {
+                allow_lowercase: !config.allow_lowercase,
+                ..config
+            },
+        ),
+        (
+            "settings.parser.abbreviations",
+            config.allow_abbreviations,
+            ParserConfig {
+                allow_abbreviations: !config.allow_abbreviations,
+                ..config
+            },
+        ),
+    ];
+    div![
+        id!["parser-config"],
+        span![model.i18n.t("settings.parser")],
+        options.iter().map(|&(key, allowed, toggled)| {
+            button![
+                class!["selected" => allowed],
+                model.i18n.t(key),
+                simple_ev(Ev::Click, Msg::SetParserConfig(toggled)),
+            ]
+        }),
+        button![
+            model.i18n.t("settings.parser.strict"),
+            simple_ev(Ev::Click, Msg::SetParserConfig(ParserConfig::strict())),
+        ],
+        button![
+            model.i18n.t("settings.parser.lenient"),
+            simple_ev(Ev::Click, Msg::SetParserConfig(ParserConfig::lenient())),
+        ],
+    ]
+}
+
+fn solver_view(model: &Model) -> Node<Msg> {
+    if !model.show_solver {
+        return empty![];
+    }
+    let numbers = model.i18n.number_format();
+    div![
+        id!["solver"],
+        select![
+            attrs! { At::Title => model.i18n.t("settings.solver") },
+            SolverStrategy::ALL.iter().map(|&strategy| {
+                option![
+                    attrs! {
+                        At::Value => strategy.id(),
+                        At::Selected => (strategy == model.balancer.strategy()).as_at_value(),
+                    },
+                    model.i18n.t(&format!("solver.{}", strategy.id())),
+                ]
+            }),
+            input_ev(Ev::Change, Msg::SetSolver),
+        ],
+        model.solve_time.map_or_else(
+            || empty![],
+            |time| span! { model.i18n.format("solver.time", &[&numbers.format(time, 2)]) },
+        ),
+        model.diagnostics.map_or_else(
+            || empty![],
+            |diagnostics| {
+                span! {
+                    model.i18n.format(
+                        "solver.diagnostics",
+                        &[&diagnostics.rank, &diagnostics.nullity],
+                    ),
+                    " ",
+                    model.i18n.t(if diagnostics.unique {
+                        "solver.unique"
+                    } else {
+                        "solver.not_unique"
+                    }),
+                }
+            },
+        ),
+        corpus_view(model),
+    ]
+}
+
+fn sync_view(model: &Model) -> Node<Msg> {
+    if let Some(offered) = &model.offered_sync {
+        return div![
+            id!["sync"],
+            class!["offer"],
+            p![model
+                .i18n
+                .format("sync.offer", &[&offered.host().unwrap_or_default()])],
+            button! {
+                model.i18n.t("sync.accept"),
+                simple_ev(Ev::Click, Msg::AcceptSync),
+            },
+            button! {
+                model.i18n.t("sync.decline"),
+                simple_ev(Ev::Click, Msg::DeclineSync),
+            },
+        ];
+    }
+    if model.sync.is_none() {
+        return empty![];
+    }
+    div![
+        id!["sync"],
+        model.sync_status.map_or_else(
+            || empty![],
+            |status| span! { model.i18n.t(status.message_key()) },
+        ),
+        button! {
+            model.i18n.t("sync.now"),
+            attrs! { At::Disabled => (model.sync_status == Some(SyncStatus::Syncing)).as_at_value() },
+            simple_ev(Ev::Click, Msg::SyncNow),
+        },
+        button! {
+            model.i18n.t("sync.stop"),
+            simple_ev(Ev::Click, Msg::StopSync),
+        },
+    ]
+}
+
+/// Collects known good results for the regression corpus, in dev builds only.
+fn corpus_view(model: &Model) -> Node<Msg> {
+    if !cfg!(debug_assertions) {
+        return empty![];
+    }
+    let add = if model.reaction.is_some() {
+        button! {
+            model.i18n.t("corpus.add"),
+            simple_ev(Ev::Click, Msg::AddToCorpus),
+        }
+    } else {
+        empty![]
+    };
+    let export = if model.corpus_additions.is_empty() {
+        empty![]
+    } else {
+        a! {
+            attrs! {
+                At::Href => format!(
+                    "data:text/plain;charset=utf-8,{}",
+                    percent_encode(&model.corpus_additions.join("\n"))
+                ),
+                At::Download => "corpus-additions.txt",
+            },
+            model.i18n.format("corpus.export", &[&model.corpus_additions.len()]),
+        }
+    };
+    span![add, export]
+}
+
+fn palette_view(model: &Model) -> Node<Msg> {
+    let palette = match &model.palette {
+        Some(palette) => palette,
+        None => return empty![],
+    };
+    let i18n = &model.i18n;
+    let matches = palette_matches(model);
+    let list = if matches.is_empty() {
+        p![class!["empty"], i18n.t("palette.empty")]
+    } else {
+        ul![matches.into_iter().enumerate().map(|(index, (label, _))| {
+            li![
+                class!["selected" => index == palette.selected],
+                label,
+                simple_ev(Ev::Click, Msg::RunCommand(index)),
+            ]
+        })]
+    };
+    div![
+        id!["palette"],
+        attrs! { At::Custom("role".into()) => "dialog" },
+        input![
+            attrs! {
+                At::Type => "text",
+                At::Placeholder => i18n.t("palette.placeholder"),
+                At::Value => palette.query,
+            },
+            el_ref(&model.palette_ref),
+            input_ev(Ev::Input, Msg::SetPaletteQuery),
+            keyboard_ev("keydown", |event| {
+                // Arrow keys pick a command rather than move the caret
+                if event.key().starts_with("Arrow") {
+                    event.prevent_default();
+                }
+                Msg::PaletteKeyDown(event.key())
+            }),
+        ],
+        list,
+        button![
+            class!["close"],
+            i18n.t("palette.close"),
+            simple_ev(Ev::Click, Msg::ClosePalette),
+        ],
+    ]
+}
+
+/// Records what is typed and balanced, and replays it as a demonstration.
+fn recording_view(model: &Model) -> Node<Msg> {
+    let i18n = &model.i18n;
+    let recording = model.recording_started.is_some();
+    let playing = model.playback.is_some();
+    let record = if recording {
+        button! {
+            i18n.t("recording.stop"),
+            simple_ev(Ev::Click, Msg::StopRecording),
+        }
+    } else {
+        button! {
+            i18n.t("recording.start"),
+            attrs! { At::Disabled => playing.as_at_value() },
+            simple_ev(Ev::Click, Msg::StartRecording),
+        }
+    };
+    let play = if playing {
+        button! {
+            i18n.t("recording.stop_playback"),
+            simple_ev(Ev::Click, Msg::StopPlayback),
+        }
+    } else {
+        button! {
+            i18n.t("recording.play"),
+            attrs! { At::Disabled => (recording || model.recording.is_empty()).as_at_value() },
+            simple_ev(Ev::Click, Msg::StartPlayback),
+        }
+    };
+    let status = if recording {
+        span![class!["recording"], i18n.t("recording.recording")]
+    } else if model.recording.is_empty() {
+        empty![]
+    } else {
+        let seconds = model.recording.duration() / 1000.0;
+        span![i18n.format(
+            "recording.summary",
+            &[
+                &model.recording.steps.len(),
+                &i18n.number_format().format(seconds, 1)
+            ],
+        )]
+    };
+    section! {
+        id!["recording"],
+        record,
+        play,
+        status,
+    }
+}
+
+fn stats_view(model: &Model) -> Node<Msg> {
+    let toggle = button! {
+        model.i18n.t(if model.show_stats { "stats.close" } else { "stats.open" }),
+        simple_ev(Ev::Click, Msg::ToggleStats),
+    };
+    if !model.show_stats {
+        return section! { id!["stats"], toggle };
+    }
+
+    let stats = &model.stats;
+    let numbers = model.i18n.number_format();
+    let percent = |ratio: Option<f64>| ratio.map_or("-".into(), |ratio| numbers.percent(ratio, 1));
+    let rows = vec![
+        ("stats.balanced", stats.balanced.to_string()),
+        ("stats.error_rate", percent(stats.error_rate())),
+        (
+            "stats.average_species",
+            stats
+                .average_species_count()
+                .map_or("-".into(), |average| numbers.format(average, 1)),
+        ),
+        (
+            "stats.accuracy",
+            format!(
+                "{} ({}/{})",
+                percent(stats.accuracy()),
+                stats.correct,
+                stats.graded
+            ),
+        ),
+    ];
+    section! {
+        id!["stats"],
+        toggle,
+        table! {
+            rows.into_iter().map(|(key, value)| tr! { th! { model.i18n.t(key) }, td! { value } })
+        },
+    }
+}
+
+/// Compounds used most in the history, with a button to type each into the input.
+fn compounds_view(model: &Model) -> Node<Msg> {
+    let toggle = button! {
+        model.i18n.t(if model.show_compounds { "compounds.close" } else { "compounds.open" }),
+        simple_ev(Ev::Click, Msg::ToggleCompounds),
+    };
+    if !model.show_compounds {
+        return section! { id!["compounds"], toggle };
+    }
+
+    let numbers = model.i18n.number_format();
+    let usage = compound_usage(model.history.iter().map(|entry| &entry.reaction));
+    let content = if usage.is_empty() {
+        p! { model.i18n.t("compounds.empty") }
+    } else {
+        table! {
+            tr! {
+                th! { model.i18n.t("compounds.formula") },
+                th! { model.i18n.t("compounds.count") },
+                th! { model.i18n.t("compounds.molar_mass") },
+                th! {},
+            },
+            usage.into_iter().take(MY_COMPOUND_COUNT).map(|compound| {
+                tr! {
+                    td! { format_chemicals(&compound.formula).iter().map(FormattedChemical::node) },
+                    td! { compound.count.to_string() },
+                    td! { compound.molar_mass.map_or("-".into(), |mass| numbers.format(mass, 2)) },
+                    td! {
+                        button! {
+                            model.i18n.t("compounds.insert"),
+                            simple_ev(Ev::Click, Msg::InsertText(compound.formula)),
+                        }
+                    },
+                }
+            }),
+        }
+    };
+    section! {
+        id!["compounds"],
+        toggle,
+        content,
+    }
+}
+
+fn failures_view(model: &Model) -> Node<Msg> {
+    let toggle = button! {
+        model.i18n.t(if model.show_failures { "failures.close" } else { "failures.open" }),
+        simple_ev(Ev::Click, Msg::ToggleFailures),
+    };
+    if !model.show_failures {
+        return section! { id!["failures"], toggle };
+    }
+
+    let failures = &model.failures;
+    let logging = button! {
+        model.i18n.t(if failures.enabled { "failures.disable" } else { "failures.enable" }),
+        simple_ev(Ev::Click, Msg::ToggleFailureLogging),
+    };
+    let log = if failures.entries.is_empty() {
+        p! { model.i18n.t("failures.empty") }
+    } else {
+        div! {
+            table! {
+                failures.counts().into_iter().map(|(category, count)| {
+                    tr! { th! { category }, td! { count.to_string() } }
+                })
+            },
+            ol! {
+                failures.entries.iter().rev().map(|failure| {
+                    li! { code! { failure.input.clone() }, " — ", failure.category.clone() }
+                })
+            },
+            a! {
+                attrs! {
+                    At::Href => format!("data:text/plain;charset=utf-8,{}", percent_encode(&failures.export())),
+                    At::Download => "failures.txt",
+                },
+                model.i18n.t("failures.export"),
+            },
+            button! {
+                model.i18n.t("failures.clear"),
+                simple_ev(Ev::Click, Msg::ClearFailures),
+            },
+        }
+    };
+    section! {
+        id!["failures"],
+        toggle,
+        p! { model.i18n.t("failures.privacy") },
+        logging,
+        log,
+    }
+}
+
+/// A recognized half-reaction balanced with its E°, and a choice of another
+/// to pair it with into a cell reaction.
+fn half_reaction_view(model: &Model) -> Node<Msg> {
+    let recognized = match model.half_reaction {
+        Some(recognized) => recognized,
+        None => return empty![],
+    };
+    let i18n = &model.i18n;
+    let numbers = i18n.number_format();
+    let cell = match model.half_partner {
+        Some(partner) => {
+            let cell = recognized.cell(partner);
+            p![
+                class!["cell-reaction"],
+                code![cell.equation],
+                " ",
+                i18n.format(
+                    "half_reaction.cell_potential",
+                    &[&numbers.format(cell.potential, 2)]
+                ),
+            ]
+        }
+        None => empty![],
+    };
+    section![
+        id!["half-reaction"],
+        p![
+            code![recognized.equation()],
+            " ",
+            i18n.format(
+                "half_reaction.potential",
+                &[&numbers.format(recognized.half_reaction().potential, 2)]
+            ),
+        ],
+        label![
+            i18n.t("half_reaction.pair"),
+            select![
+                input_ev(Ev::Change, Msg::SetHalfPartner),
+                option![attrs! { At::Value => "" }, "—"],
+                recognized.partners().into_iter().map(|index| {
+                    option![
+                        attrs! {
+                            At::Value => index,
+                            At::Selected => (model.half_partner == Some(index)).as_at_value(),
+                        },
+                        HALF_REACTIONS[index].equation,
+                    ]
+                }),
+            ],
+        ],
+        cell,
+    ]
+}
+
+/// Asks how each name that is both an alias and an element should be read,
+/// as the input is not balanced until every one is chosen.
+fn ambiguity_view(model: &Model) -> Node<Msg> {
+    let ambiguities = parser_aliases(model).ambiguities(&full_input(model));
+    if ambiguities.is_empty() {
+        return empty![];
+    }
+    section![
+        id!["ambiguities"],
+        ambiguities.into_iter().map(|ambiguity| {
+            let element_name = ambiguity.name.clone();
+            let alias_name = ambiguity.name.clone();
+            div![
+                p![model.i18n.format("ambiguity.question", &[&ambiguity.name])],
+                button![
+                    model
+                        .i18n
+                        .format("ambiguity.element", &[&ambiguity.name, &ambiguity.element]),
+                    ev(Ev::Click, move |_| {
+                        Msg::ChooseReading(element_name, Reading::Element)
+                    }),
+                ],
+                button![
+                    model
+                        .i18n
+                        .format("ambiguity.alias", &[&ambiguity.name, &ambiguity.formula]),
+                    ev(Ev::Click, move |_| Msg::ChooseReading(
+                        alias_name,
+                        Reading::Alias
+                    )),
+                ],
+            ]
+        }),
+    ]
+}
+
+fn aliases_view(model: &Model) -> Node<Msg> {
+    let config = model.parser_config;
+    details![
+        id!["aliases"],
+        summary![model.i18n.t("aliases.title")],
+        p![model.i18n.t("aliases.description")],
+        button![
+            class!["selected" => config.allow_abbreviations],
+            model.i18n.t("aliases.organic_groups"),
+            simple_ev(
+                Ev::Click,
+                Msg::SetParserConfig(ParserConfig {
+                    allow_abbreviations: !config.allow_abbreviations,
+                    ..config
+                })
+            ),
+        ],
+        button![
+            class!["selected" => model.element_groups],
+            attrs! {
+                At::Disabled => (!config.allow_abbreviations).as_at_value(),
+                At::Title => model.i18n.t("aliases.element_groups_description"),
+            },
+            model.i18n.t("aliases.element_groups"),
+            simple_ev(Ev::Click, Msg::ToggleElementGroups),
+        ],
+        ul![model.aliases.entries.iter().map(|alias| {
+            let name = alias.name.clone();
+            li![
+                code![format!("{} = {}", alias.name, alias.formula)],
+                button![
+                    model.i18n.t("aliases.remove"),
+                    ev(Ev::Click, move |_| Msg::RemoveAlias(name)),
+                ],
+            ]
+        })],
+        input![
+            class!["invalid" => model.alias_invalid],
+            attrs! {
+                At::Placeholder => "Ph = C6H5",
+                At::Value => model.alias_draft,
+            },
+            input_ev(Ev::Input, Msg::SetAliasDraft),
+            keyboard_ev("keydown", |event| {
+                if event.key() == "Enter" {
+                    Some(Msg::AddAlias)
+                } else {
+                    None
+                }
+            }),
+        ],
+        button![
+            model.i18n.t("aliases.add"),
+            simple_ev(Ev::Click, Msg::AddAlias),
+        ],
+        if model.alias_invalid {
+            label![class!["error"], model.i18n.t("aliases.invalid")]
+        } else {
+            empty![]
+        },
+    ]
+}
+
+fn worksheet_view(model: &Model) -> Node<Msg> {
+    let problems = match &model.worksheet {
+        Some(problems) => problems,
+        None => {
+            return details![
+                id!["worksheet-generator"],
+                summary![model.i18n.t("worksheet.create")],
+                input![
+                    attrs! {
+                        At::Type => "number",
+                        At::Min => "1",
+                        At::Max => worksheet::EXAMPLES.len(),
+                        At::Value => model.worksheet_size,
+                    },
+                    input_ev(Ev::Input, Msg::SetWorksheetSize),
+                ],
+                button![
+                    model.i18n.t("worksheet.generate"),
+                    simple_ev(Ev::Click, Msg::GenerateWorksheet),
+                ],
+            ]
+        }
+    };
+
+    section![
+        id!["worksheet"],
+        div![
+            class!["controls"],
+            button![
+                model.i18n.t("worksheet.print"),
+                simple_ev(Ev::Click, Msg::PrintWorksheet)
+            ],
+            button![
+                model.i18n.t("worksheet.generate"),
+                simple_ev(Ev::Click, Msg::GenerateWorksheet)
+            ],
+            button![
+                model.i18n.t("common.close"),
+                simple_ev(Ev::Click, Msg::CloseWorksheet)
+            ],
+        ],
+        div![
+            class!["page"],
+            h2![model.i18n.t("worksheet.title")],
+            p![class!["name"], model.i18n.t("worksheet.name")],
+            ol![problems.iter().map(|problem| {
+                li![format_chemicals(&problem.equation)
+                    .iter()
+                    .map(FormattedChemical::node)]
+            })],
+        ],
+        div![
+            class!["page", "answer-key"],
+            h2![model.i18n.t("worksheet.answer_key")],
+            ol![problems.iter().map(|problem| {
+                li![
+                    format_chemicals(&problem.answer)
+                        .iter()
+                        .map(FormattedChemical::node),
+                    if problem.note.is_empty() {
+                        empty![]
+                    } else {
+                        p![class!["note"], problem.note.as_str()]
+                    },
+                ]
+            })],
+        ],
+    ]
+}
+
+fn grade_message_key(grade: Grade) -> &'static str {
+    match grade {
+        Grade::Correct => "grade.correct",
+        Grade::NotLowest => "grade.not_lowest",
+        Grade::Unbalanced => "grade.unbalanced",
+    }
+}
+
+/// Steppers for each coefficient of the equation being practiced, with the
+/// atom count of each element on both sides after every step.
+fn practice_view(model: &Model) -> Node<Msg> {
+    let practice = match &model.practice {
+        Some(practice) => practice,
+        None => return empty![],
+    };
+    let i18n = &model.i18n;
+    let reaction = &practice.reaction;
+    let finished = model.practice_clock.finished;
+    let mut species = Vec::new();
+    for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+        if index == reaction.reagents.len() {
+            species.push(span![class!["operator"], "="]);
+        } else if index > 0 {
+            species.push(span![class!["operator"], "+"]);
+        }
+        species.push(span![
+            class!["stepper"],
+            button![
+                "−",
+                attrs! { At::Disabled => (coefficient <= 1 || finished).as_at_value() },
+                simple_ev(Ev::Click, Msg::StepCoefficient(index, -1)),
+            ],
+            b![coefficient.to_string()],
+            button![
+                "+",
+                attrs! { At::Disabled => finished.as_at_value() },
+                simple_ev(Ev::Click, Msg::StepCoefficient(index, 1)),
+            ],
+            span![format_fragments(chemical)
+                .iter()
+                .map(FormattedChemical::node)],
+        ]);
+    }
+    let counts = reaction
+        .element_counts()
+        .into_iter()
+        .map(|(element, (left, right))| {
+            tr![
+                class!["balanced" => left == right],
+                td![element],
+                td![left.to_string()],
+                td![right.to_string()],
+            ]
+        });
+    let grade = practice.grade();
+    let status = match grade {
+        _ if practice.revealed => i18n.t("practice.revealed"),
+        grade => i18n.t(grade_message_key(grade)),
+    };
+    section![
+        id!["practice"],
+        h3![i18n.t("practice.title")],
+        practice_clock_view(model, practice),
+        div![class!["equation"], species],
+        table![
+            tr![
+                th![i18n.t("practice.element")],
+                th![i18n.t("practice.reagents")],
+                th![i18n.t("practice.products")],
+            ],
+            counts.collect::<Vec<_>>(),
+        ],
+        p![
+            class!["status", "correct" => grade == Grade::Correct && !practice.revealed],
+            status,
+        ],
+        if finished {
+            empty![]
+        } else {
+            button![
+                i18n.t("practice.reveal"),
+                simple_ev(Ev::Click, Msg::RevealAnswer),
+            ]
+        },
+        button![
+            i18n.t("practice.end"),
+            simple_ev(Ev::Click, Msg::EndPractice)
+        ],
+    ]
+}
+
+/// Time limit choice, the timer or countdown, and the challenge score.
+fn practice_clock_view(model: &Model, practice: &Practice) -> Node<Msg> {
+    let i18n = &model.i18n;
+    let numbers = i18n.number_format();
+    let clock = &model.practice_clock;
+    let seconds = |seconds: f64| i18n.format("practice.seconds", &[&numbers.format(seconds, 1)]);
+    let limit = model.challenge.map(|challenge| challenge.time_limit);
+    let timer = match model.challenge {
+        Some(challenge) if !clock.finished => i18n.format(
+            "practice.time_left",
+            &[&numbers.format((challenge.time_limit as f64 - clock.elapsed).max(0.0), 0)],
+        ),
+        _ => seconds(clock.elapsed),
+    };
+    let best = model.stats.best_times.get(practice.difficulty.id());
+    div![
+        class!["clock"],
+        select![
+            attrs! { At::Title => i18n.t("practice.time_limit") },
+            option![
+                attrs! { At::Value => "", At::Selected => limit.is_none().as_at_value() },
+                i18n.t("practice.no_limit"),
+            ],
+            TIME_LIMITS.iter().map(|&time_limit| {
+                option![
+                    attrs! {
+                        At::Value => time_limit,
+                        At::Selected => (limit == Some(time_limit)).as_at_value(),
+                    },
+                    i18n.format("practice.limit", &[&time_limit]),
+                ]
+            }),
+            input_ev(Ev::Change, Msg::SetTimeLimit),
+        ],
+        span![
+            class!["difficulty", practice.difficulty.id()],
+            i18n.t(practice.difficulty.message_key()),
+        ],
+        span![class!["timer"], timer],
+        best.map_or_else(
+            || empty![],
+            |&best| span![i18n.format("practice.best", &[&seconds(best)])],
+        ),
+        if clock.new_best {
+            span![class!["new-best"], i18n.t("practice.new_best")]
+        } else {
+            empty![]
+        },
+        model.challenge.map_or_else(
+            || empty![],
+            |challenge| {
+                span![
+                    class!["score"],
+                    i18n.format(
+                        "practice.score",
+                        &[&challenge.score, &challenge.streak, &challenge.multiplier()]
+                    ),
+                    clock
+                        .points
+                        .map_or_else(|| empty![], |points| b![format!(" +{}", points)]),
+                ]
+            },
+        ),
+    ]
+}
+
+/// Difficulty of a problem and the hints asked for so far, one more per click.
+fn hints_view(model: &Model, problem_index: usize, problem: &AssignmentProblem) -> Node<Msg> {
+    let analysis = match &problem.analysis {
+        Some(analysis) => analysis,
+        None => return empty![],
+    };
+    let shown = problem.hints_shown.min(analysis.hints.len());
+    div![
+        class!["hints"],
+        span![
+            class!["difficulty", analysis.difficulty.id()],
+            model.i18n.t(analysis.difficulty.message_key()),
+        ],
+        if shown < analysis.hints.len() {
+            button![
+                model.i18n.t("hint.show"),
+                simple_ev(Ev::Click, Msg::ShowHint(problem_index)),
+            ]
+        } else {
+            empty![]
+        },
+        ul![analysis.hints[..shown]
+            .iter()
+            .map(|hint| li![hint.localize(&model.i18n)])],
+    ]
+}
+
+fn assignment_view(model: &Model) -> Node<Msg> {
+    let problems = match &model.assignment {
+        Some(problems) => problems,
+        None => return assignment_link_view(model),
+    };
+
+    let problem_views = problems.iter().enumerate().map(|(problem_index, problem)| {
+        let equation = match &problem.equation {
+            Ok(equation) => equation,
+            Err(error) => {
+                return li![
+                    problem.input.clone(),
+                    label![class!["error"], error.localize(&model.i18n)]
+                ]
+            }
+        };
+        let species = equation.reagents.iter().chain(&equation.products);
+        let mut nodes = Vec::new();
+        for (index, chemical) in species.enumerate() {
+            if index == equation.reagents.len() {
+                nodes.push(Node::new_text(" = "));
+            } else if index > 0 {
+                nodes.push(Node::new_text(" + "));
+            }
+            nodes.push(input![
+                attrs! {
+                    At::Type => "number",
+                    At::Min => "1",
+                    At::Placeholder => "1",
+                    At::Value => problem.answers[index],
+                },
+                input_ev(Ev::Input, move |answer| Msg::SetAnswer(
+                    problem_index,
+                    index,
+                    answer
+                )),
+            ]);
+            nodes.push(span![format_fragments(chemical)
+                .iter()
+                .map(FormattedChemical::node)]);
+        }
+        li![
+            div![class!["problem"], nodes],
+            button![
+                model.i18n.t("assignment.grade"),
+                simple_ev(Ev::Click, Msg::GradeProblem(problem_index))
+            ],
+            hints_view(model, problem_index, problem),
+            problem.grade.map_or_else(
+                || empty![],
+                |grade| {
+                    label![
+                        class!["correct" => grade == Grade::Correct],
+                        model.i18n.t(grade_message_key(grade))
+                    ]
+                }
+            ),
+        ]
+    });
+
+    let report: Vec<String> = problems
+        .iter()
+        .enumerate()
+        .map(|(index, problem)| {
+            let answer = problem
+                .answer()
+                .map_or_else(|| problem.input.clone(), |answer| answer.to_string());
+            let grade = model
+                .i18n
+                .t(problem.grade.map_or("grade.pending", grade_message_key));
+            format!("{}. {} : {}", index + 1, answer, grade)
+        })
+        .collect();
+    let title = match model.race {
+        Some(race) => model.i18n.format("race.title", &[&race.seed]),
+        None => model.i18n.t("assignment.title").into(),
+    };
+    let race_time = model.race_time.map_or_else(
+        || empty![],
+        |seconds| {
+            p![
+                class!["race-time"],
+                model.i18n.format(
+                    "race.finished",
+                    &[&model.i18n.number_format().format(seconds, 1)]
+                ),
+            ]
+        },
+    );
+    section![
+        id!["assignment"],
+        h2![title],
+        race_time,
+        ol![problem_views],
+        a![
+            attrs! {
+                At::Href => format!("data:text/plain;charset=utf-8,{}", percent_encode(&report.join("\n"))),
+                At::Download => "results.txt",
+            },
+            model.i18n.t("assignment.export"),
+        ],
+    ]
+}
+
+fn assignment_link_view(model: &Model) -> Node<Msg> {
+    let problems: Vec<String> = model
+        .assignment_draft
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    let link = if problems.is_empty() {
+        empty![]
+    } else {
+        let location = seed::window().location();
+        let link = format!(
+            "{}{}?{}",
+            location.origin().unwrap_or_default(),
+            location.pathname().unwrap_or_default(),
+            Assignment { problems }.to_query()
+        );
+        p![a![attrs! { At::Href => link }, link]]
+    };
+    let race_link = model.race_link.map_or_else(
+        || empty![],
+        |race| {
+            let location = seed::window().location();
+            let link = format!(
+                "{}{}?{}",
+                location.origin().unwrap_or_default(),
+                location.pathname().unwrap_or_default(),
+                race.to_query()
+            );
+            p![a![attrs! { At::Href => link }, link]]
+        },
+    );
+    details![
+        id!["assignment"],
+        summary![model.i18n.t("assignment.create")],
+        textarea![
+            attrs! {
+                At::Placeholder => "H2 + O2 = H2O\nCH4 + O2 = CO2 + H2O",
+                At::Value => model.assignment_draft,
+            },
+            input_ev(Ev::Input, Msg::SetAssignmentDraft),
+        ],
+        button![
+            model.i18n.t("assignment.from_history"),
+            simple_ev(Ev::Click, Msg::DraftFromHistory)
+        ],
+        link,
+        button![
+            attrs! { At::Title => model.i18n.t("race.description") },
+            model.i18n.t("race.create"),
+            simple_ev(Ev::Click, Msg::CreateRace)
+        ],
+        race_link,
+    ]
+}
+
+/// Whether the entry passes the tag filter.
+fn is_shown(model: &Model, entry: &HistoryEntry) -> bool {
+    model
+        .tag_filter
+        .as_ref()
+        .map_or(true, |tag| entry.tags.contains(tag))
+}
+
+/// Id of the element of a history entry, to scroll and link to it.
+fn entry_id(index: usize) -> String {
+    format!("{}{}", ENTRY_ANCHOR, index)
+}
+
+/// Scrolls smoothly, and only as far as needed to show the whole element.
+fn scroll_into_view(element: &web_sys::Element) {
+    let mut options = web_sys::ScrollIntoViewOptions::new();
+    options
+        .behavior(web_sys::ScrollBehavior::Smooth)
+        .block(web_sys::ScrollLogicalPosition::Nearest);
+    element.scroll_into_view_with_scroll_into_view_options(&options);
+}
+
+fn history_view(model: &Model) -> Node<Msg> {
+    let shown: Vec<(usize, &HistoryEntry)> = model
+        .history
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| is_shown(model, entry))
+        .collect();
+    let hidden = shown.len().saturating_sub(model.history_shown);
+    let show_more = if hidden > 0 {
+        li! {
+            class!["more"],
+            button! {
+                model.i18n.format("history.more", &[&hidden]),
+                simple_ev(Ev::Click, Msg::ShowMoreHistory),
+            }
+        }
+    } else {
+        empty![]
+    };
+    let i18n = &model.i18n;
+    let entries = shown
+        .into_iter()
+        .skip(hidden)
+        .rev()
+        .map(|(index, entry)| {
+            let selected = model.history_selection.contains(&index);
+            li! {
+                attrs! { At::Id => entry_id(index) },
+                class![
+                    "selected" => selected,
+                    "highlighted" => model.highlighted == Some(index),
+                ],
+                input! {
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => selected.as_at_value(),
+                        At::Title => i18n.t("history.select"),
+                    },
+                    mouse_ev(Ev::Click, move |event| {
+                        Msg::SelectHistoryEntry(index, event.shift_key())
+                    }),
+                },
+                entry.node.clone(),
+                tags_view(model, index, &entry.tags),
+                note_view(model, index, &entry.note),
+            }
+        })
+        .collect::<Vec<_>>();
+    ul! {
+        id!["history-list"],
+        class! [ "result" ],
+        entries,
+        show_more,
+    }
+}
+
+/// Tags of a history entry, each clicked to remove it.
+fn tags_view(model: &Model, index: usize, tags: &[String]) -> Node<Msg> {
+    if tags.is_empty() {
+        return empty![];
+    }
+    ul![
+        class!["tags"],
+        tags.iter().map(|tag| {
+            let removed = tag.clone();
+            li![
+                attrs! { At::Title => model.i18n.t("tag.remove") },
+                tag.as_str(),
+                simple_ev(Ev::Click, Msg::RemoveTag(index, removed)),
+            ]
+        }),
+    ]
+}
+
+/// The note of a history entry, edited in place.
+fn note_view(model: &Model, index: usize, note: &str) -> Node<Msg> {
+    let i18n = &model.i18n;
+    match &model.note_editing {
+        Some((editing, draft)) if *editing == index => div![
+            class!["note", "editing"],
+            textarea![
+                attrs! {
+                    At::Value => draft,
+                    At::Placeholder => i18n.t("note.placeholder"),
+                    At::Custom("autofocus".into()) => "",
+                },
+                input_ev(Ev::Input, Msg::SetNoteDraft),
+                keyboard_ev("keydown", |event| {
+                    // Enter saves, while Shift+Enter starts a new line
+                    match event.key().as_str() {
+                        "Enter" if !event.shift_key() => {
+                            event.prevent_default();
+                            Some(Msg::SaveNote)
+                        }
+                        "Escape" => Some(Msg::CancelNote),
+                        _ => None,
+                    }
+                }),
+            ],
+            button![i18n.t("note.save"), simple_ev(Ev::Click, Msg::SaveNote)],
+            button![i18n.t("note.cancel"), simple_ev(Ev::Click, Msg::CancelNote)],
+        ],
+        _ if note.is_empty() => button![
+            class!["note", "add"],
+            i18n.t("note.add"),
+            simple_ev(Ev::Click, Msg::EditNote(index)),
+        ],
+        _ => p![
+            class!["note"],
+            attrs! { At::Title => i18n.t("note.edit") },
+            note,
+            simple_ev(Ev::Click, Msg::EditNote(index)),
+        ],
+    }
+}
+
+/// Shows only the history entries with a tag, once any entry has one.
+fn tag_filter_view(model: &Model) -> Node<Msg> {
+    let counts = tag_counts(model.history.iter().map(|entry| entry.tags.as_slice()));
+    if counts.is_empty() {
+        return empty![];
+    }
+    let filter = model.tag_filter.as_deref().unwrap_or_default();
+    div![
+        id!["tag-filter"],
+        label![
+            model.i18n.t("tag.filter"),
+            select![
+                option![
+                    attrs! { At::Value => "", At::Selected => filter.is_empty().as_at_value() },
+                    model.i18n.t("tag.all"),
+                ],
+                counts.into_iter().map(|(tag, count)| {
+                    option![
+                        attrs! { At::Value => tag, At::Selected => (tag == filter).as_at_value() },
+                        format!("{} ({})", tag, count),
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::SetTagFilter),
+            ],
+        ],
+    ]
+}
+
+/// Actions on the selected history entries, shown once any is selected.
+fn history_actions_view(model: &Model) -> Node<Msg> {
+    if model.history_selection.is_empty() {
+        return empty![];
+    }
+    let i18n = &model.i18n;
+    let action = |key: &'static str, msg: Msg| button![i18n.t(key), simple_ev(Ev::Click, msg)];
+    div![
+        id!["history-actions"],
+        span![i18n.format("history.selected", &[&model.history_selection.len()])],
+        input![
+            attrs! {
+                At::Type => "text",
+                At::Placeholder => i18n.t("tag.placeholder"),
+                At::Value => model.tag_draft,
+            },
+            input_ev(Ev::Input, Msg::SetTagDraft),
+            keyboard_ev("keydown", |event| {
+                if event.key() == "Enter" {
+                    Some(Msg::TagSelected)
+                } else {
+                    None
+                }
+            }),
+        ],
+        action("tag.add", Msg::TagSelected),
+        action("history.combine", Msg::CombineSelected),
+        action("history.to_worksheet", Msg::WorksheetFromSelected),
+        action("history.export", Msg::ExportSelected),
+        action("history.delete", Msg::DeleteSelected),
+        action("history.deselect", Msg::ClearHistorySelection),
+    ]
+}
+
+/// The selected entries added up by Hess's law, with the enthalpy of each
+/// step scaled by its factor when the thermochemical data covers them.
+fn combined_view(model: &Model) -> Node<Msg> {
+    let (indices, overall) = match &model.combined {
+        Some(combined) => combined,
+        None => return empty![],
+    };
+    let i18n = &model.i18n;
+    let numbers = i18n.number_format();
+    let mut rows = Vec::new();
+    let mut total = Some(0.0);
+    for (&index, &factor) in indices.iter().zip(&overall.factors) {
+        let enthalpy = model.history.get(index).and_then(|entry| {
+            reaction_thermochemistry(&entry.reaction, &model.datasets)
+                .ok()
+                .map(|thermo| thermo.enthalpy * factor as f64)
+        });
+        total = total.and_then(|total: f64| Some(total + enthalpy?));
+        rows.push(tr![
+            th![format!("Out[{}] × {}", index, factor)],
+            td![enthalpy.map_or("-".into(), |enthalpy| format!(
+                "{} kJ",
+                numbers.format(enthalpy, 1)
+            ))],
+        ]);
+    }
+    let leftover = if overall.leftover.is_empty() {
+        empty![]
+    } else {
+        p![i18n.format("network.leftover", &[&overall.leftover.join(", ")])]
+    };
+    section![
+        id!["combined"],
+        h3![i18n.t("history.combined")],
+        p![i18n.format("network.overall", &[&overall.reaction])],
+        table![
+            rows,
+            tr![
+                th!["ΣΔH°"],
+                td![total.map_or("-".into(), |total| format!(
+                    "{} kJ",
+                    numbers.format(total, 1)
+                ))],
+            ],
+        ],
+        leftover,
+        button![
+            i18n.t("history.close_combined"),
+            simple_ev(Ev::Click, Msg::CloseCombined),
+        ],
+    ]
+}
+
+/// Exported sessions can be published and opened read-only with `?session=<url>`.
+fn session_export_view(model: &Model) -> Node<Msg> {
+    if model.history.is_empty() {
+        return empty![];
+    }
+    div![
+        id!["session-export"],
+        match &model.session_export {
+            Some(json) => a! {
+                attrs! {
+                    At::Href => format!("data:application/json;charset=utf-8,{}", percent_encode(json)),
+                    At::Download => "session.json",
+                },
+                model.i18n.t("session.download"),
+            },
+            None => button! {
+                model.i18n.t("session.export"),
+                simple_ev(Ev::Click, Msg::ExportSession),
+            },
+        },
+    ]
+}
+
+fn gallery_view(model: &Model, gallery: &Gallery) -> Vec<Node<Msg>> {
+    let content = match gallery {
+        Gallery::Loading => p! { model.i18n.t("gallery.loading") },
+        Gallery::Failed => label! { class!["error"], model.i18n.t("gallery.failed") },
+        Gallery::Loaded(entries) => ul! {
+            id!["gallery"],
+            class!["result"],
+            entries
+                .iter()
+                .map(|entry| li! { entry.node.clone() })
+                .collect::<Vec<_>>(),
+        },
+    };
+    vec![
+        div![
+            id!["settings"],
+            locale_view(model),
+            theme_view(model),
+            text_size_view(model),
+        ],
+        h1! { model.i18n.t("gallery.title") },
+        content,
+    ]
+}
+
+fn view(model: &Model) -> impl IntoNodes<Msg> {
+    if let Some(gallery) = &model.gallery {
+        return gallery_view(model, gallery);
+    }
+
+    let error_view = if let Some(ref error_message) = model.error {
+        label![
+            class!["error"],
+            glossary_text(model, &model.i18n.format("error.prefix", &[error_message])),
+            model.error_code.map_or_else(
+                || empty![],
+                |code| a![
+                    class!["error-code"],
+                    attrs! {
+                        At::Href => Route::Page(Page::About).fragment(),
+                        At::Title => model.i18n.t("error.code_help"),
+                    },
+                    code,
+                    ev(Ev::Click, move |event| {
+                        event.prevent_default();
+                        Msg::ShowErrorHelp(code)
+                    }),
+                ]
+            ),
+        ]
+    } else {
+        empty![]
+    };
+
+    if model.embed.is_some() {
+        return vec![
+            main! {
+                id! { "calculator" },
+                input_view(model),
+                diagram_view(model),
+            },
+            error_view,
+            species_panel_view(model),
+        ];
+    }
+
+    let mut nodes = vec![pages_view(model), palette_view(model)];
+    nodes.extend(match model.page {
+        Page::Calculator => vec![
+            main! {
+                id! { "calculator" },
+                h1! { model.i18n.t("calculator.title") },
+                input_view(model),
+                template_view(model),
+                practice_view(model),
+                diagram_view(model),
+                oxidation_view(model),
+                charge_view(model),
+                mole_ratio_view(model),
+                ph_view(model),
+                thermochemistry_view(model),
+            },
+            ambiguity_view(model),
+            half_reaction_view(model),
+            error_view,
+            species_panel_view(model),
+            assignment_view(model),
+        ],
+        Page::History => vec![
+            tag_filter_view(model),
+            history_actions_view(model),
+            combined_view(model),
+            network_view(model),
+            history_view(model),
+            session_export_view(model),
+        ],
+        Page::Tools => vec![
+            tools_view(model),
+            recording_view(model),
+            stats_view(model),
+            compounds_view(model),
+            failures_view(model),
+            aliases_view(model),
+            worksheet_view(model),
+        ],
+        Page::Settings => vec![div![
+            id!["settings"],
+            locale_view(model),
+            theme_view(model),
+            text_size_view(model),
+            auto_close_view(model),
+            output_style_view(model),
+            parser_view(model),
+            solver_view(model),
+            sync_view(model),
+        ]],
+        Page::About => vec![how_to_view(model)],
+    });
+    nodes
+}
+
+fn tools_view(model: &Model) -> Node<Msg> {
+    div![
+        id!["tools"],
+        model.tools.iter().enumerate().map(|(index, tool)| {
+            section![
+                class!["tool"],
+                h3![
+                    span![class!["icon"], tool.icon()],
+                    model.i18n.t(tool.title()),
+                ],
+                tool.view(&ToolContext {
+                    i18n: &model.i18n,
+                    datasets: &model.datasets,
+                })
+                .map_msg(move |msg| Msg::Tool(index, msg)),
+            ]
+        }),
+    ]
+}
+
+/// Links to every page, the one shown marked.
+fn pages_view(model: &Model) -> Node<Msg> {
+    nav![
+        id!["pages"],
+        Page::ALL.iter().map(|&page| {
+            a![
+                class!["selected" => page == model.page],
+                attrs! { At::Href => Route::Page(page).fragment() },
+                model.i18n.t(page.message_key()),
+            ]
+        }),
+    ]
+}
+
+/// Balances `input` and returns the result in the versioned JSON schema of `crate::schema`.
+#[wasm_bindgen]
+pub fn balance(input: &str) -> Result<String, JsValue> {
+    let i18n = I18n::default();
+    let equation =
+        parse_equation(input).map_err(|error| JsValue::from_str(&error.localize(&i18n)))?;
+    let coefficients = reaction::calculate_coefficients(&equation.reagents, &equation.products)
+        .map_err(|error| JsValue::from_str(&error.localize(&i18n)))?;
+    let diagnostics = reaction::diagnose(&equation.reagents, &equation.products).ok();
+    let warnings = equation.warnings.clone();
+    let mut result = BalanceResult::new(&equation.into_reaction(coefficients));
+    result.diagnostics = diagnostics;
+    result.warnings = warnings;
+    Ok(result.to_json())
+}
+
+fn after_mount(_: Url, orders: &mut impl Orders<Msg>) -> AfterMount<Model> {
+    orders.perform_cmd(fetch_catalog_index());
+    let mut model = Model::default();
+    model.stats = load_stats().unwrap_or_default();
+    model.failures = load_failures().unwrap_or_default();
+    model.aliases = load_aliases().unwrap_or_default();
+    model.parser_config = load_item(PARSER_CONFIG_STORAGE_KEY)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| ParserConfig {
+            allow_abbreviations: load_item(ORGANIC_GROUPS_STORAGE_KEY).as_deref() != Some("false"),
+            ..ParserConfig::default()
+        });
+    model.element_groups = load_item(ELEMENT_GROUPS_STORAGE_KEY).as_deref() == Some("true");
+    if let Some(theme) = load_item(THEME_STORAGE_KEY).and_then(|id| Theme::find(&id)) {
+        model.theme = theme;
+    }
+    apply_theme(model.theme);
+    if let Some(size) =
+        load_item(TEXT_SIZE_STORAGE_KEY).and_then(|class| TextSize::from_class(&class))
+    {
+        model.text_size = size;
+    }
+    apply_text_size(model.text_size);
+    model.auto_close = load_item(AUTO_CLOSE_STORAGE_KEY).as_deref() == Some("true");
+    model.output_style = load_item(OUTPUT_STYLE_STORAGE_KEY)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    model.recording = load_item(RECORDING_STORAGE_KEY)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let search = seed::window().location().search().unwrap_or_default();
+    if let Some(url) = query::param(&search, "session") {
+        model.gallery = Some(Gallery::Loading);
+        orders.perform_cmd(fetch_gallery(url));
+        return AfterMount::new(model);
+    }
+    model.race = Race::from_query(&search);
+    model.race_started = now();
+    let assignment =
+        Assignment::from_query(&search).or_else(|| model.race.map(|race| race.assignment()));
+    model.assignment = assignment.map(|assignment| {
+        assignment
+            .problems
+            .into_iter()
+            .map(AssignmentProblem::new)
+            .collect()
+    });
+    model.embed = Embed::from_query(&search);
+    if let Some(embed) = &model.embed {
+        let origin = embed.origin.clone();
+        orders.stream(streams::window_event(Ev::Message, move |event| {
+            host_request_handler(event, origin.as_deref())
+        }));
+    }
+    model.page = current_route().page();
+    if model.embed.is_none() {
+        orders.stream(streams::window_event(Ev::KeyDown, palette_shortcut_handler));
+    }
+    if let Some(id) = query::param(&search, "solver") {
+        model.show_solver = true;
+        if let Some(strategy) = SolverStrategy::from_id(&id) {
+            model.balancer.set_strategy(strategy);
+        }
+    }
+    if model.embed.is_some() {
+        if let Some(equation) = query::param(&search, "equation") {
+            model.input = equation;
+            orders.send_msg(Msg::Balance);
+        }
+    } else {
+        model.sync = load_item(SYNC_STORAGE_KEY).and_then(|json| serde_json::from_str(&json).ok());
+        // A link only offers an endpoint, and never replaces one already set up
+        if model.sync.is_none() {
+            model.offered_sync = SyncConfig::from_query(&search);
+            if model.offered_sync.is_some() {
+                navigate(model, Route::Page(Page::Settings));
+            }
+        }
+        orders.perform_cmd(load_session());
+    }
+    AfterMount::new(model)
+}
+
+fn current_route() -> Route {
+    Route::parse(&seed::window().location().hash().unwrap_or_default())
+}
+
+/// Shows the page of `route` and puts its fragment in the URL, unless it is
+/// there already, as when following a link.
+fn navigate(model: &mut Model, route: Route) {
+    model.page = route.page();
+    let fragment = route.fragment();
+    let location = seed::window().location();
+    if Route::parse(&location.hash().unwrap_or_default()) != route {
+        let _ = location.set_hash(&fragment);
+    }
+}
+
+fn routes(_: Url) -> Option<Msg> {
+    Some(Msg::Navigate(current_route()))
+}
+
+#[cfg_attr(not(feature = "ui-test"), wasm_bindgen(start))]
+pub fn render() {
+    start("app");
+}
+
+/// Starts the app in the element with the id `root`. UI tests build with
+/// `ui-test` so that nothing starts on load and mount it themselves.
+pub fn start(root: &'static str) {
+    App::builder(update, view)
+        .before_mount(move |_| BeforeMount::new().mount_point(root))
+        .after_mount(after_mount)
+        .routes(routes)
+        .build_and_start();
+}
@@ -0,0 +1,153 @@
+use crate::query::{param, percent_encode};
+use crate::reaction::{gcd, Reaction};
+use crate::worksheet::{self, EXAMPLES};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Query parameter carrying the equations of an assignment.
+pub const QUERY_KEY: &str = "assignment";
+
+/// A set of equations for students to balance, shared as a link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub problems: Vec<String>,
+}
+
+impl Assignment {
+    /// Encodes the assignment as a query string such as `assignment=H2%20%2B%20O2%20%3D%20H2O`.
+    pub fn to_query(&self) -> String {
+        let mut query = String::from(QUERY_KEY);
+        query.push('=');
+        query.push_str(&percent_encode(&self.problems.join("\n")));
+        query
+    }
+
+    /// Reads the assignment from a query string, with or without the leading `?`.
+    pub fn from_query(query: &str) -> Option<Self> {
+        let problems: Vec<String> = param(query, QUERY_KEY)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        if problems.is_empty() {
+            None
+        } else {
+            Some(Self { problems })
+        }
+    }
+}
+
+/// Query parameter carrying the seed of a race.
+pub const RACE_KEY: &str = "race";
+/// Number of problems in a race whose link does not say.
+pub const RACE_SIZE: usize = 10;
+
+/// Problems drawn from the worksheet examples by a shared seed, so that a
+/// whole class races through the same set without a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub seed: u64,
+    pub count: usize,
+}
+
+impl Race {
+    /// Encodes the race as a query string such as `race=42&count=10`.
+    pub fn to_query(&self) -> String {
+        format!("{}={}&count={}", RACE_KEY, self.seed, self.count)
+    }
+
+    /// Reads the race from a query string, with or without the leading `?`.
+    pub fn from_query(query: &str) -> Option<Self> {
+        let seed = param(query, RACE_KEY)?.parse().ok()?;
+        let count = param(query, "count")
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(RACE_SIZE)
+            .clamp(1, EXAMPLES.len());
+        Some(Self { seed, count })
+    }
+
+    pub fn assignment(&self) -> Assignment {
+        Assignment {
+            problems: worksheet::generate(self.count, self.seed)
+                .into_iter()
+                .map(|problem| problem.equation)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grade {
+    Correct,
+    /// Balanced, but all coefficients share a common factor.
+    NotLowest,
+    Unbalanced,
+}
+
+/// Grades the coefficients in `answer` against the element counts of the reaction.
+pub fn grade(answer: &Reaction) -> Grade {
+    if !answer.is_balanced() {
+        Grade::Unbalanced
+    } else if answer.coefficients.iter().copied().reduce(gcd) > Some(1) {
+        Grade::NotLowest
+    } else {
+        Grade::Correct
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+
+    #[test]
+    fn assignment_query_round_trip() {
+        let assignment = Assignment {
+            problems: vec!["H2 + O2 = H2O".into(), "N2 + H2 =[Fe]= NH3; 450°C".into()],
+        };
+        let query = assignment.to_query();
+        assert!(!query.contains(' '));
+        assert_eq!(
+            Some(assignment),
+            Assignment::from_query(&format!("?lang=ko&{}", query))
+        );
+        assert_eq!(None, Assignment::from_query("?lang=ko"));
+    }
+
+    #[test]
+    fn race_from_seed() {
+        let race = Race {
+            seed: 2024,
+            count: 5,
+        };
+        assert_eq!(
+            Some(race),
+            Race::from_query(&format!("?{}", race.to_query()))
+        );
+        assert_eq!(5, race.assignment().problems.len());
+        assert_eq!(
+            race.assignment(),
+            Race::from_query("race=2024&count=5").unwrap().assignment()
+        );
+        let default = Race::from_query("race=7&count=x").unwrap();
+        assert_eq!(RACE_SIZE, default.count);
+        assert_eq!(None, Race::from_query("race=seven"));
+    }
+
+    #[test]
+    fn grade_answers() {
+        let chemicals = |list: &[&str]| -> Vec<_> {
+            list.iter()
+                .map(|&formula| parse_chemical(formula).unwrap())
+                .collect()
+        };
+        let answer = |coefficients| {
+            Reaction::new(chemicals(&["H2", "O2"]), chemicals(&["H2O"]), coefficients)
+        };
+        assert_eq!(Grade::Correct, grade(&answer(vec![2, 1, 2])));
+        assert_eq!(Grade::NotLowest, grade(&answer(vec![4, 2, 4])));
+        assert_eq!(Grade::Unbalanced, grade(&answer(vec![1, 1, 1])));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::chemical::Chemical;
+use crate::reaction::{calculate_coefficients_with, verify_coefficients, Result};
+use crate::solver::SolverStrategy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Reusable balancing engine that remembers recently solved reactions.
+///
+/// Reactions are cached by their canonical form, so the same species typed in a
+/// different order are answered from the cache as well. The element table is
+/// static data and needs no loading.
+pub struct Balancer {
+    capacity: usize,
+    strategy: SolverStrategy,
+    /// Whether new solutions are cross-checked modulo large primes before being returned.
+    verify: bool,
+    /// Most recently used entries first, with coefficients in canonical species order.
+    cache: Mutex<VecDeque<(String, Vec<i64>)>>,
+}
+
+impl Default for Balancer {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl Balancer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            strategy: SolverStrategy::default(),
+            verify: true,
+            cache: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn balance(&self, reagents: &[Chemical], products: &[Chemical]) -> Result<Vec<i64>> {
+        let (reagent_order, reagent_formulas) = canonical_order(reagents);
+        let (product_order, product_formulas) = canonical_order(products);
+        let key = format!(
+            "{}={}",
+            reagent_formulas.join("+"),
+            product_formulas.join("+")
+        );
+        // Original species index of each canonical position
+        let order: Vec<usize> = reagent_order
+            .into_iter()
+            .chain(
+                product_order
+                    .into_iter()
+                    .map(|index| index + reagents.len()),
+            )
+            .collect();
+
+        let mut cache = self.cache.lock().expect("Balancer cache poisoned");
+        if let Some(position) = cache.iter().position(|(cached, _)| *cached == key) {
+            let entry = cache.remove(position).unwrap();
+            let mut coefficients = vec![0; order.len()];
+            for (&original, &coefficient) in order.iter().zip(entry.1.iter()) {
+                coefficients[original] = coefficient;
+            }
+            cache.push_front(entry);
+            return Ok(coefficients);
+        }
+
+        let coefficients = calculate_coefficients_with(reagents, products, self.strategy)?;
+        if self.verify {
+            verify_coefficients(reagents, products, &coefficients)?;
+        }
+        let canonical = order.iter().map(|&index| coefficients[index]).collect();
+        if self.capacity > 0 {
+            cache.truncate(self.capacity - 1);
+            cache.push_front((key, canonical));
+        }
+        Ok(coefficients)
+    }
+
+    pub fn strategy(&self) -> SolverStrategy {
+        self.strategy
+    }
+
+    /// Switches the solver, dropping answers cached from the previous one.
+    pub fn set_strategy(&mut self, strategy: SolverStrategy) {
+        self.strategy = strategy;
+        self.clear();
+    }
+
+    pub fn set_verify(&mut self, verify: bool) {
+        self.verify = verify;
+    }
+
+    pub fn clear(&self) {
+        self.cache.lock().expect("Balancer cache poisoned").clear();
+    }
+}
+
+fn canonical_order(chemicals: &[Chemical]) -> (Vec<usize>, Vec<String>) {
+    let formulas: Vec<String> = chemicals.iter().map(Chemical::canonical_formula).collect();
+    let mut order: Vec<usize> = (0..chemicals.len()).collect();
+    order.sort_by(|&a, &b| formulas[a].cmp(&formulas[b]));
+    let sorted = order.iter().map(|&index| formulas[index].clone()).collect();
+    (order, sorted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+
+    fn parse_all(formulas: &[&str]) -> Vec<Chemical> {
+        formulas
+            .iter()
+            .map(|f| parse_chemical(f).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn balance_reordered_from_cache() {
+        let balancer = Balancer::default();
+        let products = parse_all(&["H2O"]);
+        let first = balancer.balance(&parse_all(&["H2", "O2"]), &products);
+        assert_eq!(vec![2, 1, 2], first.unwrap());
+        let second = balancer.balance(&parse_all(&["O2", "H2"]), &products);
+        assert_eq!(vec![1, 2, 2], second.unwrap());
+        assert_eq!(1, balancer.cache.lock().unwrap().len());
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let balancer = Balancer::new(1);
+        balancer
+            .balance(&parse_all(&["H2", "O2"]), &parse_all(&["H2O"]))
+            .unwrap();
+        balancer
+            .balance(&parse_all(&["H2O"]), &parse_all(&["H2", "O2"]))
+            .unwrap();
+        let cache = balancer.cache.lock().unwrap();
+        assert_eq!(1, cache.len());
+        assert_eq!("H2O=H2+O2", cache[0].0);
+    }
+}
@@ -0,0 +1,273 @@
+//! Advisory checks that species are written with the charge they carry. The
+//! parser reads a species without a written charge as neutral, so a polyatomic
+//! ion written without its charge leaves its side with a charge nobody wrote.
+
+use crate::chemical::{parse_chemical, Chemical};
+use crate::i18n::I18n;
+use crate::reaction::{Reaction, Side};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Common polyatomic ions and their charges. Formulas that are also a common
+/// neutral molecule, like `NO2` or `SO3`, are left out.
+pub const COMMON_IONS: [(&str, i32); 20] = [
+    ("NH4", 1),
+    ("H3O", 1),
+    ("Hg2", 2),
+    ("OH", -1),
+    ("CN", -1),
+    ("NO3", -1),
+    ("HCO3", -1),
+    ("HSO4", -1),
+    ("H2PO4", -1),
+    ("ClO3", -1),
+    ("ClO4", -1),
+    ("MnO4", -1),
+    ("SO4", -2),
+    ("CO3", -2),
+    ("C2O4", -2),
+    ("S2O3", -2),
+    ("CrO4", -2),
+    ("Cr2O7", -2),
+    ("HPO4", -2),
+    ("PO4", -3),
+];
+
+/// Common ions of a single atom with more than one charge, so that `Fe3+`
+/// reads as Fe³⁺ while `O2+`, which no such ion matches, reads as O₂⁺.
+pub const MONATOMIC_IONS: [(&str, i32); 40] = [
+    ("Be", 2),
+    ("Mg", 2),
+    ("Ca", 2),
+    ("Sr", 2),
+    ("Ba", 2),
+    ("Ra", 2),
+    ("Sc", 3),
+    ("Y", 3),
+    ("La", 3),
+    ("Ce", 3),
+    ("Ce", 4),
+    ("Ti", 3),
+    ("Ti", 4),
+    ("V", 3),
+    ("Cr", 2),
+    ("Cr", 3),
+    ("Mn", 2),
+    ("Fe", 2),
+    ("Fe", 3),
+    ("Co", 2),
+    ("Co", 3),
+    ("Ni", 2),
+    ("Pd", 2),
+    ("Pt", 2),
+    ("Cu", 2),
+    ("Au", 3),
+    ("Zn", 2),
+    ("Cd", 2),
+    ("Hg", 2),
+    ("Al", 3),
+    ("Ga", 3),
+    ("In", 3),
+    ("Sn", 2),
+    ("Sn", 4),
+    ("Pb", 2),
+    ("Pb", 4),
+    ("Bi", 3),
+    ("N", -3),
+    ("O", -2),
+    ("S", -2),
+];
+
+/// A species that looks like an ion but was written without a charge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnwrittenCharge {
+    /// The species as written.
+    pub formula: String,
+    pub charge: i32,
+}
+
+impl UnwrittenCharge {
+    pub fn message_key(&self) -> &'static str {
+        "advice.unwritten_charge"
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        i18n.format(
+            self.message_key(),
+            &[&self.formula, &format_charge(self.charge)],
+        )
+    }
+}
+
+/// Writes a charge the way it goes after a formula, like `2-` or `+`.
+pub fn format_charge(charge: i32) -> String {
+    let sign = if charge < 0 { '-' } else { '+' };
+    match charge.abs() {
+        1 => format!("{}", sign),
+        magnitude => format!("{}{}", magnitude, sign),
+    }
+}
+
+/// Writes the charge of a side, like `+2`, `-1` or `0`.
+pub fn format_total(charge: i64) -> String {
+    match charge {
+        0 => "0".into(),
+        charge => format!("{:+}", charge),
+    }
+}
+
+/// A charged species' share of the charge of its side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChargeShare {
+    /// Index of the species, reagents first.
+    pub index: usize,
+    pub side: Side,
+    pub coefficient: i64,
+    pub charge: i32,
+}
+
+impl ChargeShare {
+    pub fn total(&self) -> i64 {
+        self.coefficient * self.charge as i64
+    }
+}
+
+/// Charges of a balanced reaction, side by side like its element counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChargeTally {
+    pub shares: Vec<ChargeShare>,
+    pub reagents: i64,
+    pub products: i64,
+}
+
+impl ChargeTally {
+    pub fn is_conserved(&self) -> bool {
+        self.reagents == self.products
+    }
+}
+
+/// The charge of each charged species and each side, or `None` if no species is charged.
+pub fn charge_tally(reaction: &Reaction) -> Option<ChargeTally> {
+    let shares: Vec<ChargeShare> = reaction
+        .species()
+        .enumerate()
+        .filter(|(_, (chemical, _))| chemical.charge != 0)
+        .map(|(index, (chemical, coefficient))| ChargeShare {
+            index,
+            side: if index < reaction.reagents.len() {
+                Side::Reagent
+            } else {
+                Side::Product
+            },
+            coefficient,
+            charge: chemical.charge,
+        })
+        .collect();
+    if shares.is_empty() {
+        return None;
+    }
+    let (reagents, products) = reaction.charges();
+    Some(ChargeTally {
+        shares,
+        reagents,
+        products,
+    })
+}
+
+/// The charge of a species written exactly like one of `COMMON_IONS`, in any
+/// order, and without a charge of its own.
+pub fn implied_charge(chemical: &Chemical) -> Option<i32> {
+    if chemical.charge != 0 {
+        return None;
+    }
+    let formula = chemical.canonical_formula();
+    COMMON_IONS.iter().find_map(|&(ion, charge)| {
+        let ion = parse_chemical(ion)?.canonical_formula();
+        if ion == formula {
+            Some(charge)
+        } else {
+            None
+        }
+    })
+}
+
+/// Species among `species` that would carry a charge nobody wrote, once each.
+pub fn unwritten_charges<'a>(
+    species: impl IntoIterator<Item = &'a Chemical>,
+) -> Vec<UnwrittenCharge> {
+    let mut found: Vec<UnwrittenCharge> = Vec::new();
+    for chemical in species {
+        if let Some(charge) = implied_charge(chemical) {
+            if !found
+                .iter()
+                .any(|advice| advice.formula == chemical.display)
+            {
+                found.push(UnwrittenCharge {
+                    formula: chemical.display.clone(),
+                    charge,
+                });
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ions_written_as_neutral() {
+        let species = ["NaOH", "H2SO4", "Na", "SO4", "OH", "NO2", "OH"]
+            .iter()
+            .map(|formula| parse_chemical(formula).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                UnwrittenCharge {
+                    formula: "SO4".into(),
+                    charge: -2
+                },
+                UnwrittenCharge {
+                    formula: "OH".into(),
+                    charge: -1
+                },
+            ],
+            unwritten_charges(&species)
+        );
+        assert_eq!("2-", format_charge(-2));
+        assert_eq!("+", format_charge(1));
+    }
+
+    #[test]
+    fn tally_of_ionic_reaction() {
+        let parse = |formulas: &[&str]| -> Vec<Chemical> {
+            formulas
+                .iter()
+                .map(|formula| parse_chemical(formula).unwrap())
+                .collect()
+        };
+        let reaction = Reaction::new(
+            parse(&["Cu", "Ag+"]),
+            parse(&["Cu2+", "Ag"]),
+            vec![1, 2, 1, 2],
+        );
+        let tally = charge_tally(&reaction).unwrap();
+        assert_eq!(
+            vec![1, 2],
+            tally
+                .shares
+                .iter()
+                .map(|share| share.index)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Side::Product, tally.shares[1].side);
+        assert_eq!((2, 2), (tally.reagents, tally.products));
+        assert!(tally.is_conserved());
+        assert_eq!("+2", format_total(tally.reagents));
+        assert_eq!("0", format_total(0));
+        let neutral = Reaction::new(parse(&["H2", "O2"]), parse(&["H2O"]), vec![2, 1, 2]);
+        assert_eq!(None, charge_tally(&neutral));
+    }
+}
@@ -1,12 +1,462 @@
-use std::collections::HashMap;
+use crate::alias::Aliases;
+use crate::charge::{format_charge, COMMON_IONS, MONATOMIC_IONS};
+use crate::element::find_element;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Chemical {
-    pub parts: HashMap<String, usize>,
+    pub parts: BTreeMap<String, usize>,
     pub display: String,
+    /// Condensed fragments of `display` in written order, e.g. `CH3`, `CH2`, `OH` for `CH3CH2OH`.
+    pub fragments: Vec<String>,
+    /// Net charge as written, e.g. `-2` for `SO4^2-`.
+    pub charge: i32,
+    /// State written after the formula, which takes no part in balancing.
+    pub phase: Option<Phase>,
 }
 
+/// A state of matter written after a formula, such as `(aq)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Phase {
+    Solid,
+    Liquid,
+    Gas,
+    Aqueous,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Solid, Phase::Liquid, Phase::Gas, Phase::Aqueous];
+
+    /// The state symbol as it is written, like `(aq)`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Phase::Solid => "(s)",
+            Phase::Liquid => "(l)",
+            Phase::Gas => "(g)",
+            Phase::Aqueous => "(aq)",
+        }
+    }
+
+    pub fn message_key(self) -> &'static str {
+        match self {
+            Phase::Solid => "phase.solid",
+            Phase::Liquid => "phase.liquid",
+            Phase::Gas => "phase.gas",
+            Phase::Aqueous => "phase.aqueous",
+        }
+    }
+}
+
+/// Splits a state symbol such as `(aq)`, in any case, off the end of a formula.
+pub fn split_phase(input: &str) -> (&str, Option<Phase>) {
+    for &phase in Phase::ALL.iter() {
+        let start = input.len().saturating_sub(phase.symbol().len());
+        let written = input.get(start..).unwrap_or_default();
+        if written.eq_ignore_ascii_case(phase.symbol()) {
+            return (input[..start].trim_end(), Some(phase));
+        }
+    }
+    (input, None)
+}
+
+impl Chemical {
+    /// Formula identifying the species regardless of how it was written, with
+    /// any charge after a `^` so that it cannot run into the last count.
+    pub fn canonical_formula(&self) -> String {
+        if self.is_electron() {
+            return "e-".into();
+        }
+        let formula = hill_formula(&self.parts);
+        match self.charge {
+            0 => formula,
+            charge => formula + "^" + &format_charge(charge),
+        }
+    }
+
+    /// The electron of a half-reaction, which has a charge but no elements.
+    pub fn is_electron(&self) -> bool {
+        self.parts.is_empty() && self.charge == -1
+    }
+
+    /// Returns `None` if any part is not a known element.
+    pub fn molar_mass(&self) -> Option<f64> {
+        self.parts
+            .iter()
+            .map(|(name, &count)| {
+                find_element(name).map(|(_, element)| element.mass * count as f64)
+            })
+            .sum()
+    }
+
+    /// Mass percentage of each element, ordered by atomic number.
+    pub fn mass_percents(&self) -> Option<Vec<(String, f64)>> {
+        let molar_mass = self.molar_mass()?;
+        let mut percents = Vec::new();
+        for (name, &count) in &self.parts {
+            let (number, element) = find_element(name)?;
+            let percent = element.mass * count as f64 / molar_mass * 100.0;
+            percents.push((number, name.clone(), percent));
+        }
+        percents.sort_by_key(|&(number, _, _)| number);
+        Some(
+            percents
+                .into_iter()
+                .map(|(_, name, percent)| (name, percent))
+                .collect(),
+        )
+    }
+
+    /// Degree of unsaturation of an organic compound made of C, H, N, O, S and halogens.
+    pub fn degree_of_unsaturation(&self) -> Option<f64> {
+        let mut doubled = 2i64;
+        for (name, &count) in &self.parts {
+            let count = count as i64;
+            match name.as_str() {
+                "C" => doubled += 2 * count,
+                "N" => doubled += count,
+                "H" | "F" | "Cl" | "Br" | "I" => doubled -= count,
+                "O" | "S" => {}
+                _ => return None,
+            }
+        }
+        if self.parts.contains_key("C") {
+            Some(doubled as f64 / 2.0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Which syntax beyond plain formulas the parser accepts, so that embedders
+/// and teachers can lock it down. The default accepts all but lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize), serde(default))]
+pub struct ParserConfig {
+    /// State symbols such as `(aq)`, kept with their species.
+    pub allow_phases: bool,
+    /// Charges such as `SO4^2-` or `Cu2+`.
+    pub allow_charges: bool,
+    /// User-defined aliases such as `EtOH`.
+    pub allow_names: bool,
+    /// Formulas typed all in lowercase such as `nacl`, read with the case fixed.
+    pub allow_lowercase: bool,
+    /// Built-in organic groups among the aliases, such as `Ph`.
+    pub allow_abbreviations: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            allow_phases: true,
+            allow_charges: true,
+            allow_names: true,
+            allow_lowercase: false,
+            allow_abbreviations: true,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Accepts plain formulas only.
+    pub fn strict() -> Self {
+        Self {
+            allow_phases: false,
+            allow_charges: false,
+            allow_names: false,
+            allow_lowercase: false,
+            allow_abbreviations: false,
+        }
+    }
+
+    /// Accepts everything the parser can read.
+    pub fn lenient() -> Self {
+        Self {
+            allow_phases: true,
+            allow_charges: true,
+            allow_names: true,
+            allow_lowercase: true,
+            allow_abbreviations: true,
+        }
+    }
+
+    /// `aliases` without the ones this configuration turns off.
+    pub fn allowed_aliases(&self, aliases: &Aliases) -> Aliases {
+        let mut allowed = aliases.clone();
+        allowed.entries.retain(|alias| {
+            if alias.is_organic_group() {
+                self.allow_abbreviations
+            } else {
+                self.allow_names
+            }
+        });
+        allowed
+    }
+}
+
+/// Symbol of the electron, written with its charge as `e-`, `e⁻` or `e^-`.
+const ELECTRON: &str = "e";
+
+/// Whether `formula` is written as an electron.
+pub fn is_electron(formula: &str) -> bool {
+    split_charge(&plain_formula(formula)) == Some((ELECTRON, -1))
+}
+
+/// Separators of the water of crystallization in hydrates such as `CuSO4·5H2O`.
+pub const HYDRATE_SEPARATORS: [char; 3] = ['·', '.', '*'];
+
 pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
+    let input = input.as_ref();
+    if let Some(smiles) = input.strip_prefix("smiles:") {
+        return crate::smiles::parse_smiles(smiles);
+    }
+    let (written, phase) = split_phase(input);
+    if written.is_empty() {
+        return None;
+    }
+    let plain = plain_formula(written);
+    let (formula, charge) = split_charge(&plain)?;
+    if formula == ELECTRON {
+        return (charge == -1).then(|| Chemical {
+            parts: BTreeMap::new(),
+            display: written.into(),
+            fragments: vec![formula.into()],
+            charge,
+            phase,
+        });
+    }
+    let mut chemical = match formula.find(&HYDRATE_SEPARATORS[..]) {
+        Some(index) => parse_hydrate(formula, index),
+        None => parse_formula(formula),
+    }?;
+    if charge != 0 {
+        chemical.charge = charge;
+        chemical.display = written.into();
+    }
+    chemical.phase = phase;
+    Some(chemical)
+}
+
+/// Writes a formula pasted with Unicode subscripts and superscripts, like
+/// `SO₄²⁻`, in plain characters. Superscripts ending the formula with a sign
+/// are its charge, so they are read exactly like a charge after a caret.
+fn plain_formula(written: &str) -> String {
+    let formula = written.trim_end_matches(is_superscript);
+    let superscripts = &written[formula.len()..];
+    let mut plain: String = formula
+        .chars()
+        .map(|c| plain_superscript(plain_subscript(c)))
+        .collect();
+    if superscripts.contains(&['⁺', '⁻'][..]) && !formula.contains('^') {
+        plain.push('^');
+    }
+    plain.extend(superscripts.chars().map(plain_superscript));
+    plain
+}
+
+fn is_superscript(c: char) -> bool {
+    matches!(c, '⁺' | '⁻' | '⁰' | '¹' | '²' | '³' | '⁴'..='⁹')
+}
+
+/// The plain form of a subscript digit, or `c` itself if it has none.
+pub fn plain_subscript(c: char) -> char {
+    match c {
+        '₀'..='₉' => core::char::from_u32(c as u32 - '₀' as u32 + '0' as u32).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// The plain form of a superscript digit or sign, or `c` itself if it has none.
+pub fn plain_superscript(c: char) -> char {
+    match c {
+        '⁺' => '+',
+        '⁻' | '−' => '-',
+        '⁰' => '0',
+        '¹' => '1',
+        '²' => '2',
+        '³' => '3',
+        '⁴'..='⁹' => core::char::from_u32(c as u32 - '⁴' as u32 + '4' as u32).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Splits the charge off the end of a formula. After a caret, as in `SO4^2-`,
+/// the charge is read exactly; otherwise a run of signs, as in `OH-` or `O--`,
+/// or a digit and a sign, as in `Cu2+`, is. That digit stays in the formula
+/// unless the formula reads as a known ion of the larger charge without it, as
+/// in `SO42-` or `Fe3+`, and not as one of charge 1 with it, as in `NH4+`.
+fn split_charge(input: &str) -> Option<(&str, i32)> {
+    if let Some((formula, charge)) = input.split_once('^') {
+        if formula.is_empty() {
+            return None;
+        }
+        return Some((formula, parse_charge(charge)?));
+    }
+    let unsigned = input.trim_end_matches(&['+', '-'][..]);
+    let signs = &input[unsigned.len()..];
+    if signs.is_empty() {
+        return Some((input, 0));
+    }
+    let sign = repeated_sign(signs)?;
+    if unsigned.is_empty() {
+        return None;
+    }
+    if sign.abs() > 1 {
+        return Some((unsigned, sign));
+    }
+    // The count after a bracketed complex, as in `[Fe(CN)6]3-`, is its charge
+    let complex = unsigned.trim_end_matches(|c: char| c.is_ascii_digit());
+    let bracketed = complex
+        .chars()
+        .next()
+        .and_then(closing_bracket)
+        .filter(|&close| close != ')');
+    if complex.len() < unsigned.len() && bracketed.is_some_and(|close| complex.ends_with(close)) {
+        let size: i32 = unsigned[complex.len()..]
+            .parse()
+            .ok()
+            .filter(|&size| size > 0)?;
+        return Some((complex, sign * size));
+    }
+    let size = match unsigned.chars().last().and_then(|c| c.to_digit(10)) {
+        Some(size) if size > 1 => size as i32,
+        _ => return Some((unsigned, sign)),
+    };
+    let without = &unsigned[..unsigned.len() - 1];
+    if !is_known_ion(unsigned, sign) && is_known_ion(without, sign * size) {
+        Some((without, sign * size))
+    } else {
+        Some((unsigned, sign))
+    }
+}
+
+/// Whether `formula` is written like one of the known ions of charge `charge`.
+fn is_known_ion(formula: &str, charge: i32) -> bool {
+    COMMON_IONS
+        .iter()
+        .chain(&MONATOMIC_IONS)
+        .any(|&(ion, ion_charge)| ion == formula && ion_charge == charge)
+}
+
+/// Whether the digit before a single sign ending the formula could be either
+/// its last count or its charge, as in `B4O72-`, because no known ion is
+/// written like it either way. `split_charge` reads such a digit as a count.
+pub fn has_ambiguous_charge(input: &str) -> bool {
+    let plain = plain_formula(split_phase(input).0);
+    if plain.contains('^') {
+        return false;
+    }
+    let unsigned = plain.trim_end_matches(&['+', '-'][..]);
+    let sign = match repeated_sign(&plain[unsigned.len()..]) {
+        Some(sign) if sign.abs() == 1 => sign,
+        _ => return false,
+    };
+    let size = match unsigned.chars().last().and_then(|c| c.to_digit(10)) {
+        Some(size) if size > 1 => size as i32,
+        _ => return false,
+    };
+    let without = &unsigned[..unsigned.len() - 1];
+    // The count after a bracketed complex is always its charge
+    let complex = unsigned.trim_end_matches(|c: char| c.is_ascii_digit());
+    !without.is_empty()
+        && !complex.ends_with(&[']', '}'][..])
+        && !is_known_ion(unsigned, sign)
+        && !is_known_ion(without, sign * size)
+}
+
+/// Reads a charge written after a caret, like `2-`, `+` or `++`.
+fn parse_charge(written: &str) -> Option<i32> {
+    let signs = written.trim_start_matches(|c: char| c.is_ascii_digit());
+    let sign = repeated_sign(signs)?;
+    match &written[..written.len() - signs.len()] {
+        "" => Some(sign),
+        _ if sign.abs() > 1 => None,
+        size => size
+            .parse::<i32>()
+            .ok()
+            .filter(|&size| size > 0)
+            .map(|size| sign * size),
+    }
+}
+
+/// The charge of a run of one sign, like `-1` for `-` or `3` for `+++`.
+fn repeated_sign(signs: &str) -> Option<i32> {
+    let first = signs.chars().next()?;
+    let sign = match first {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    if signs.chars().all(|c| c == first) {
+        Some(sign * signs.len() as i32)
+    } else {
+        None
+    }
+}
+
+/// Parses a hydrate whose separator is at `index`, adding the water of
+/// crystallization after it, times the count it starts with, to the salt.
+fn parse_hydrate(input: &str, index: usize) -> Option<Chemical> {
+    let salt = parse_formula(&input[..index])?;
+    let separator = input[index..].chars().next()?;
+    let water = &input[index + separator.len_utf8()..];
+    let formula = water.trim_start_matches(|c: char| c.is_ascii_digit());
+    let count = match &water[..water.len() - formula.len()] {
+        "" => 1,
+        digits => digits.parse().ok().filter(|&count| count > 0)?,
+    };
+    let water = parse_chemical(formula)?;
+    if salt.parts.is_empty() || water.parts.is_empty() {
+        return None;
+    }
+    let mut parts = salt.parts;
+    for (name, water_count) in water.parts {
+        create_or_add(&mut parts, name, water_count * count);
+    }
+    let mut fragments = salt.fragments;
+    fragments.push(input[index..].into());
+    Some(Chemical {
+        parts,
+        display: input.into(),
+        fragments,
+        charge: 0,
+        phase: None,
+    })
+}
+
+/// The bracket closing `open`, for each kind of bracket a formula groups with.
+pub fn closing_bracket(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// Whether every bracket of `formula` is closed, in order, by one of its kind.
+pub fn brackets_match(formula: &str) -> bool {
+    let mut closing = Vec::new();
+    for c in formula.chars() {
+        if let Some(close) = closing_bracket(c) {
+            closing.push(close);
+        } else if matches!(c, ')' | ']' | '}') && closing.pop() != Some(c) {
+            return false;
+        }
+    }
+    closing.is_empty()
+}
+
+fn parse_formula(input: &str) -> Option<Chemical> {
     enum State {
         None,
         ShallowLetter,
@@ -21,12 +471,28 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
     let mut name = String::new();
     let mut count = 0usize;
     let mut composite_count = 0usize;
-    let mut parts = HashMap::new();
+    let mut parts = BTreeMap::new();
     let mut parts_stack = Vec::new();
     let mut state = State::None;
-    let input = input.as_ref();
+    let mut fragments = Vec::new();
+    let mut fragment_start = 0;
+    let mut closing = Vec::new();
 
-    for c in input.chars() {
+    for (index, c) in input.char_indices() {
+        // Square and curly brackets group like parentheses, but only close their own kind
+        let c = match c {
+            '(' | '[' | '{' => {
+                closing.push(closing_bracket(c)?);
+                '('
+            }
+            ')' | ']' | '}' => {
+                if closing.pop() != Some(c) {
+                    return None;
+                }
+                ')'
+            }
+            c => c,
+        };
         match (state, c) {
             (State::None, 'A'..='Z') => {
                 name.push(c);
@@ -34,7 +500,7 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
             }
             (State::None, '(') => {
                 parts_stack.push(parts);
-                parts = HashMap::new();
+                parts = BTreeMap::new();
                 state = State::DeepNone;
             }
             (State::ShallowLetter, 'A'..='Z') => {
@@ -53,13 +519,19 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
             }
             (State::ShallowLetter, '(') | (State::DeepLetter, '(') => {
                 create_or_add(&mut parts, name, 1);
+                if parts_stack.is_empty() {
+                    fragments.push(input[fragment_start..index].into());
+                    fragment_start = index;
+                }
                 name = String::new();
                 parts_stack.push(parts);
-                parts = HashMap::new();
+                parts = BTreeMap::new();
                 state = State::DeepNone;
             }
             (State::ShallowDigit, 'A'..='Z') => {
                 create_or_add(&mut parts, name, count);
+                fragments.push(input[fragment_start..index].into());
+                fragment_start = index;
                 name = String::new();
                 name.push(c);
                 state = State::ShallowLetter;
@@ -70,9 +542,13 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
             }
             (State::ShallowDigit, '(') | (State::DeepDigit, '(') => {
                 create_or_add(&mut parts, name, count);
+                if parts_stack.is_empty() {
+                    fragments.push(input[fragment_start..index].into());
+                    fragment_start = index;
+                }
                 name = String::new();
                 parts_stack.push(parts);
-                parts = HashMap::new();
+                parts = BTreeMap::new();
                 state = State::DeepNone;
             }
             (State::DeepNone, 'A'..='Z') => {
@@ -129,6 +605,10 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
                     }
                 }
                 parts = saved_parts;
+                if parts_stack.is_empty() && c != ')' {
+                    fragments.push(input[fragment_start..index].into());
+                    fragment_start = index;
+                }
                 match c {
                     'A'..='Z' => {
                         name.push(c);
@@ -143,7 +623,7 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
                     }
                     '(' => {
                         parts_stack.push(parts);
-                        parts = HashMap::new();
+                        parts = BTreeMap::new();
                         // It is guaranteed that name is an empty String, making no new allocation needed
                         state = State::DeepNone;
                     }
@@ -167,6 +647,10 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
                     }
                 }
                 parts = saved_parts;
+                if parts_stack.is_empty() && c != ')' {
+                    fragments.push(input[fragment_start..index].into());
+                    fragment_start = index;
+                }
                 match c {
                     'A'..='Z' => {
                         name.push(c);
@@ -181,7 +665,7 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
                     }
                     '(' => {
                         parts_stack.push(parts);
-                        parts = HashMap::new();
+                        parts = BTreeMap::new();
                         // Refer to (State::DeepEnd, '(')
                         state = State::DeepNone;
                     }
@@ -191,6 +675,9 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
             _ => return None,
         }
     }
+    if !closing.is_empty() {
+        return None;
+    }
     match state {
         State::ShallowLetter => {
             create_or_add(&mut parts, name, 1);
@@ -220,14 +707,125 @@ pub fn parse_chemical(input: impl AsRef<str>) -> Option<Chemical> {
         State::None => {}
         State::DeepNone | State::DeepLetter | State::DeepDigit => return None,
     }
+    if fragment_start < input.len() {
+        fragments.push(input[fragment_start..].into());
+    }
 
     Some(Chemical {
         parts,
         display: input.into(),
+        fragments,
+        charge: 0,
+        phase: None,
+    })
+}
+
+/// Parses the formula with `aliases` expanded, keeping it as written for display.
+pub fn parse_chemical_with(input: impl AsRef<str>, aliases: &Aliases) -> Option<Chemical> {
+    let input = input.as_ref();
+    let expanded = aliases.expand(input);
+    let mut chemical = parse_chemical(&expanded)?;
+    if expanded != input {
+        let (written, _) = split_phase(input);
+        let fragment = split_charge(written).map_or(written, |(formula, _)| formula);
+        chemical.display = written.into();
+        chemical.fragments = vec![fragment.into()];
+    }
+    Some(chemical)
+}
+
+/// Parses the formula with the `aliases` that `config` allows expanded,
+/// failing on syntax it does not allow.
+pub fn parse_chemical_with_config(
+    input: impl AsRef<str>,
+    aliases: &Aliases,
+    config: &ParserConfig,
+) -> Option<Chemical> {
+    let input = input.as_ref();
+    let fixed = if config.allow_lowercase {
+        fix_case(input)
+    } else {
+        None
+    };
+    let formula = fixed.as_deref().unwrap_or(input);
+    parse_chemical_with(formula, &config.allowed_aliases(aliases)).filter(|chemical| {
+        (config.allow_charges || chemical.charge == 0)
+            && (config.allow_phases || chemical.phase.is_none())
+    })
+}
+
+/// The formula typed all in lowercase, like `nacl`, with its element symbols
+/// capitalized, trying one-letter symbols before two-letter ones. `None` if
+/// it has an uppercase letter or cannot be read as elements.
+pub fn fix_case(formula: &str) -> Option<String> {
+    let lowercase = formula.chars().any(char::is_alphabetic)
+        && !formula.chars().any(char::is_uppercase)
+        && !formula.starts_with("smiles:");
+    let mut fixed = String::new();
+    if lowercase && capitalize_symbols(formula, &mut fixed) {
+        Some(fixed)
+    } else {
+        None
+    }
+}
+
+fn capitalize_symbols(rest: &str, fixed: &mut String) -> bool {
+    let first = match rest.chars().next() {
+        Some(first) => first,
+        None => return true,
+    };
+    if !first.is_ascii_lowercase() {
+        fixed.push(first);
+        return capitalize_symbols(&rest[first.len_utf8()..], fixed);
+    }
+    let length = fixed.len();
+    for size in 1..=2 {
+        let symbol = match rest.get(..size) {
+            Some(symbol) if symbol.chars().all(|c| c.is_ascii_lowercase()) => symbol,
+            _ => break,
+        };
+        fixed.push_str(&symbol[..1].to_ascii_uppercase());
+        fixed.push_str(&symbol[1..]);
+        if find_element(&fixed[length..]).is_some() && capitalize_symbols(&rest[size..], fixed) {
+            return true;
+        }
+        fixed.truncate(length);
+    }
+    false
+}
+
+/// Writes element counts in Hill order: carbon, hydrogen, then the rest alphabetically.
+pub fn hill_formula(parts: &BTreeMap<String, usize>) -> String {
+    let mut elements: Vec<_> = parts.iter().filter(|(_, &count)| count > 0).collect();
+    let has_carbon = parts.contains_key("C");
+    elements.sort_by_key(|&(name, _)| match name.as_str() {
+        "C" if has_carbon => (0, name.as_str()),
+        "H" if has_carbon => (1, name.as_str()),
+        _ => (2, name.as_str()),
+    });
+    let mut formula = String::new();
+    for (name, count) in elements {
+        formula.push_str(name);
+        if *count > 1 {
+            formula.push_str(&count.to_string());
+        }
+    }
+    formula
+}
+
+/// Whether the text holds Hangul, including the lone jamo a Korean IME leaves mid-composition.
+pub fn contains_hangul(input: &str) -> bool {
+    input.chars().any(|c| {
+        matches!(c,
+            '\u{1100}'..='\u{11ff}'
+            | '\u{3130}'..='\u{318f}'
+            | '\u{a960}'..='\u{a97f}'
+            | '\u{ac00}'..='\u{d7a3}'
+            | '\u{d7b0}'..='\u{d7ff}')
     })
 }
 
-fn create_or_add(map: &mut HashMap<String, usize>, key: String, value: usize) {
+fn create_or_add(map: &mut BTreeMap<String, usize>, key: String, value: usize) {
     if let Some(previous_value) = map.get_mut(&key) {
         *previous_value += value;
     } else {
@@ -239,6 +837,13 @@ fn create_or_add(map: &mut HashMap<String, usize>, key: String, value: usize) {
 mod test {
     use super::*;
 
+    #[test]
+    fn contains_hangul_test() {
+        assert!(contains_hangul("H2ㅒ"));
+        assert!(contains_hangul("물"));
+        assert!(!contains_hangul("H2O"));
+    }
+
     #[test]
     fn parse_chemical_test_shallow() {
         let output = parse_chemical("CH3COONa").unwrap().parts;
@@ -248,6 +853,154 @@ mod test {
         assert_eq!(1, output["Na"]);
     }
 
+    #[test]
+    fn parse_chemical_test_hydrate() {
+        let hydrate = parse_chemical("CuSO4·5H2O").unwrap();
+        assert_eq!(1, hydrate.parts["Cu"]);
+        assert_eq!(9, hydrate.parts["O"]);
+        assert_eq!(10, hydrate.parts["H"]);
+        assert_eq!("CuSO4·5H2O", hydrate.display);
+        assert_eq!(vec!["CuSO4", "·5H2O"], hydrate.fragments);
+        assert_eq!(hydrate.parts, parse_chemical("CuSO4.5H2O").unwrap().parts);
+        assert_eq!(hydrate.parts, parse_chemical("CuSO4*5H2O").unwrap().parts);
+        assert_eq!(2, parse_chemical("CaSO4·H2O").unwrap().parts["H"]);
+        assert!(parse_chemical("CuSO4·").is_none());
+        assert!(parse_chemical("·5H2O").is_none());
+        assert!(parse_chemical("CuSO4·0H2O").is_none());
+    }
+
+    #[test]
+    fn parse_chemical_test_charge() {
+        let charge = |input| parse_chemical(input).map(|chemical| chemical.charge);
+        assert_eq!(Some(-2), charge("SO4^2-"));
+        assert_eq!(Some(3), charge("Fe^3+"));
+        assert_eq!(Some(-1), charge("OH-"));
+        assert_eq!(Some(2), charge("Cu2+"));
+        assert_eq!(Some(-2), charge("SO42-"));
+        assert_eq!(Some(1), charge("NH4+"));
+        assert_eq!(Some(-1), charge("H2PO4-"));
+        assert_eq!(Some(-2), charge("O--"));
+        assert_eq!(Some(3), charge("Fe3+"));
+        let oxalate = parse_chemical("C2O42-").unwrap();
+        assert_eq!((4, -2), (oxalate.parts["O"], oxalate.charge));
+        let thiosulfate = parse_chemical("S2O32-").unwrap();
+        assert_eq!((3, -2), (thiosulfate.parts["O"], thiosulfate.charge));
+        let mercury = parse_chemical("Hg22+").unwrap();
+        assert_eq!((2, 2), (mercury.parts["Hg"], mercury.charge));
+        assert!(has_ambiguous_charge("B4O72-"));
+        assert!(has_ambiguous_charge("O2+"));
+        for written in [
+            "C2O42-",
+            "Hg22+",
+            "Cu2+",
+            "NH4+",
+            "B4O7^2-",
+            "[Cu(NH3)4]2+",
+            "OH-",
+        ] {
+            assert!(!has_ambiguous_charge(written), "{}", written);
+        }
+        let dihydrogen = parse_chemical("H2+").unwrap();
+        assert_eq!((2, 1), (dihydrogen.parts["H"], dihydrogen.charge));
+        let dioxygen = parse_chemical("O2+").unwrap();
+        assert_eq!((2, 1), (dioxygen.parts["O"], dioxygen.charge));
+        assert_eq!(Some(3), charge("Fe³⁺"));
+        assert_eq!(None, charge("Fe^+-"));
+        assert_eq!(None, charge("^2+"));
+        let sulfate = parse_chemical("SO42-").unwrap();
+        assert_eq!(4, sulfate.parts["O"]);
+        assert_eq!("SO42-", sulfate.display);
+        assert_eq!("O4S^2-", sulfate.canonical_formula());
+        let dithionite = parse_chemical("S2O4^-").unwrap();
+        assert_eq!("O4S2^-", dithionite.canonical_formula());
+        assert_ne!(
+            parse_chemical("SO4^2-").unwrap().canonical_formula(),
+            dithionite.canonical_formula()
+        );
+    }
+
+    #[test]
+    fn parse_chemical_test_phase() {
+        let ion = parse_chemical("Fe^3+(aq)").unwrap();
+        assert_eq!((3, Some(Phase::Aqueous)), (ion.charge, ion.phase));
+        assert_eq!("Fe^3+", ion.display);
+        let water = parse_chemical("H2O (L)").unwrap();
+        assert_eq!(Some(Phase::Liquid), water.phase);
+        assert_eq!(2, water.parts["H"]);
+        assert_eq!(None, parse_chemical("NaCl").unwrap().phase);
+        assert!(parse_chemical("(aq)").is_none());
+        assert!(parse_chemical(" (s)").is_none());
+    }
+
+    #[test]
+    fn parse_chemical_test_unicode_scripts() {
+        let water = parse_chemical("H₂O").unwrap();
+        assert_eq!((2, 1), (water.parts["H"], water.parts["O"]));
+        assert_eq!("H2O", water.display);
+        let sulfate = parse_chemical("SO₄²⁻").unwrap();
+        assert_eq!((4, -2), (sulfate.parts["O"], sulfate.charge));
+        assert_eq!("SO₄²⁻", sulfate.display);
+        assert_eq!(3, parse_chemical("Fe³⁺").unwrap().charge);
+        let mercury = parse_chemical("Hg₂²⁺").unwrap();
+        assert_eq!((2, 2), (mercury.parts["Hg"], mercury.charge));
+        assert_eq!(1, parse_chemical("NH₄⁺").unwrap().charge);
+        assert!(parse_chemical("⁺").is_none());
+    }
+
+    #[test]
+    fn parse_chemical_test_electron() {
+        for written in &["e-", "e⁻", "e^-"] {
+            let electron = parse_chemical(written).unwrap();
+            assert!(electron.is_electron());
+            assert!(electron.parts.is_empty());
+            assert_eq!("e-", electron.canonical_formula());
+            assert!(is_electron(written));
+        }
+        assert!(parse_chemical("e+").is_none());
+        assert!(parse_chemical("e").is_none());
+        assert!(!is_electron("Fe-"));
+    }
+
+    #[test]
+    fn parse_chemical_test_brackets() {
+        let salt = parse_chemical("K3[Fe(CN)6]").unwrap();
+        assert_eq!(3, salt.parts["K"]);
+        assert_eq!(1, salt.parts["Fe"]);
+        assert_eq!(6, salt.parts["C"]);
+        assert_eq!(6, salt.parts["N"]);
+        assert_eq!(vec!["K3", "[Fe(CN)6]"], salt.fragments);
+        let complex = parse_chemical("[Fe(CN)6]3-").unwrap();
+        assert_eq!(-3, complex.charge);
+        assert_eq!(6, complex.parts["C"]);
+        let complex = parse_chemical("{Cu(NH3)4}2+").unwrap();
+        assert_eq!((2, 12), (complex.charge, complex.parts["H"]));
+        assert_eq!(2, parse_chemical("[Cu(H2O)6]2").unwrap().parts["Cu"]);
+        assert!(parse_chemical("[Fe(CN)6)").is_none());
+        assert!(parse_chemical("(H2O]").is_none());
+        assert!(parse_chemical("(H))").is_none());
+        assert!(parse_chemical("((H)").is_none());
+        assert!(brackets_match("K3[Fe(CN)6]"));
+        assert!(!brackets_match("K3[Fe(CN)6"));
+    }
+
+    #[test]
+    fn parse_chemical_test_config() {
+        let strict = ParserConfig::strict();
+        let lenient = ParserConfig::lenient();
+        let aliases = Aliases::default().with_organic_groups(false);
+        let parse = |input, config| parse_chemical_with_config(input, &aliases, &config);
+        assert!(parse("Fe^3+", strict).is_none());
+        // Without the group `Ph` reads as an unknown element
+        assert!(!parse("PhOH", strict).unwrap().parts.contains_key("C"));
+        assert!(parse("nacl", ParserConfig::default()).is_none());
+        assert_eq!("NaCl", parse("nacl", lenient).unwrap().display);
+        assert_eq!(1, parse("PhOH", lenient).unwrap().parts["O"]);
+        assert_eq!(Some("CO2".into()), fix_case("co2"));
+        assert_eq!(Some("KMnO4".into()), fix_case("kmno4"));
+        assert_eq!(None, fix_case("NaCl"));
+        assert_eq!(None, fix_case("xyz"));
+    }
+
     #[test]
     fn parse_chemical_test_deep() {
         let output = parse_chemical("(MgFe)2(MgFe)(OH)2Si8O22").unwrap().parts;
@@ -257,4 +1010,30 @@ mod test {
         assert_eq!(2, output["H"]);
         assert_eq!(8, output["Si"]);
     }
+
+    #[test]
+    fn chemical_analysis() {
+        let output = parse_chemical("H2O").unwrap();
+        assert!((output.molar_mass().unwrap() - 18.015).abs() < 1e-9);
+        let percents = output.mass_percents().unwrap();
+        assert_eq!("H", percents[0].0);
+        assert!((percents[1].1 - 88.81).abs() < 0.01);
+        assert_eq!(None, output.degree_of_unsaturation());
+        let output = parse_chemical("C6H6").unwrap();
+        assert_eq!(Some(4.0), output.degree_of_unsaturation());
+    }
+
+    #[test]
+    fn parse_chemical_test_smiles() {
+        let output = parse_chemical("smiles:CCO").unwrap();
+        assert_eq!("C2H6O", output.display);
+    }
+
+    #[test]
+    fn parse_chemical_test_fragments() {
+        let output = parse_chemical("CH3CH2OH").unwrap().fragments;
+        assert_eq!(vec!["CH3", "CH2", "OH"], output);
+        let output = parse_chemical("Ca(OH)2").unwrap().fragments;
+        assert_eq!(vec!["Ca", "(OH)2"], output);
+    }
 }
@@ -0,0 +1,116 @@
+//! Kinds of reaction a balanced equation is recognized as, used to tag it.
+
+use crate::acid_base::is_acid_base;
+use crate::chemical::{parse_chemical, Chemical};
+use crate::oxidation::{oxidation_report, redox_kind};
+use crate::reaction::Reaction;
+use alloc::vec::Vec;
+
+/// Salts and hydroxides that barely dissolve in water.
+pub static PRECIPITATES: [&str; 20] = [
+    "AgCl",
+    "AgBr",
+    "AgI",
+    "Ag2CO3",
+    "Ag2CrO4",
+    "Ag2S",
+    "BaSO4",
+    "BaCO3",
+    "CaCO3",
+    "Ca3(PO4)2",
+    "PbSO4",
+    "PbCl2",
+    "PbI2",
+    "PbS",
+    "CuS",
+    "Cu(OH)2",
+    "Fe(OH)2",
+    "Fe(OH)3",
+    "Mg(OH)2",
+    "Al(OH)3",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReactionType {
+    /// Burning in oxygen, where every product is an oxide.
+    Combustion,
+    /// An acid neutralized by a base into water.
+    Neutralization,
+    /// Some element changes its oxidation state.
+    Redox,
+    /// An insoluble salt forms without any element changing its state.
+    Precipitation,
+}
+
+impl ReactionType {
+    pub fn message_key(self) -> &'static str {
+        match self {
+            ReactionType::Combustion => "reaction_type.combustion",
+            ReactionType::Neutralization => "reaction_type.neutralization",
+            ReactionType::Redox => "reaction_type.redox",
+            ReactionType::Precipitation => "reaction_type.precipitation",
+        }
+    }
+}
+
+fn is_precipitate(chemical: &Chemical) -> bool {
+    let formula = chemical.canonical_formula();
+    PRECIPITATES.iter().any(|known| {
+        parse_chemical(known).is_some_and(|known| known.canonical_formula() == formula)
+    })
+}
+
+fn is_oxide(chemical: &Chemical) -> bool {
+    chemical.parts.contains_key("O") && chemical.parts.len() <= 2
+}
+
+/// Every kind `reaction` is recognized as, in the order of `ReactionType`.
+pub fn classify(reaction: &Reaction) -> Vec<ReactionType> {
+    let mut types = Vec::new();
+    let oxygen = reaction
+        .reagents
+        .iter()
+        .any(|chemical| chemical.canonical_formula() == "O2");
+    if oxygen && reaction.reagents.len() >= 2 && reaction.products.iter().all(is_oxide) {
+        types.push(ReactionType::Combustion);
+    }
+    if is_acid_base(reaction) {
+        types.push(ReactionType::Neutralization);
+    }
+    let redox = oxidation_report(reaction)
+        .and_then(|changes| redox_kind(&changes))
+        .is_some();
+    if redox {
+        types.push(ReactionType::Redox);
+    }
+    let precipitates = !reaction.reagents.iter().any(is_precipitate)
+        && reaction.products.iter().any(is_precipitate);
+    if precipitates && !redox {
+        types.push(ReactionType::Precipitation);
+    }
+    types
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+    use crate::reaction::calculate_coefficients;
+    use alloc::vec;
+
+    fn types(input: &str) -> Vec<ReactionType> {
+        let equation = parse_equation(input).unwrap();
+        let coefficients = calculate_coefficients(&equation.reagents, &equation.products).unwrap();
+        classify(&equation.into_reaction(coefficients))
+    }
+
+    #[test]
+    fn classify_reactions() {
+        use ReactionType::*;
+        assert_eq!(vec![Combustion, Redox], types("CH4 + O2 = CO2 + H2O"));
+        assert_eq!(vec![Neutralization], types("HCl + NaOH = NaCl + H2O"));
+        assert_eq!(vec![Redox], types("Zn + HCl = ZnCl2 + H2"));
+        assert_eq!(vec![Precipitation], types("AgNO3 + NaCl = AgCl + NaNO3"));
+        assert_eq!(Vec::<ReactionType>::new(), types("CaCO3 = CaO + CO2"));
+    }
+}
@@ -0,0 +1,72 @@
+//! Molar mass and mass percentages of any formula, without balancing.
+
+use crate::chemical::parse_chemical;
+use crate::tool::{Tool, ToolContext, ToolMsg};
+use seed::{prelude::*, *};
+
+#[derive(Debug, Default)]
+pub struct CompositionTool {
+    formula: String,
+}
+
+impl Tool for CompositionTool {
+    fn title(&self) -> &'static str {
+        "tool.composition"
+    }
+
+    fn icon(&self) -> &'static str {
+        "%"
+    }
+
+    fn init(&mut self) {
+        self.formula.clear();
+    }
+
+    fn update(&mut self, msg: ToolMsg) {
+        if msg.field == 0 {
+            self.formula = msg.value;
+        }
+    }
+
+    fn view(&self, context: &ToolContext) -> Node<ToolMsg> {
+        let i18n = context.i18n;
+        let input = input![
+            attrs! {
+                At::Type => "text",
+                At::Placeholder => "C6H12O6",
+                At::Value => self.formula,
+            },
+            input_ev(Ev::Input, |value| ToolMsg { field: 0, value }),
+        ];
+        if self.formula.trim().is_empty() {
+            return div![input];
+        }
+        let chemical = match parse_chemical(self.formula.trim()) {
+            Some(chemical) => chemical,
+            None => return div![input, p![class!["error"], i18n.t("tool.invalid_formula")]],
+        };
+        let numbers = i18n.number_format();
+        let (molar_mass, percents) = match (chemical.molar_mass(), chemical.mass_percents()) {
+            (Some(molar_mass), Some(percents)) => (molar_mass, percents),
+            _ => return div![input, p![i18n.t("species.unknown_element")]],
+        };
+        div![
+            input,
+            p![i18n.format("species.molar_mass", &[&numbers.format(molar_mass, 3)])],
+            table![
+                tr![
+                    th![i18n.t("species.element")],
+                    th![i18n.t("species.count")],
+                    th![i18n.t("species.mass_percent")],
+                ],
+                percents.iter().map(|(name, percent)| {
+                    tr![
+                        td![name.as_str()],
+                        td![chemical.parts[name].to_string()],
+                        td![format!("{}%", numbers.format(*percent, 2))],
+                    ]
+                }),
+            ],
+        ]
+    }
+}
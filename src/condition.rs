@@ -0,0 +1,85 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A reaction condition written after the equation, as in `N2 + H2 = NH3; 450°C, 200 atm`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Heat,
+    Light,
+    Temperature(String),
+    Pressure(String),
+    Other(String),
+}
+
+impl Condition {
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        let has_number = input.starts_with(|c: char| c.is_ascii_digit() || c == '-');
+        let ends_with_any = |units: &[&str]| units.iter().any(|unit| input.ends_with(unit));
+        match input {
+            "Δ" | "heat" | "가열" => Condition::Heat,
+            "hv" | "hν" | "light" | "빛" => Condition::Light,
+            _ if has_number && ends_with_any(&["°C", "℃", "K"]) => {
+                Condition::Temperature(input.to_string())
+            }
+            _ if has_number && ends_with_any(&["atm", "Pa", "bar", "mmHg", "torr"]) => {
+                Condition::Pressure(input.to_string())
+            }
+            _ => Condition::Other(input.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Heat => f.write_str("Δ"),
+            Condition::Light => f.write_str("hν"),
+            Condition::Temperature(text) | Condition::Pressure(text) | Condition::Other(text) => {
+                f.write_str(text)
+            }
+        }
+    }
+}
+
+/// Whether `text` has none of the characters that start markup, so that text
+/// from outside, such as a shared session, cannot open an element.
+pub fn is_plain_text(text: &str) -> bool {
+    !text.contains(&['<', '>', '&', '"'][..])
+}
+
+/// Splits a trailing `; condition, condition` clause off an equation.
+pub fn split_conditions(input: &str) -> (&str, Vec<Condition>) {
+    match input.find(';') {
+        Some(index) => {
+            let conditions = input[index + 1..]
+                .split(',')
+                .filter(|condition| !condition.trim().is_empty())
+                .map(Condition::parse)
+                .collect();
+            (&input[..index], conditions)
+        }
+        None => (input, Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_conditions_test() {
+        let (equation, conditions) = split_conditions("N2 + H2 = NH3; 450°C, 200 atm, heat");
+        assert_eq!("N2 + H2 = NH3", equation);
+        assert_eq!(
+            vec![
+                Condition::Temperature("450°C".into()),
+                Condition::Pressure("200 atm".into()),
+                Condition::Heat,
+            ],
+            conditions
+        );
+        assert_eq!("Δ", Condition::Heat.to_string());
+    }
+}
@@ -0,0 +1,224 @@
+//! Dimensional analysis between two species of any equation, balanced first,
+//! with every conversion factor labelled.
+
+use crate::equation::{parse_equation, ParseError};
+use crate::i18n::I18n;
+use crate::quantity::Quantity;
+use crate::reaction::{calculate_coefficients, Reaction, ReactionError};
+use crate::renderer::unicode_species;
+use crate::stoichiometry::{conversion_chain, Term};
+use crate::tool::{Tool, ToolContext, ToolMsg};
+use seed::{prelude::*, *};
+
+/// Units the conversion chain can end in.
+const UNITS: [&str; 5] = ["g", "mg", "kg", "mol", "mmol"];
+
+const EQUATION: usize = 0;
+const AMOUNT: usize = 1;
+const FROM: usize = 2;
+const TO: usize = 3;
+const UNIT: usize = 4;
+
+/// Why the typed equation gave no reaction.
+#[derive(Debug)]
+enum Failure {
+    Parse(ParseError),
+    Balance(ReactionError),
+}
+
+impl Failure {
+    fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            Failure::Parse(error) => error.localize(i18n),
+            Failure::Balance(error) => error.localize(i18n),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConversionTool {
+    equation: String,
+    /// The typed equation balanced, once one is typed.
+    reaction: Option<Result<Reaction, Failure>>,
+    /// Amount of species `from`, in g unless a unit is given.
+    amount: String,
+    from: usize,
+    to: usize,
+    unit: String,
+}
+
+fn balance(equation: &str) -> Option<Result<Reaction, Failure>> {
+    if equation.trim().is_empty() {
+        return None;
+    }
+    let equation = match parse_equation(equation) {
+        Ok(equation) => equation,
+        Err(error) => return Some(Err(Failure::Parse(error))),
+    };
+    Some(
+        calculate_coefficients(&equation.reagents, &equation.products)
+            .map(|coefficients| equation.into_reaction(coefficients))
+            .map_err(Failure::Balance),
+    )
+}
+
+impl Tool for ConversionTool {
+    fn title(&self) -> &'static str {
+        "conversion.title"
+    }
+
+    fn icon(&self) -> &'static str {
+        "×"
+    }
+
+    fn init(&mut self) {
+        self.equation.clear();
+        self.reaction = None;
+        self.amount.clear();
+        self.from = 0;
+        self.to = 0;
+        self.unit = "g".into();
+    }
+
+    fn update(&mut self, msg: ToolMsg) {
+        match msg.field {
+            EQUATION => {
+                self.reaction = balance(&msg.value);
+                self.equation = msg.value;
+                // Keep the chosen species while they are still in the equation
+                if let Some(Ok(reaction)) = &self.reaction {
+                    let count = reaction.coefficients.len();
+                    if self.from >= count {
+                        self.from = 0;
+                    }
+                    if self.to >= count || self.to == self.from {
+                        self.to = count - 1;
+                    }
+                }
+            }
+            AMOUNT => self.amount = msg.value,
+            FROM => self.from = msg.value.parse().unwrap_or(self.from),
+            TO => self.to = msg.value.parse().unwrap_or(self.to),
+            UNIT => self.unit = msg.value,
+            _ => {}
+        }
+    }
+
+    fn view(&self, context: &ToolContext) -> Node<ToolMsg> {
+        let i18n = context.i18n;
+        let equation = input![
+            attrs! {
+                At::Type => "text",
+                At::Placeholder => "H2 + O2 = H2O",
+                At::Value => self.equation,
+            },
+            input_ev(Ev::Input, |value| ToolMsg {
+                field: EQUATION,
+                value
+            }),
+        ];
+        let reaction = match &self.reaction {
+            None => return div![equation],
+            Some(Err(failure)) => {
+                return div![equation, p![class!["error"], failure.localize(i18n)]]
+            }
+            Some(Ok(reaction)) => reaction,
+        };
+        let names: Vec<String> = reaction
+            .reagents
+            .iter()
+            .chain(&reaction.products)
+            .map(unicode_species)
+            .collect();
+        let species_select = |field: usize, selected: usize| {
+            select![
+                names.iter().enumerate().map(|(index, name)| {
+                    option![
+                        attrs! {
+                            At::Value => index,
+                            At::Selected => (index == selected).as_at_value(),
+                        },
+                        name.as_str(),
+                    ]
+                }),
+                input_ev(Ev::Change, move |value| ToolMsg { field, value }),
+            ]
+        };
+        let numbers = i18n.number_format();
+        let term = |term: &Term| {
+            // Coefficients and unit sizes are whole numbers, so they get no decimals
+            let decimals = if term.value.fract() == 0.0 { 0 } else { 3 };
+            format!(
+                "{} {} {}",
+                numbers.format(term.value, decimals),
+                term.unit,
+                names[term.species]
+            )
+        };
+        let chain = match self.amount.trim() {
+            "" => empty![],
+            amount => match Quantity::parse_or(amount, "g") {
+                Err(error) => p![class!["error"], error.localize(i18n)],
+                Ok(given) => {
+                    match conversion_chain(reaction, self.from, given, self.to, &self.unit) {
+                        None => p![class!["error"], i18n.t("conversion.unavailable")],
+                        Some(chain) => div![
+                            class!["chain"],
+                            span![term(&chain.given)],
+                            chain.factors.iter().flat_map(|factor| {
+                                vec![
+                                    span![class!["operator"], "×"],
+                                    span![
+                                        class!["factor"],
+                                        span![class!["numerator"], term(&factor.numerator)],
+                                        span![class!["denominator"], term(&factor.denominator)],
+                                        small![i18n.t(factor.kind.message_key())],
+                                    ],
+                                ]
+                            }),
+                            span![class!["operator"], "="],
+                            strong![term(&chain.result)],
+                        ],
+                    }
+                }
+            },
+        };
+        div![
+            class!["conversion"],
+            equation,
+            p![reaction.to_string()],
+            p![
+                input![
+                    attrs! {
+                        At::Type => "text",
+                        At::Value => self.amount,
+                        At::Placeholder => "10 g",
+                    },
+                    input_ev(Ev::Input, |value| ToolMsg {
+                        field: AMOUNT,
+                        value
+                    }),
+                ],
+                " ",
+                species_select(FROM, self.from),
+                " → ",
+                select![
+                    attrs! { At::Title => i18n.t("conversion.unit") },
+                    UNITS.iter().map(|&symbol| {
+                        option![
+                            attrs! {
+                                At::Value => symbol,
+                                At::Selected => (symbol == self.unit).as_at_value(),
+                            },
+                            symbol,
+                        ]
+                    }),
+                    input_ev(Ev::Change, |value| ToolMsg { field: UNIT, value }),
+                ],
+                " ",
+                species_select(TO, self.to),
+            ],
+            chain,
+        ]
+    }
+}
@@ -0,0 +1,109 @@
+//! Regression corpus of equations with known coefficients, kept in `corpus.txt`
+//! as lines of `equation | coefficients`, with `#` starting a comment.
+
+use crate::equation::parse_equation;
+use crate::reaction::{calculate_coefficients, Reaction};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub equation: String,
+    pub coefficients: Vec<i64>,
+}
+
+/// An entry the solver no longer answers as recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub entry: Entry,
+    /// The solved coefficients, or the message of the error met instead.
+    pub actual: Result<Vec<i64>, String>,
+}
+
+/// Reads corpus lines, failing with the 1-based number of the first malformed line.
+pub fn parse_corpus(text: &str) -> Result<Vec<Entry>, usize> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| parse_entry(line).ok_or(index + 1))
+        .collect()
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let (equation, coefficients) = line.rsplit_once('|')?;
+    let coefficients = coefficients
+        .split_whitespace()
+        .map(|coefficient| coefficient.parse().ok())
+        .collect::<Option<Vec<i64>>>()?;
+    Some(Entry {
+        equation: equation.trim().to_string(),
+        coefficients,
+    })
+}
+
+/// Formats a balanced reaction as a corpus line.
+pub fn entry_line(reaction: &Reaction) -> String {
+    let mut unbalanced = reaction.clone();
+    unbalanced.coefficients = vec![1; reaction.coefficients.len()];
+    let coefficients: Vec<String> = reaction
+        .coefficients
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    format!("{} | {}", unbalanced, coefficients.join(" "))
+}
+
+pub fn check(entry: &Entry) -> Result<(), Mismatch> {
+    let actual = parse_equation(&entry.equation)
+        .map_err(|error| error.to_string())
+        .and_then(|equation| {
+            calculate_coefficients(&equation.reagents, &equation.products)
+                .map_err(|error| error.to_string())
+        });
+    if actual.as_ref() == Ok(&entry.coefficients) {
+        Ok(())
+    } else {
+        Err(Mismatch {
+            entry: entry.clone(),
+            actual,
+        })
+    }
+}
+
+pub fn run(entries: &[Entry]) -> Vec<Mismatch> {
+    entries
+        .iter()
+        .filter_map(|entry| check(entry).err())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+
+    #[test]
+    fn corpus_still_balances() {
+        let entries = parse_corpus(include_str!("../corpus.txt"))
+            .unwrap_or_else(|line| panic!("malformed corpus line {}", line));
+        assert!(entries.len() >= 300);
+        let mismatches = run(&entries);
+        assert!(mismatches.is_empty(), "{:#?}", mismatches);
+    }
+
+    #[test]
+    fn entry_line_round_trips() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("H2O").unwrap()],
+            vec![2, 1, 2],
+        );
+        let line = entry_line(&reaction);
+        assert_eq!("H2 + O2 = H2O | 2 1 2", line);
+        let entries = parse_corpus(&line).unwrap();
+        assert_eq!(Ok(()), check(&entries[0]));
+        assert_eq!(Err(2), parse_corpus("# comment\nH2 = H2 | x"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Optional data tables that are either bundled into the binary behind a
+//! `dataset-*` cargo feature or fetched as JSON at runtime, keeping the base
+//! build small.
+
+use crate::chemical::parse_chemical;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "json")]
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Dataset {
+    /// Common names of compounds, as a JSON object from formula to name.
+    CompoundNames,
+    /// Dissociation constants of weak acids and bases at 25 °C, as a JSON
+    /// object from formula to `{"ka": ...}` or `{"kb": ...}`.
+    DissociationConstants,
+    /// Standard enthalpies of formation in kJ/mol and molar entropies in
+    /// J/(mol·K) at 25 °C, as a JSON object from formula to `{"h": ..., "s": ...}`.
+    /// Each formula takes its usual state at 25 °C.
+    Thermochemistry,
+}
+
+impl Dataset {
+    pub const ALL: [Dataset; 3] = [
+        Dataset::CompoundNames,
+        Dataset::DissociationConstants,
+        Dataset::Thermochemistry,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Dataset::CompoundNames => "compound-names",
+            Dataset::DissociationConstants => "dissociation-constants",
+            Dataset::Thermochemistry => "thermochemistry",
+        }
+    }
+
+    /// Where the app fetches the dataset from when it is not bundled.
+    pub fn url(self) -> String {
+        format!("data/{}.json", self.id())
+    }
+
+    /// The JSON compiled into this build, if the dataset's feature is enabled.
+    pub fn bundled(self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "dataset-names")]
+            Dataset::CompoundNames => Some(include_str!("../data/compound-names.json")),
+            #[cfg(feature = "dataset-constants")]
+            Dataset::DissociationConstants => {
+                Some(include_str!("../data/dissociation-constants.json"))
+            }
+            #[cfg(feature = "dataset-thermochemistry")]
+            Dataset::Thermochemistry => Some(include_str!("../data/thermochemistry.json")),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+/// How far a weak acid or base dissociates in water.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dissociation {
+    Ka(f64),
+    Kb(f64),
+}
+
+/// Standard thermochemical data of a compound at 25 °C.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(Deserialize))]
+pub struct Thermochemistry {
+    /// Standard enthalpy of formation in kJ/mol.
+    #[cfg_attr(feature = "json", serde(rename = "h"))]
+    pub enthalpy: f64,
+    /// Standard molar entropy in J/(mol·K).
+    #[cfg_attr(feature = "json", serde(rename = "s"))]
+    pub entropy: f64,
+}
+
+/// Datasets loaded so far, from the binary or from the network.
+#[derive(Debug, Clone, Default)]
+pub struct Datasets {
+    /// Compound names keyed by canonical formula, so any way of writing a formula finds them.
+    names: Option<BTreeMap<String, String>>,
+    /// Dissociation constants keyed by canonical formula.
+    constants: Option<BTreeMap<String, Dissociation>>,
+    /// Thermochemical data keyed by canonical formula.
+    thermochemistry: Option<BTreeMap<String, Thermochemistry>>,
+}
+
+impl Datasets {
+    /// Starts with every dataset bundled into this build.
+    #[cfg(feature = "json")]
+    pub fn bundled() -> Self {
+        let mut datasets = Self::default();
+        for &dataset in Dataset::ALL.iter() {
+            if let Some(json) = dataset.bundled() {
+                datasets
+                    .load_json(dataset, json)
+                    .expect("bundled datasets are valid JSON");
+            }
+        }
+        datasets
+    }
+
+    pub fn is_loaded(&self, dataset: Dataset) -> bool {
+        match dataset {
+            Dataset::CompoundNames => self.names.is_some(),
+            Dataset::DissociationConstants => self.constants.is_some(),
+            Dataset::Thermochemistry => self.thermochemistry.is_some(),
+        }
+    }
+
+    /// Loads a dataset from its JSON file.
+    #[cfg(feature = "json")]
+    pub fn load_json(&mut self, dataset: Dataset, json: &str) -> serde_json::Result<()> {
+        match dataset {
+            Dataset::CompoundNames => {
+                let names: BTreeMap<String, String> = serde_json::from_str(json)?;
+                self.load_names(names);
+            }
+            Dataset::DissociationConstants => {
+                let constants: BTreeMap<String, BTreeMap<String, f64>> =
+                    serde_json::from_str(json)?;
+                self.load_constants(constants.into_iter().filter_map(|(formula, constant)| {
+                    let constant = match (constant.get("ka"), constant.get("kb")) {
+                        (Some(&ka), _) => Dissociation::Ka(ka),
+                        (None, Some(&kb)) => Dissociation::Kb(kb),
+                        (None, None) => return None,
+                    };
+                    Some((formula, constant))
+                }));
+            }
+            Dataset::Thermochemistry => {
+                let data: BTreeMap<String, Thermochemistry> = serde_json::from_str(json)?;
+                self.load_thermochemistry(data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads compound names from formula and name pairs, skipping invalid formulas.
+    pub fn load_names(&mut self, names: impl IntoIterator<Item = (String, String)>) {
+        let table = self.names.get_or_insert_with(BTreeMap::new);
+        for (formula, name) in names {
+            if let Some(chemical) = parse_chemical(&formula) {
+                table.entry(chemical.canonical_formula()).or_insert(name);
+            }
+        }
+    }
+
+    /// Loads dissociation constants by formula, skipping invalid formulas.
+    pub fn load_constants(&mut self, constants: impl IntoIterator<Item = (String, Dissociation)>) {
+        let table = self.constants.get_or_insert_with(BTreeMap::new);
+        for (formula, constant) in constants {
+            if let Some(chemical) = parse_chemical(&formula) {
+                table
+                    .entry(chemical.canonical_formula())
+                    .or_insert(constant);
+            }
+        }
+    }
+
+    /// Loads thermochemical data by formula, skipping invalid formulas.
+    pub fn load_thermochemistry(
+        &mut self,
+        data: impl IntoIterator<Item = (String, Thermochemistry)>,
+    ) {
+        let table = self.thermochemistry.get_or_insert_with(BTreeMap::new);
+        for (formula, entry) in data {
+            if let Some(chemical) = parse_chemical(&formula) {
+                table.entry(chemical.canonical_formula()).or_insert(entry);
+            }
+        }
+    }
+
+    pub fn thermochemistry(&self, canonical_formula: &str) -> Option<Thermochemistry> {
+        self.thermochemistry
+            .as_ref()?
+            .get(canonical_formula)
+            .copied()
+    }
+
+    pub fn dissociation_constant(&self, canonical_formula: &str) -> Option<Dissociation> {
+        self.constants.as_ref()?.get(canonical_formula).copied()
+    }
+
+    pub fn compound_name(&self, canonical_formula: &str) -> Option<&str> {
+        self.names
+            .as_ref()?
+            .get(canonical_formula)
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_found_by_any_formula() {
+        let mut datasets = Datasets::default();
+        assert!(!datasets.is_loaded(Dataset::CompoundNames));
+        datasets.load_names(vec![
+            ("CH3COOH".into(), "아세트산".into()),
+            ("2x".into(), "잘못된 화학식".into()),
+        ]);
+        assert!(datasets.is_loaded(Dataset::CompoundNames));
+        let acetic = parse_chemical("C2H4O2").unwrap().canonical_formula();
+        assert_eq!(Some("아세트산"), datasets.compound_name(&acetic));
+        assert_eq!("data/compound-names.json", Dataset::CompoundNames.url());
+    }
+
+    #[test]
+    fn constants_found_by_any_formula() {
+        let mut datasets = Datasets::default();
+        datasets.load_constants(vec![("CH3COOH".into(), Dissociation::Ka(1.8e-5))]);
+        assert!(datasets.is_loaded(Dataset::DissociationConstants));
+        let acetic = parse_chemical("HC2H3O2").unwrap().canonical_formula();
+        assert_eq!(
+            Some(Dissociation::Ka(1.8e-5)),
+            datasets.dissociation_constant(&acetic)
+        );
+    }
+
+    #[test]
+    fn thermochemistry_found_by_any_formula() {
+        let mut datasets = Datasets::default();
+        let water = Thermochemistry {
+            enthalpy: -285.83,
+            entropy: 69.95,
+        };
+        datasets.load_thermochemistry(vec![("H2O".into(), water)]);
+        assert!(datasets.is_loaded(Dataset::Thermochemistry));
+        let canonical = parse_chemical("OH2").unwrap().canonical_formula();
+        assert_eq!(Some(water), datasets.thermochemistry(&canonical));
+        assert_eq!(None, datasets.thermochemistry("CO2"));
+    }
+}
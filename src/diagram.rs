@@ -0,0 +1,789 @@
+use crate::chemical::Chemical;
+use crate::format::escape_into;
+use crate::i18n::I18n;
+use crate::network::Link;
+use crate::number::NumberFormat;
+use crate::oxidation::{oxidation_states, OxidationChange};
+use crate::reaction::Reaction;
+use crate::theme::Theme;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+const ATOM_RADIUS: usize = 5;
+const ATOM_SIZE: usize = ATOM_RADIUS * 2;
+const MOLECULE_GAP: usize = 8;
+const SIDE_WIDTH: usize = 240;
+const ARROW_WIDTH: usize = 40;
+const LEGEND_HEIGHT: usize = 24;
+const CHART_WIDTH: usize = 480;
+const LABEL_WIDTH: usize = 80;
+const BAR_HEIGHT: usize = 8;
+const PLOT_HEIGHT: f64 = 200.0;
+const PLOT_LEFT: f64 = 60.0;
+const PLOT_BOTTOM: f64 = 24.0;
+const OXIDATION_COLOR: &str = "#e53935";
+const REDUCTION_COLOR: &str = "#1e88e5";
+
+/// Fill color of an atom, roughly following the CPK convention.
+pub fn element_color(symbol: &str) -> &'static str {
+    const FALLBACK: [&str; 6] = [
+        "#ff9800", "#795548", "#009688", "#e91e63", "#607d8b", "#cddc39",
+    ];
+    match symbol {
+        "H" => "#ffffff",
+        "C" => "#424242",
+        "N" => "#3050f8",
+        "O" => "#ff0d0d",
+        "F" | "Cl" => "#1ff01f",
+        "Br" => "#a62929",
+        "I" => "#940094",
+        "S" => "#ffd123",
+        "P" => "#ff8000",
+        "Na" | "K" => "#ab5cf2",
+        "Mg" | "Ca" => "#3dff00",
+        "Fe" => "#e06633",
+        "Cu" => "#c88033",
+        _ => {
+            let hash = symbol
+                .bytes()
+                .fold(0usize, |hash, byte| hash * 31 + byte as usize);
+            FALLBACK[hash % FALLBACK.len()]
+        }
+    }
+}
+
+/// Draws every molecule of the reaction as a cluster of colored atoms,
+/// repeated by its coefficient, so that both sides visibly hold the same atoms.
+pub fn particle_diagram(reaction: &Reaction, theme: &Theme) -> String {
+    let mut body = String::new();
+    let reagents = reaction
+        .reagents
+        .iter()
+        .zip(reaction.reagent_coefficients());
+    let products = reaction
+        .products
+        .iter()
+        .zip(reaction.product_coefficients());
+    let left_height = draw_side(&mut body, reagents, 0, theme);
+    let right_height = draw_side(&mut body, products, SIDE_WIDTH + ARROW_WIDTH, theme);
+    let height = left_height.max(right_height);
+
+    let arrow_y = height / 2;
+    let arrow_start = SIDE_WIDTH + 5;
+    let arrow_end = SIDE_WIDTH + ARROW_WIDTH - 5;
+    write!(
+        body,
+        r##"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="#9e9e9e" stroke-width="2"/><polygon points="{},{} {},{} {},{}" fill="#9e9e9e"/>"##,
+        arrow_start,
+        arrow_end - 6,
+        arrow_end - 6,
+        arrow_y - 4,
+        arrow_end,
+        arrow_y,
+        arrow_end - 6,
+        arrow_y + 4,
+        y = arrow_y,
+    )
+    .unwrap();
+
+    let elements: BTreeSet<&String> = reaction
+        .reagents
+        .iter()
+        .flat_map(|chemical| chemical.parts.keys())
+        .collect();
+    let legend_y = height + LEGEND_HEIGHT / 2;
+    for (index, element) in elements.iter().enumerate() {
+        let x = ATOM_RADIUS + index * 40;
+        write!(
+            body,
+            r##"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="#616161"/><text x="{}" y="{}" font-size="10">{}</text>"##,
+            x,
+            legend_y,
+            ATOM_RADIUS,
+            theme.element_color(element),
+            x + ATOM_SIZE,
+            legend_y + 4,
+            element,
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="particle-diagram" viewBox="0 0 {} {}">{}</svg>"#,
+        SIDE_WIDTH * 2 + ARROW_WIDTH,
+        height + LEGEND_HEIGHT,
+        body
+    )
+}
+
+/// Lays out molecules left to right with wrapping, returning the height used.
+fn draw_side<'a>(
+    body: &mut String,
+    species: impl Iterator<Item = (&'a Chemical, &'a i64)>,
+    offset: usize,
+    theme: &Theme,
+) -> usize {
+    let mut x = 0;
+    let mut y = 0;
+    let mut row_height = 0;
+    for (chemical, &coefficient) in species {
+        let atoms: usize = chemical.parts.values().sum();
+        let columns = (1..)
+            .find(|columns| columns * columns >= atoms)
+            .unwrap_or(1);
+        let rows = atoms.div_ceil(columns);
+        let width = columns * ATOM_SIZE;
+        let height = rows.max(1) * ATOM_SIZE;
+        for _ in 0..coefficient {
+            if x > 0 && x + width > SIDE_WIDTH {
+                x = 0;
+                y += row_height + MOLECULE_GAP;
+                row_height = 0;
+            }
+            write!(
+                body,
+                r##"<g class="molecule"><rect x="{}" y="{}" width="{}" height="{}" rx="3" fill="none" stroke="#e0e0e0"/>"##,
+                offset + x,
+                y,
+                width,
+                height
+            )
+            .unwrap();
+            let symbols = chemical
+                .parts
+                .iter()
+                .flat_map(|(symbol, &count)| core::iter::repeat_n(symbol, count));
+            for (index, symbol) in symbols.enumerate() {
+                write!(
+                    body,
+                    r##"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="#616161"/>"##,
+                    offset + x + (index % columns) * ATOM_SIZE + ATOM_RADIUS,
+                    y + (index / columns) * ATOM_SIZE + ATOM_RADIUS,
+                    ATOM_RADIUS - 1,
+                    theme.element_color(symbol)
+                )
+                .unwrap();
+            }
+            body.push_str("</g>");
+            x += width + MOLECULE_GAP;
+            row_height = row_height.max(height);
+        }
+    }
+    y + row_height
+}
+
+/// Compares the atom count of each element on both sides, with every
+/// coefficient 1 and then balanced, as pairs of horizontal bars.
+pub fn element_bar_chart(reaction: &Reaction, theme: &Theme, i18n: &I18n) -> String {
+    let before = reaction.unbalanced_element_counts();
+    let after = reaction.element_counts();
+    let max = after
+        .values()
+        .chain(before.values())
+        .map(|&(left, right)| left.max(right))
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let scale = |count: i64| count as usize * (CHART_WIDTH - LABEL_WIDTH - 30) / max as usize;
+
+    let mut body = String::new();
+    let mut y = 0;
+    write_legend(
+        &mut body,
+        &[
+            (i18n.t("diagram.reagents"), theme.reagent),
+            (i18n.t("diagram.products"), theme.product),
+        ],
+    );
+    y += LEGEND_HEIGHT;
+    for (element, &(left, right)) in &before {
+        write!(
+            body,
+            r#"<text x="0" y="{}" font-size="12" font-weight="bold">{}</text>"#,
+            y + 2 * BAR_HEIGHT + 2,
+            element
+        )
+        .unwrap();
+        let (balanced_left, balanced_right) = after[element];
+        let rows = [
+            (i18n.t("diagram.unbalanced"), left, right),
+            (i18n.t("diagram.balanced"), balanced_left, balanced_right),
+        ];
+        for (label, left, right) in rows.iter() {
+            write!(
+                body,
+                r#"<text x="20" y="{}" font-size="9">{}</text>"#,
+                y + BAR_HEIGHT + 2,
+                label
+            )
+            .unwrap();
+            write_bar(&mut body, y, scale(*left), *left, theme.reagent);
+            write_bar(
+                &mut body,
+                y + BAR_HEIGHT,
+                scale(*right),
+                *right,
+                theme.product,
+            );
+            y += 2 * BAR_HEIGHT + 4;
+        }
+        y += BAR_HEIGHT;
+    }
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="element-chart" viewBox="0 0 {} {}">{}</svg>"#,
+        CHART_WIDTH, y, body
+    )
+}
+
+/// Draws a balance scale holding the total mass of each side, which
+/// stays level when mass is conserved.
+pub fn mass_balance_scale(
+    reaction: &Reaction,
+    theme: &Theme,
+    numbers: &NumberFormat,
+    i18n: &I18n,
+) -> Option<String> {
+    let (left, right) = reaction.side_masses()?;
+    let heavier = left.max(right).max(f64::EPSILON);
+    // Tilt proportionally to the relative difference, at most 20 units
+    let tilt = ((left - right) / heavier * 20.0).clamp(-20.0, 20.0);
+    let (left_y, right_y) = (60.0 + tilt, 60.0 - tilt);
+
+    let mut body = String::new();
+    write!(
+        body,
+        r##"<polygon points="150,150 130,170 170,170" fill="#9e9e9e"/><line x1="150" y1="60" x2="150" y2="150" stroke="#9e9e9e" stroke-width="4"/><line x1="50" y1="{:.1}" x2="250" y2="{:.1}" stroke="#616161" stroke-width="4"/>"##,
+        left_y, right_y
+    )
+    .unwrap();
+    let pans = [
+        (
+            50.0,
+            left_y,
+            left,
+            i18n.t("diagram.reagents"),
+            theme.reagent,
+        ),
+        (
+            250.0,
+            right_y,
+            right,
+            i18n.t("diagram.products"),
+            theme.product,
+        ),
+    ];
+    for (x, y, mass, label, color) in pans.iter() {
+        write!(
+            body,
+            r#"<line x1="{x}" y1="{y:.1}" x2="{x}" y2="{pan:.1}" stroke="{color}"/><rect x="{left}" y="{pan:.1}" width="80" height="6" fill="{color}"/><text x="{x}" y="{label_y:.1}" font-size="11" text-anchor="middle">{label}</text><text x="{x}" y="{mass_y:.1}" font-size="11" text-anchor="middle">{mass} g</text>"#,
+            x = x,
+            y = y,
+            pan = y + 40.0,
+            left = x - 40.0,
+            label_y = y + 60.0,
+            mass_y = y + 75.0,
+            color = color,
+            label = label,
+            mass = numbers.format(*mass, 3),
+        )
+        .unwrap();
+    }
+
+    Some(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="mass-balance" viewBox="0 0 300 180">{}</svg>"#,
+        body
+    ))
+}
+
+/// Draws the given history steps as a column of boxes, with an arrow for
+/// each species a step hands over to a later one.
+pub fn network_graph(steps: &[&Reaction], shown: &[usize], links: &[Link]) -> String {
+    const BOX_WIDTH: usize = 300;
+    const BOX_HEIGHT: usize = 30;
+    const ROW_HEIGHT: usize = 50;
+
+    let mut body = String::from(
+        r##"<defs><marker id="network-arrow" markerWidth="8" markerHeight="8" refX="8" refY="4" orient="auto"><path d="M0,0 L8,4 L0,8 z" fill="#616161"/></marker></defs>"##,
+    );
+    let row_of = |step: usize| shown.iter().position(|&shown| shown == step);
+    // Catalysts and conditions are free text, so every label is escaped
+    let escaped = |text: &str| {
+        let mut out = String::new();
+        escape_into(text, &mut out);
+        out
+    };
+    for (row, &step) in shown.iter().enumerate() {
+        write!(
+            body,
+            r##"<rect x="0" y="{}" width="{}" height="{}" rx="4" fill="white" stroke="#9e9e9e"/><text x="8" y="{}" font-size="12">Out[{}] : {}</text>"##,
+            row * ROW_HEIGHT,
+            BOX_WIDTH,
+            BOX_HEIGHT,
+            row * ROW_HEIGHT + 19,
+            step,
+            escaped(&steps[step].to_string()),
+        )
+        .unwrap();
+    }
+    for (index, link) in links.iter().enumerate() {
+        let (from, to) = match (row_of(link.from), row_of(link.to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => continue,
+        };
+        let from_y = from * ROW_HEIGHT + BOX_HEIGHT / 2;
+        let to_y = to * ROW_HEIGHT + BOX_HEIGHT / 2;
+        let bend = BOX_WIDTH + 30 + index * 25;
+        write!(
+            body,
+            r##"<path d="M{x},{} C{bend},{} {bend},{} {x},{}" fill="none" stroke="#616161" marker-end="url(#network-arrow)"/><text x="{}" y="{}" font-size="10">{}</text>"##,
+            from_y,
+            from_y,
+            to_y,
+            to_y,
+            bend - 20,
+            (from_y + to_y) / 2,
+            escaped(&link.formula),
+            x = BOX_WIDTH,
+            bend = bend,
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="network-graph" viewBox="0 0 {} {}">{}</svg>"#,
+        BOX_WIDTH + 60 + links.len() * 25,
+        shown.len() * ROW_HEIGHT,
+        body
+    )
+}
+
+/// Bridge diagram of a redox reaction, with an arrow above the equation from
+/// each oxidized species to its product and one below for each reduced one,
+/// labelled with the electrons per atom times the atoms. Returns `None` when
+/// there are no changes or a species of one cannot be found.
+pub fn electron_transfer_diagram(
+    reaction: &Reaction,
+    changes: &[OxidationChange],
+    i18n: &I18n,
+) -> Option<String> {
+    const CHAR_WIDTH: usize = 9;
+    const SPECIES_GAP: usize = 24;
+    const LEVEL_HEIGHT: usize = 20;
+
+    if changes.is_empty() {
+        return None;
+    }
+    // Room above the equation for every oxidation arrow
+    let oxidations = changes
+        .iter()
+        .filter(|change| change.is_oxidation())
+        .count();
+    let baseline = 20 + (oxidations + 1) * LEVEL_HEIGHT;
+    // Center of each species, with `→` and `+` between them
+    let mut body = String::new();
+    let mut centers = Vec::new();
+    let mut x = 0;
+    for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+        let separator = if index == reaction.reagents.len() {
+            Some("→")
+        } else if index > 0 {
+            Some("+")
+        } else {
+            None
+        };
+        if let Some(separator) = separator {
+            write!(
+                body,
+                r#"<text x="{}" y="{}" font-size="14" text-anchor="middle">{}</text>"#,
+                x + SPECIES_GAP / 2,
+                baseline,
+                separator
+            )
+            .unwrap();
+            x += SPECIES_GAP;
+        }
+        let text = if coefficient == 1 {
+            chemical.display.clone()
+        } else {
+            format!("{}{}", coefficient, chemical.display)
+        };
+        let width = text.chars().count() * CHAR_WIDTH;
+        write!(
+            body,
+            r#"<text x="{}" y="{}" font-size="14" text-anchor="middle">{}</text>"#,
+            x + width / 2,
+            baseline,
+            text
+        )
+        .unwrap();
+        centers.push(x + width / 2);
+        x += width;
+    }
+
+    let holds = |chemical: &Chemical, element: &str, state| {
+        oxidation_states(chemical).and_then(|states| states.get(element).copied()) == Some(state)
+    };
+    let (mut above, mut below) = (0, 0);
+    for change in changes {
+        let from = reaction
+            .reagents
+            .iter()
+            .position(|chemical| holds(chemical, &change.element, change.before))?;
+        let to = reaction.reagents.len()
+            + reaction
+                .products
+                .iter()
+                .position(|chemical| holds(chemical, &change.element, change.after))?;
+        let (x1, x2) = (centers[from], centers[to]);
+        let (start, end, key, kind, color) = if change.is_oxidation() {
+            above += 1;
+            let end = baseline - 16 - above * LEVEL_HEIGHT;
+            (
+                baseline - 16,
+                end,
+                "oxidation.bridge_loses",
+                "oxidation",
+                OXIDATION_COLOR,
+            )
+        } else {
+            below += 1;
+            let end = baseline + 6 + below * LEVEL_HEIGHT;
+            (
+                baseline + 6,
+                end,
+                "oxidation.bridge_gains",
+                "reduction",
+                REDUCTION_COLOR,
+            )
+        };
+        let label = i18n.format(key, &[&change.electrons_per_atom().abs(), &change.atoms]);
+        write!(
+            body,
+            r#"<polyline points="{x1},{start} {x1},{end} {x2},{end} {x2},{start}" fill="none" stroke="{color}" marker-end="url(#bridge-{kind})"/><text x="{}" y="{}" font-size="11" text-anchor="middle" fill="{color}">{}: {}</text>"#,
+            (x1 + x2) / 2,
+            end - 3,
+            change.element,
+            label,
+            x1 = x1,
+            x2 = x2,
+            start = start,
+            end = end,
+            color = color,
+            kind = kind,
+        )
+        .unwrap();
+    }
+
+    Some(format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" class="electron-transfer" viewBox="0 0 {} {}"><defs><marker id="bridge-oxidation" markerWidth="8" markerHeight="8" refX="8" refY="4" orient="auto"><path d="M0,0 L8,4 L0,8 z" fill="{}"/></marker><marker id="bridge-reduction" markerWidth="8" markerHeight="8" refX="8" refY="4" orient="auto"><path d="M0,0 L8,4 L0,8 z" fill="{}"/></marker></defs>{}</svg>"##,
+        x,
+        baseline + 12 + below * LEVEL_HEIGHT,
+        OXIDATION_COLOR,
+        REDUCTION_COLOR,
+        body
+    ))
+}
+
+/// A line of a [`line_chart`], through points sorted by x.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series<'a> {
+    pub label: &'a str,
+    pub color: &'a str,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Plots each series against shared axes, with the y range stretched to
+/// include zero and a dashed vertical line at each x of `markers` in range.
+/// Gives `None` when there is nothing finite to plot.
+pub fn line_chart(
+    series: &[Series],
+    x_label: &str,
+    y_label: &str,
+    markers: &[f64],
+    numbers: &NumberFormat,
+) -> Option<String> {
+    let points = || {
+        series
+            .iter()
+            .flat_map(|series| series.points.iter().copied())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+    };
+    let (x_min, x_max) = points().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (x, _)| {
+        (min.min(x), max.max(x))
+    });
+    let (y_min, y_max) = points().fold((0.0f64, 0.0f64), |(min, max), (_, y)| {
+        (min.min(y), max.max(y))
+    });
+    if x_min >= x_max {
+        return None;
+    }
+    let y_span = if y_max > y_min { y_max - y_min } else { 1.0 };
+    let plot_width = CHART_WIDTH as f64 - PLOT_LEFT - 10.0;
+    let to_x = |x: f64| PLOT_LEFT + (x - x_min) / (x_max - x_min) * plot_width;
+    let to_y = |y: f64| LEGEND_HEIGHT as f64 + (y_max - y) / y_span * PLOT_HEIGHT;
+
+    let mut body = String::new();
+    let legend: Vec<(&str, &str)> = series
+        .iter()
+        .map(|series| (series.label, series.color))
+        .collect();
+    write_legend(&mut body, &legend);
+    let (left, right) = (PLOT_LEFT, PLOT_LEFT + plot_width);
+    let (top, bottom) = (to_y(y_max), to_y(y_min));
+    write!(
+        body,
+        r##"<line x1="{left}" y1="{top:.1}" x2="{left}" y2="{bottom:.1}" stroke="#9e9e9e"/><line x1="{left}" y1="{zero:.1}" x2="{right:.1}" y2="{zero:.1}" stroke="#9e9e9e"/>"##,
+        left = left,
+        right = right,
+        top = top,
+        bottom = bottom,
+        zero = to_y(0.0),
+    )
+    .unwrap();
+    for &y in [y_min, y_max].iter() {
+        write!(
+            body,
+            r#"<text x="{:.1}" y="{:.1}" font-size="9" text-anchor="end">{}</text>"#,
+            left - 4.0,
+            to_y(y) + 3.0,
+            numbers.format(y, 1)
+        )
+        .unwrap();
+    }
+    for &x in [x_min, x_max].iter() {
+        write!(
+            body,
+            r#"<text x="{:.1}" y="{:.1}" font-size="9" text-anchor="middle">{}</text>"#,
+            to_x(x),
+            bottom + 12.0,
+            numbers.format(x, 0)
+        )
+        .unwrap();
+    }
+    write!(
+        body,
+        r#"<text x="{:.1}" y="{:.1}" font-size="10" text-anchor="middle">{}</text><text x="0" y="{:.1}" font-size="10">{}</text>"#,
+        left + plot_width / 2.0,
+        bottom + PLOT_BOTTOM - 2.0,
+        x_label,
+        top - 4.0,
+        y_label
+    )
+    .unwrap();
+    for &x in markers.iter().filter(|&&x| x_min <= x && x <= x_max) {
+        write!(
+            body,
+            r##"<line class="marker" x1="{x:.1}" y1="{top:.1}" x2="{x:.1}" y2="{bottom:.1}" stroke="#757575" stroke-dasharray="4 3"/><text x="{x:.1}" y="{label:.1}" font-size="9" text-anchor="middle">{value}</text>"##,
+            x = to_x(x),
+            top = top,
+            bottom = bottom,
+            label = top - 4.0,
+            value = numbers.format(x, 0),
+        )
+        .unwrap();
+    }
+    for series in series {
+        let points: Vec<String> = series
+            .points
+            .iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|&(x, y)| format!("{:.1},{:.1}", to_x(x), to_y(y)))
+            .collect();
+        write!(
+            body,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            points.join(" "),
+            series.color
+        )
+        .unwrap();
+    }
+
+    Some(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="line-chart" viewBox="0 0 {} {:.1}">{}</svg>"#,
+        CHART_WIDTH,
+        bottom + PLOT_BOTTOM,
+        body
+    ))
+}
+
+fn write_bar(body: &mut String, y: usize, width: usize, count: i64, color: &str) {
+    write!(
+        body,
+        r#"<rect x="{x}" y="{}" width="{}" height="{}" fill="{}"/><text x="{}" y="{}" font-size="8">{}</text>"#,
+        y,
+        width,
+        BAR_HEIGHT - 1,
+        color,
+        LABEL_WIDTH + width + 3,
+        y + BAR_HEIGHT - 1,
+        count,
+        x = LABEL_WIDTH,
+    )
+    .unwrap();
+}
+
+fn write_legend(body: &mut String, entries: &[(&str, &str)]) {
+    for (index, (label, color)) in entries.iter().enumerate() {
+        let x = index * 80;
+        write!(
+            body,
+            r#"<rect x="{}" y="4" width="10" height="10" fill="{}"/><text x="{}" y="13" font-size="10">{}</text>"#,
+            x,
+            color,
+            x + 14,
+            label
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+    use crate::theme::THEMES;
+
+    #[test]
+    fn particle_diagram_repeats_molecules() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2O").unwrap()],
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![2, 2, 1],
+        );
+        let svg = particle_diagram(&reaction, &THEMES[0]);
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(5, svg.matches(r#"<g class="molecule">"#).count());
+        // 12 atoms and a legend entry for each of H and O
+        assert_eq!(14, svg.matches("<circle").count());
+    }
+
+    #[test]
+    fn element_bar_chart_shows_both_states() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2O").unwrap()],
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![2, 2, 1],
+        );
+        let svg = element_bar_chart(&reaction, &THEMES[0], &I18n::default());
+        // Two legend swatches and four bars per element
+        assert_eq!(10, svg.matches("<rect").count());
+    }
+
+    #[test]
+    fn mass_balance_scale_is_level() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2O").unwrap()],
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![2, 2, 1],
+        );
+        let svg = mass_balance_scale(
+            &reaction,
+            &THEMES[0],
+            &NumberFormat::default(),
+            &I18n::default(),
+        )
+        .unwrap();
+        assert!(svg.contains(r#"x1="50" y1="60.0" x2="250" y2="60.0""#));
+        assert!(svg.contains("36.030 g"));
+    }
+
+    #[test]
+    fn electron_transfer_bridges() {
+        let reaction = Reaction::new(
+            vec![
+                parse_chemical("Fe2O3").unwrap(),
+                parse_chemical("CO").unwrap(),
+            ],
+            vec![
+                parse_chemical("Fe").unwrap(),
+                parse_chemical("CO2").unwrap(),
+            ],
+            vec![1, 3, 2, 3],
+        );
+        let changes = crate::oxidation::oxidation_report(&reaction).unwrap();
+        let svg = electron_transfer_diagram(&reaction, &changes, &I18n::default()).unwrap();
+        assert_eq!(2, svg.matches("<polyline").count());
+        assert!(svg.contains(">3CO<"));
+        assert_eq!(
+            None,
+            electron_transfer_diagram(&reaction, &[], &I18n::default())
+        );
+    }
+
+    #[test]
+    fn network_graph_escapes_text() {
+        let mut reaction = Reaction::new(
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("H2O").unwrap()],
+            vec![2, 1, 2],
+        );
+        reaction.conditions = vec![crate::condition::Condition::parse(
+            "<img src=x onerror=alert(1)>",
+        )];
+        let links = [Link {
+            from: 0,
+            to: 0,
+            formula: "</text><script>".into(),
+        }];
+        reaction.catalyst = Some("</text><script>alert(1)</script>".into());
+        let svg = network_graph(&[&reaction], &[0], &links);
+        assert!(!svg.contains("<img"));
+        assert!(!svg.contains("<script"));
+        assert!(svg.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    }
+
+    #[test]
+    fn line_chart_plots_each_series() {
+        let numbers = NumberFormat::default();
+        let line = |label, color, slope: f64| Series {
+            label,
+            color,
+            points: (0..5).map(|x| (x as f64, slope * x as f64 - 1.0)).collect(),
+        };
+        let svg = line_chart(
+            &[line("a", "red", 1.0), line("b", "blue", -1.0)],
+            "x",
+            "y",
+            &[1.0, 10.0],
+            &numbers,
+        )
+        .unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(2, svg.matches("<polyline").count());
+        // Only the marker in range is drawn
+        assert_eq!(1, svg.matches(r#"class="marker""#).count());
+
+        let point = Series {
+            label: "a",
+            color: "red",
+            points: vec![(1.0, 1.0)],
+        };
+        assert_eq!(None, line_chart(&[point], "x", "y", &[], &numbers));
+    }
+
+    #[test]
+    fn network_graph_draws_links() {
+        let first = Reaction::new(
+            vec![parse_chemical("C").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("CO2").unwrap()],
+            vec![1, 1, 1],
+        );
+        let second = Reaction::new(
+            vec![
+                parse_chemical("CO2").unwrap(),
+                parse_chemical("H2O").unwrap(),
+            ],
+            vec![parse_chemical("H2CO3").unwrap()],
+            vec![1, 1, 1],
+        );
+        let steps = [&first, &second];
+        let links = crate::network::find_links(&steps);
+        let svg = network_graph(&steps, &[0, 1], &links);
+        assert!(svg.contains("Out[1] : CO2 + H2O = H2CO3"));
+        assert_eq!(1, svg.matches("marker-end").count());
+    }
+}
@@ -0,0 +1,157 @@
+//! Editing the equation input at its caret, so that pickers and quick-fixes
+//! change the text where the user is instead of at its end.
+
+use alloc::string::String;
+use core::ops::Range;
+
+/// Selected range of an input field in UTF-16 code units, as the DOM reports it.
+/// An empty selection is a caret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Selection {
+    pub fn caret(at: usize) -> Self {
+        Self { start: at, end: at }
+    }
+
+    /// A caret after the last character of `text`.
+    pub fn end_of(text: &str) -> Self {
+        Self::caret(utf16_len(text))
+    }
+
+    pub fn is_caret(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Byte range of the selection in `text`, ordered and clamped to it.
+    pub fn byte_range(&self, text: &str) -> Range<usize> {
+        let start = byte_offset(text, self.start.min(self.end));
+        let end = byte_offset(text, self.start.max(self.end));
+        start..end
+    }
+}
+
+/// A replacement of part of the input, as proposed by a quick-fix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    /// Byte range of the input to replace.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+pub fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Byte offset of a UTF-16 offset, past the end of `text` clamping to its length.
+fn byte_offset(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= offset {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Replaces the selection with `inserted`, leaving the caret after it.
+pub fn insert(text: &mut String, selection: &mut Selection, inserted: &str) {
+    let range = selection.byte_range(text);
+    let caret = utf16_len(&text[..range.start]) + utf16_len(inserted);
+    text.replace_range(range, inserted);
+    *selection = Selection::caret(caret);
+}
+
+/// Surrounds the selection with `open` and `close`, keeping it selected.
+/// A caret is left between the two.
+pub fn wrap(text: &mut String, selection: &mut Selection, open: &str, close: &str) {
+    let range = selection.byte_range(text);
+    let start = utf16_len(&text[..range.start]) + utf16_len(open);
+    let end = start + utf16_len(&text[range.clone()]);
+    text.insert_str(range.end, close);
+    text.insert_str(range.start, open);
+    *selection = Selection { start, end };
+}
+
+/// Applies `edit`, moving the selection with the text around it. A selection
+/// overlapping the edit collapses to a caret after the replacement.
+pub fn apply(text: &mut String, selection: &mut Selection, edit: &Edit) {
+    let selected = selection.byte_range(text);
+    let start = edit.range.start.min(text.len());
+    let end = edit.range.end.clamp(start, text.len());
+    let removed = utf16_len(&text[start..end]);
+    let edit_start = utf16_len(&text[..start]);
+    let edit_end = edit_start + removed;
+    let added = utf16_len(&edit.text);
+    let selection_start = selection.start.min(selection.end);
+    let selection_end = selection.start.max(selection.end);
+    text.replace_range(start..end, &edit.text);
+    *selection = if selected.end <= start {
+        Selection {
+            start: selection_start,
+            end: selection_end,
+        }
+    } else if selected.start >= end {
+        Selection {
+            start: selection_start + added - removed,
+            end: selection_end + added - removed,
+        }
+    } else {
+        Selection::caret(edit_end + added - removed)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_at_caret() {
+        let mut text = String::from("물 H2 = O2");
+        let mut selection = Selection::caret(4);
+        insert(&mut text, &mut selection, "O + 2H");
+        assert_eq!("물 H2O + 2H = O2", text);
+        assert_eq!(Selection::caret(10), selection);
+
+        let mut selection = Selection { start: 15, end: 13 };
+        insert(&mut text, &mut selection, "H2O2");
+        assert_eq!("물 H2O + 2H = H2O2", text);
+        assert_eq!(Selection::end_of(&text), selection);
+    }
+
+    #[test]
+    fn wrap_selection() {
+        let mut text = String::from("CaOH2");
+        let mut selection = Selection { start: 2, end: 4 };
+        wrap(&mut text, &mut selection, "(", ")");
+        assert_eq!("Ca(OH)2", text);
+        assert_eq!(Selection { start: 3, end: 5 }, selection);
+
+        let mut selection = Selection::end_of(&text);
+        wrap(&mut text, &mut selection, "(", ")");
+        assert_eq!("Ca(OH)2()", text);
+        assert_eq!(Selection::caret(8), selection);
+    }
+
+    #[test]
+    fn apply_moves_selection() {
+        let fix = Edit {
+            range: 0..2,
+            text: "H2O".into(),
+        };
+        let mut text = String::from("HO = H2 + O2");
+        let mut selection = Selection { start: 5, end: 7 };
+        apply(&mut text, &mut selection, &fix);
+        assert_eq!("H2O = H2 + O2", text);
+        assert_eq!(Selection { start: 6, end: 8 }, selection);
+
+        let mut text = String::from("HO = H2 + O2");
+        let mut selection = Selection::caret(1);
+        apply(&mut text, &mut selection, &fix);
+        assert_eq!(Selection::caret(3), selection);
+    }
+}
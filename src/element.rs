@@ -0,0 +1,156 @@
+pub struct Element {
+    pub symbol: &'static str,
+    pub name: &'static str,
+    /// Standard atomic weight in g/mol, or the mass number of the most stable isotope.
+    pub mass: f64,
+}
+
+macro_rules! elements {
+    ($(($symbol:expr, $name:expr, $mass:expr)),* $(,)?) => {
+        [$(Element { symbol: $symbol, name: $name, mass: $mass }),*]
+    };
+}
+
+/// Every element ordered by atomic number, starting from hydrogen at index 0.
+pub static ELEMENTS: [Element; 118] = elements![
+    ("H", "수소", 1.008),
+    ("He", "헬륨", 4.0026),
+    ("Li", "리튬", 6.94),
+    ("Be", "베릴륨", 9.0122),
+    ("B", "붕소", 10.81),
+    ("C", "탄소", 12.011),
+    ("N", "질소", 14.007),
+    ("O", "산소", 15.999),
+    ("F", "플루오린", 18.998),
+    ("Ne", "네온", 20.180),
+    ("Na", "나트륨", 22.990),
+    ("Mg", "마그네슘", 24.305),
+    ("Al", "알루미늄", 26.982),
+    ("Si", "규소", 28.085),
+    ("P", "인", 30.974),
+    ("S", "황", 32.06),
+    ("Cl", "염소", 35.45),
+    ("Ar", "아르곤", 39.948),
+    ("K", "칼륨", 39.098),
+    ("Ca", "칼슘", 40.078),
+    ("Sc", "스칸듐", 44.956),
+    ("Ti", "타이타늄", 47.867),
+    ("V", "바나듐", 50.942),
+    ("Cr", "크로뮴", 51.996),
+    ("Mn", "망가니즈", 54.938),
+    ("Fe", "철", 55.845),
+    ("Co", "코발트", 58.933),
+    ("Ni", "니켈", 58.693),
+    ("Cu", "구리", 63.546),
+    ("Zn", "아연", 65.38),
+    ("Ga", "갈륨", 69.723),
+    ("Ge", "저마늄", 72.630),
+    ("As", "비소", 74.922),
+    ("Se", "셀레늄", 78.971),
+    ("Br", "브로민", 79.904),
+    ("Kr", "크립톤", 83.798),
+    ("Rb", "루비듐", 85.468),
+    ("Sr", "스트론튬", 87.62),
+    ("Y", "이트륨", 88.906),
+    ("Zr", "지르코늄", 91.224),
+    ("Nb", "나이오븀", 92.906),
+    ("Mo", "몰리브데넘", 95.95),
+    ("Tc", "테크네튬", 98.0),
+    ("Ru", "루테늄", 101.07),
+    ("Rh", "로듐", 102.91),
+    ("Pd", "팔라듐", 106.42),
+    ("Ag", "은", 107.87),
+    ("Cd", "카드뮴", 112.41),
+    ("In", "인듐", 114.82),
+    ("Sn", "주석", 118.71),
+    ("Sb", "안티모니", 121.76),
+    ("Te", "텔루륨", 127.60),
+    ("I", "아이오딘", 126.90),
+    ("Xe", "제논", 131.29),
+    ("Cs", "세슘", 132.91),
+    ("Ba", "바륨", 137.33),
+    ("La", "란타넘", 138.91),
+    ("Ce", "세륨", 140.12),
+    ("Pr", "프라세오디뮴", 140.91),
+    ("Nd", "네오디뮴", 144.24),
+    ("Pm", "프로메튬", 145.0),
+    ("Sm", "사마륨", 150.36),
+    ("Eu", "유로퓸", 151.96),
+    ("Gd", "가돌리늄", 157.25),
+    ("Tb", "터븀", 158.93),
+    ("Dy", "디스프로슘", 162.50),
+    ("Ho", "홀뮴", 164.93),
+    ("Er", "어븀", 167.26),
+    ("Tm", "툴륨", 168.93),
+    ("Yb", "이터븀", 173.05),
+    ("Lu", "루테튬", 174.97),
+    ("Hf", "하프늄", 178.49),
+    ("Ta", "탄탈럼", 180.95),
+    ("W", "텅스텐", 183.84),
+    ("Re", "레늄", 186.21),
+    ("Os", "오스뮴", 190.23),
+    ("Ir", "이리듐", 192.22),
+    ("Pt", "백금", 195.08),
+    ("Au", "금", 196.97),
+    ("Hg", "수은", 200.59),
+    ("Tl", "탈륨", 204.38),
+    ("Pb", "납", 207.2),
+    ("Bi", "비스무트", 208.98),
+    ("Po", "폴로늄", 209.0),
+    ("At", "아스타틴", 210.0),
+    ("Rn", "라돈", 222.0),
+    ("Fr", "프랑슘", 223.0),
+    ("Ra", "라듐", 226.0),
+    ("Ac", "악티늄", 227.0),
+    ("Th", "토륨", 232.04),
+    ("Pa", "프로트악티늄", 231.04),
+    ("U", "우라늄", 238.03),
+    ("Np", "넵투늄", 237.0),
+    ("Pu", "플루토늄", 244.0),
+    ("Am", "아메리슘", 243.0),
+    ("Cm", "퀴륨", 247.0),
+    ("Bk", "버클륨", 247.0),
+    ("Cf", "캘리포늄", 251.0),
+    ("Es", "아인슈타이늄", 252.0),
+    ("Fm", "페르뮴", 257.0),
+    ("Md", "멘델레븀", 258.0),
+    ("No", "노벨륨", 259.0),
+    ("Lr", "로렌슘", 266.0),
+    ("Rf", "러더포듐", 267.0),
+    ("Db", "더브늄", 268.0),
+    ("Sg", "시보귬", 269.0),
+    ("Bh", "보륨", 270.0),
+    ("Hs", "하슘", 270.0),
+    ("Mt", "마이트너륨", 278.0),
+    ("Ds", "다름슈타튬", 281.0),
+    ("Rg", "뢴트게늄", 282.0),
+    ("Cn", "코페르니슘", 285.0),
+    ("Nh", "니호늄", 286.0),
+    ("Fl", "플레로븀", 289.0),
+    ("Mc", "모스코븀", 290.0),
+    ("Lv", "리버모륨", 293.0),
+    ("Ts", "테네신", 294.0),
+    ("Og", "오가네손", 294.0),
+];
+
+/// Looks up an element by its symbol, returning its atomic number alongside.
+pub fn find_element(symbol: &str) -> Option<(usize, &'static Element)> {
+    ELEMENTS
+        .iter()
+        .enumerate()
+        .find(|(_, element)| element.symbol == symbol)
+        .map(|(index, element)| (index + 1, element))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_element_test() {
+        let (number, element) = find_element("Fe").unwrap();
+        assert_eq!(26, number);
+        assert_eq!("철", element.name);
+        assert!(find_element("Xx").is_none());
+    }
+}
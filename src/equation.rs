@@ -0,0 +1,506 @@
+use crate::alias::Aliases;
+use crate::chemical::{
+    brackets_match, closing_bracket, contains_hangul, fix_case, has_ambiguous_charge, is_electron,
+    parse_chemical, parse_chemical_with_config, split_phase, Chemical, ParserConfig,
+};
+use crate::condition::{split_conditions, Condition};
+use crate::i18n::I18n;
+use crate::paste::normalize_symbols;
+use crate::reaction::{Reaction, Side};
+use crate::token::{tokenize, TokenKind};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// An equation as typed, before its coefficients are solved.
+#[derive(Debug, Clone)]
+pub struct Equation {
+    pub reagents: Vec<Chemical>,
+    pub products: Vec<Chemical>,
+    pub catalyst: Option<String>,
+    pub conditions: Vec<Condition>,
+    /// The coefficient typed before each species, reagents first, or 1 where there is none.
+    pub coefficients: Vec<i64>,
+    /// Things changed or left out while reading the input, which did not stop it.
+    pub warnings: Vec<Warning>,
+}
+
+impl Equation {
+    pub fn into_reaction(self, coefficients: Vec<i64>) -> Reaction {
+        let mut reaction = Reaction::new(self.reagents, self.products, coefficients);
+        reaction.catalyst = self.catalyst;
+        reaction.conditions = self.conditions;
+        reaction
+    }
+}
+
+/// A non-fatal note about how the input was read.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum Warning {
+    /// A coefficient typed before the formula gave way to the solved one.
+    CoefficientReplaced { formula: String },
+    /// Arrows, Unicode digits or fullwidth signs were turned into plain text.
+    InputNormalized,
+    /// A formula typed all in lowercase was read as this one.
+    CaseCorrected { formula: String },
+    /// The digit before the sign ending this formula was read as a count, not a charge.
+    AmbiguousCharge { formula: String },
+}
+
+impl Warning {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Warning::CoefficientReplaced { .. } => "warning.coefficient_replaced",
+            Warning::InputNormalized => "warning.input_normalized",
+            Warning::CaseCorrected { .. } => "warning.case_corrected",
+            Warning::AmbiguousCharge { .. } => "warning.ambiguous_charge",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            Warning::CoefficientReplaced { formula }
+            | Warning::CaseCorrected { formula }
+            | Warning::AmbiguousCharge { formula } => i18n.format(self.message_key(), &[formula]),
+            Warning::InputNormalized => i18n.t(self.message_key()).to_string(),
+        }
+    }
+}
+
+/// Why an equation could not be read, with the byte range of the offending
+/// formula in the input where there is one.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ParseError {
+    #[error("`{formula}` is not a valid formula")]
+    InvalidFormula { formula: String, span: Range<usize> },
+    #[error("`{formula}` contains Hangul, but formulas are written in Latin letters")]
+    Hangul { formula: String, span: Range<usize> },
+    #[error("expected `reagent + ... = product + ...`")]
+    MissingProducts,
+    #[error("`{name}` could be an element or an alias")]
+    Ambiguous { name: String, span: Range<usize> },
+    #[error("the brackets of `{formula}` do not match")]
+    UnmatchedBracket { formula: String, span: Range<usize> },
+}
+
+impl ParseError {
+    fn invalid_formula(formula: &str, span: Range<usize>) -> Self {
+        let formula = formula.to_string();
+        // Typing with the Korean IME still on is the most common cause, so it gets its own hint
+        if contains_hangul(&formula) {
+            ParseError::Hangul { formula, span }
+        } else if !brackets_match(&formula) {
+            ParseError::UnmatchedBracket { formula, span }
+        } else {
+            ParseError::InvalidFormula { formula, span }
+        }
+    }
+
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            ParseError::InvalidFormula { span, .. }
+            | ParseError::Hangul { span, .. }
+            | ParseError::Ambiguous { span, .. }
+            | ParseError::UnmatchedBracket { span, .. } => Some(span.clone()),
+            ParseError::MissingProducts => None,
+        }
+    }
+
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            ParseError::InvalidFormula { .. } => "error.invalid_formula",
+            ParseError::Hangul { .. } => "error.hangul",
+            ParseError::MissingProducts => "error.missing_products",
+            ParseError::Ambiguous { .. } => "error.ambiguous",
+            ParseError::UnmatchedBracket { .. } => "error.unmatched_bracket",
+        }
+    }
+
+    /// The stable code of the error, listed in `error_code::ERROR_CODES`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::InvalidFormula { .. } => "E001",
+            ParseError::Hangul { .. } => "E002",
+            ParseError::MissingProducts => "E003",
+            ParseError::Ambiguous { .. } => "E004",
+            ParseError::UnmatchedBracket { .. } => "E005",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            ParseError::InvalidFormula { formula, .. }
+            | ParseError::Hangul { formula, .. }
+            | ParseError::Ambiguous { name: formula, .. }
+            | ParseError::UnmatchedBracket { formula, .. } => {
+                i18n.format(self.message_key(), &[formula])
+            }
+            ParseError::MissingProducts => i18n.t(self.message_key()).to_string(),
+        }
+    }
+}
+
+pub fn parse_equation(input: impl AsRef<str>) -> Result<Equation, ParseError> {
+    parse_equation_with(input, &Aliases::default())
+}
+
+/// Parses the equation with user-defined `aliases` expanded in each formula.
+/// Fails on the first name that could also be an element until its reading is chosen.
+pub fn parse_equation_with(
+    input: impl AsRef<str>,
+    aliases: &Aliases,
+) -> Result<Equation, ParseError> {
+    parse_equation_with_config(input, aliases, &ParserConfig::default())
+}
+
+/// Parses the equation like `parse_equation_with`, failing on syntax that
+/// `config` does not allow.
+pub fn parse_equation_with_config(
+    input: impl AsRef<str>,
+    aliases: &Aliases,
+    config: &ParserConfig,
+) -> Result<Equation, ParseError> {
+    let input = input.as_ref();
+    let aliases = &config.allowed_aliases(aliases);
+    if let Some(ambiguity) = aliases.ambiguities(input).into_iter().next() {
+        return Err(ParseError::Ambiguous {
+            name: ambiguity.name,
+            span: ambiguity.span,
+        });
+    }
+    let (equation, conditions) = split_conditions(input);
+    let mut warnings = Vec::new();
+    let normalized = normalize_symbols(equation);
+    if normalized != equation {
+        warnings.push(Warning::InputNormalized);
+    }
+    let (equation, catalyst) = split_catalyst(&normalized);
+    // Formulas are found in the original input in order, to report where they are
+    let mut cursor = 0;
+    let mut coefficients = Vec::new();
+    let mut parse_side = |side: &str| -> Result<Vec<Chemical>, ParseError> {
+        split_species(side)
+            .into_iter()
+            .map(|token| {
+                let token = token.trim();
+                let start = input[cursor..]
+                    .find(token)
+                    .map_or(cursor, |offset| cursor + offset);
+                // A normalized token is not in the input as is, so its span is only near it
+                cursor = (start + token.len()).min(input.len());
+                while !input.is_char_boundary(cursor) {
+                    cursor += 1;
+                }
+                let formula = strip_coefficient(token, config, &mut warnings);
+                let written = token[..token.len() - formula.len()].trim();
+                coefficients.push(written.parse().unwrap_or(1));
+                if config.allow_lowercase {
+                    if let Some(fixed) = fix_case(formula) {
+                        warnings.push(Warning::CaseCorrected { formula: fixed });
+                    }
+                }
+                let chemical = parse_chemical_with_config(formula, aliases, config)
+                    .ok_or_else(|| ParseError::invalid_formula(token, start..cursor))?;
+                if has_ambiguous_charge(formula) {
+                    warnings.push(Warning::AmbiguousCharge {
+                        formula: split_phase(formula).0.to_string(),
+                    });
+                }
+                Ok(chemical)
+            })
+            .collect()
+    };
+    let mut sides = equation.splitn(2, '=');
+    let reagents = parse_side(sides.next().unwrap())?;
+    let products = parse_side(sides.next().ok_or(ParseError::MissingProducts)?)?;
+    Ok(Equation {
+        reagents,
+        products,
+        catalyst,
+        conditions,
+        coefficients,
+        warnings,
+    })
+}
+
+/// Splits a side at each `+` that separates species rather than ends a
+/// charge: one after a space, or one right before the next species.
+pub fn split_species(side: &str) -> Vec<&str> {
+    let mut species = Vec::new();
+    let mut start = 0;
+    for (index, c) in side.char_indices() {
+        if c != '+' {
+            continue;
+        }
+        let after_space = side[..index].ends_with(char::is_whitespace);
+        let before_species = side[index + 1..]
+            .starts_with(|c: char| c.is_alphanumeric() || closing_bracket(c).is_some());
+        if after_space || before_species {
+            species.push(side[start..index].trim());
+            start = index + 1;
+        }
+    }
+    species.push(side[start..].trim());
+    species.retain(|species| !species.is_empty());
+    species
+}
+
+/// Rewrites the input in canonical form, with single spaces around `+` and
+/// `=` and normalized symbols, keeping the coefficients and state symbols as typed.
+/// Reads the input with `aliases` and `config` as it would be balanced.
+pub fn tidy(
+    input: impl AsRef<str>,
+    aliases: &Aliases,
+    config: &ParserConfig,
+) -> Result<String, ParseError> {
+    let equation = parse_equation_with_config(input, aliases, config)?;
+    let coefficients = equation.coefficients.clone();
+    Ok(equation.into_reaction(coefficients).to_string())
+}
+
+/// Swaps what is before and after the arrow, keeping the arrow, its catalyst
+/// and the conditions as written. Fails when there is no arrow.
+pub fn swap_sides(input: &str) -> Option<String> {
+    let (equation, conditions) = input.split_at(input.find(';').unwrap_or(input.len()));
+    let arrow = match equation.find("=[").zip(equation.find("]=")) {
+        Some((start, end)) if start < end => start..end + 2,
+        _ => {
+            tokenize(equation)
+                .into_iter()
+                .find(|token| token.kind == TokenKind::Arrow)?
+                .range
+        }
+    };
+    Some(format!(
+        "{} {} {}{}",
+        equation[arrow.end..].trim(),
+        &equation[arrow.clone()],
+        equation[..arrow.start].trim(),
+        conditions
+    ))
+}
+
+/// The unbalanced equation of `reaction` with one more species, for fixing a
+/// forgotten reagent or product. The span of an error is within `formula`.
+pub fn append_species(
+    reaction: &Reaction,
+    formula: &str,
+    side: Side,
+) -> Result<String, ParseError> {
+    let formula = formula.trim();
+    let chemical = parse_chemical(formula)
+        .ok_or_else(|| ParseError::invalid_formula(formula, 0..formula.len()))?;
+    let mut appended = reaction.clone();
+    match side {
+        Side::Reagent => appended.reagents.push(chemical),
+        Side::Product => appended.products.push(chemical),
+    }
+    appended.coefficients = vec![1; appended.reagents.len() + appended.products.len()];
+    Ok(appended.to_string())
+}
+
+/// Drops a leading coefficient from a formula, noting it as a warning. A
+/// trailing state symbol is left for the parser to keep with the species.
+fn strip_coefficient<'a>(
+    token: &'a str,
+    config: &ParserConfig,
+    warnings: &mut Vec<Warning>,
+) -> &'a str {
+    let mut formula = token;
+    let rest = formula
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start();
+    // A formula proper starts with an element, a group or an electron, never a digit
+    let has_coefficient = rest.len() < formula.len()
+        && (rest.starts_with(|c: char| {
+            c.is_ascii_uppercase()
+                || closing_bracket(c).is_some()
+                || config.allow_lowercase && c.is_ascii_lowercase()
+        }) || is_electron(split_phase(rest).0));
+    if has_coefficient {
+        formula = rest;
+        warnings.push(Warning::CoefficientReplaced {
+            formula: split_phase(formula).0.to_string(),
+        });
+    }
+    formula
+}
+
+/// Takes the catalyst written over the arrow, as in `N2 + H2 =[Fe]= NH3`, out of the equation.
+fn split_catalyst(input: &str) -> (String, Option<String>) {
+    if let Some(start) = input.find("=[") {
+        if let Some(length) = input[start..].find("]=") {
+            let catalyst = input[start + 2..start + length].trim().to_string();
+            let equation = format!("{}={}", &input[..start], &input[start + length + 2..]);
+            return (equation, Some(catalyst));
+        }
+    }
+    (input.into(), None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alias::Alias;
+    use crate::chemical::Phase;
+
+    #[test]
+    fn parse_with_catalyst_and_conditions() {
+        let equation = parse_equation("N2 + H2 =[Fe]= NH3; 450°C").unwrap();
+        assert_eq!(2, equation.reagents.len());
+        assert_eq!(1, equation.products.len());
+        assert_eq!(Some("Fe".into()), equation.catalyst);
+        assert_eq!(1, equation.conditions.len());
+    }
+
+    #[test]
+    fn ambiguous_alias_stops_parsing() {
+        let mut aliases = Aliases::default().with_organic_groups(true);
+        assert_eq!(
+            Err(ParseError::Ambiguous {
+                name: "Ac".into(),
+                span: 0..2
+            }),
+            parse_equation_with("AcOH + NaOH = NaOAc + H2O", &aliases).map(|_| ())
+        );
+        aliases
+            .readings
+            .insert("Ac".into(), crate::alias::Reading::Alias);
+        let equation = parse_equation_with("AcOH + NaOH = NaOAc + H2O", &aliases).unwrap();
+        assert_eq!("C2H3NaO2", equation.products[0].canonical_formula());
+    }
+
+    #[test]
+    fn annotations_become_warnings() {
+        let equation = parse_equation("2H2(g) + O2(g) → 2H2O(L)").unwrap();
+        assert_eq!(3, equation.products.len() + equation.reagents.len());
+        assert_eq!(
+            vec![
+                Warning::InputNormalized,
+                Warning::CoefficientReplaced {
+                    formula: "H2".into()
+                },
+                Warning::CoefficientReplaced {
+                    formula: "H2O".into()
+                },
+            ],
+            equation.warnings
+        );
+        assert_eq!(Some(Phase::Liquid), equation.products[0].phase);
+        assert_eq!(
+            "2H2(g) + O2(g) = 2H2O(l)",
+            equation.into_reaction(vec![2, 1, 2]).to_string()
+        );
+        assert!(parse_equation("H2 + O2 = H2O").unwrap().warnings.is_empty());
+        assert_eq!(
+            vec![Warning::AmbiguousCharge {
+                formula: "B4O72-".into()
+            }],
+            parse_equation("B4O72- + H2O = H3BO3 + OH-")
+                .unwrap()
+                .warnings
+        );
+    }
+
+    #[test]
+    fn config_limits_syntax() {
+        let aliases = Aliases::default();
+        let parse = |input, config| parse_equation_with_config(input, &aliases, &config);
+        assert!(parse("H2(g) + O2(g) = H2O(l)", ParserConfig::default()).is_ok());
+        assert!(matches!(
+            parse("H2(g) + O2(g) = H2O(l)", ParserConfig::strict()),
+            Err(ParseError::InvalidFormula { .. })
+        ));
+        assert!(parse("Cu + Ag+ = Cu2+ + Ag", ParserConfig::strict()).is_err());
+        let equation = parse("2h2 + o2 = h2o", ParserConfig::lenient()).unwrap();
+        assert_eq!(
+            "H2 + O2 = H2O",
+            equation.into_reaction(vec![1; 3]).to_string()
+        );
+        let warnings = parse("nacl = na + cl2", ParserConfig::lenient())
+            .unwrap()
+            .warnings;
+        assert!(warnings.contains(&Warning::CaseCorrected {
+            formula: "NaCl".into()
+        }));
+    }
+
+    #[test]
+    fn tidy_input() {
+        let aliases = Aliases::default();
+        let plain = |input: &str| tidy(input, &aliases, &ParserConfig::default());
+        assert_eq!(
+            Ok("2H2(g) + O2 = 2H2O; Δ".into()),
+            plain("2H2(g)+O2  ->2 H2O;Δ")
+        );
+        assert_eq!(Ok("N2 + H2 =[Fe]= NH3".into()), plain("N₂+H₂ =[ Fe ]=NH₃"));
+        assert_eq!(Ok("3Fe + 2O2 = Fe3O4".into()), plain("3 Fe+2O2=Fe3O4"));
+        assert!(plain("H2 + O2").is_err());
+
+        let mut aliases = Aliases::default();
+        aliases.insert(Alias::parse("EtOH = C2H5OH").unwrap());
+        assert_eq!(
+            Ok("EtOH + 3O2 = 2CO2 + 3H2O".into()),
+            tidy("EtOH+3O2=2CO2+3H2O", &aliases, &ParserConfig::default())
+        );
+        assert_eq!(
+            Ok("2H2 + O2 = 2H2O".into()),
+            tidy("2h2+o2=2h2o", &aliases, &ParserConfig::lenient())
+        );
+    }
+
+    #[test]
+    fn swap_equation_sides() {
+        assert_eq!(
+            Some("H2O -> H2 + O2; Δ".into()),
+            swap_sides("H2 + O2->H2O; Δ")
+        );
+        assert_eq!(
+            Some("NH3 =[Fe]= N2 + H2".into()),
+            swap_sides("N2 + H2 =[Fe]= NH3")
+        );
+        assert_eq!(None, swap_sides("H2 + O2"));
+    }
+
+    #[test]
+    fn append_forgotten_species() {
+        let equation = parse_equation("CH4 + O2 = CO2; Δ").unwrap();
+        let reaction = equation.into_reaction(vec![1, 1, 1]);
+        assert_eq!(
+            Ok("CH4 + O2 = CO2 + H2O; Δ".into()),
+            append_species(&reaction, " H2O ", Side::Product)
+        );
+        assert_eq!(
+            Ok("CH4 + O2 + N2 = CO2; Δ".into()),
+            append_species(&reaction, "N2", Side::Reagent)
+        );
+        assert!(append_species(&reaction, "물", Side::Product).is_err());
+    }
+
+    #[test]
+    fn errors_point_at_formula() {
+        let error = parse_equation("H2 + O2 = H2O + 2x").unwrap_err();
+        assert_eq!(Some(16..18), error.span());
+        let i18n = I18n::default();
+        assert_eq!("2x은(는) 올바른 화학식이 아닙니다.", error.localize(&i18n));
+
+        let error = parse_equation("H2 + 산소 = H2O").unwrap_err();
+        assert!(matches!(error, ParseError::Hangul { .. }));
+        let error = parse_equation("K3[Fe(CN)6) = K3Fe(CN)6").unwrap_err();
+        assert!(matches!(error, ParseError::UnmatchedBracket { .. }));
+        assert_eq!(Some(0..11), error.span());
+        assert_eq!(
+            "error.missing_products",
+            parse_equation("H2O").unwrap_err().message_key()
+        );
+    }
+}
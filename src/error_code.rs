@@ -0,0 +1,83 @@
+//! Stable codes of the errors, shown with each message so that reports can
+//! name an error exactly, and the help written for each of them.
+
+/// Every code with the message key of its help, in the order they are listed.
+/// Parsing errors start at `E001`, solving errors at `E010` and errors of
+/// quantities at `E020`; a code is never reused for another error.
+pub const ERROR_CODES: [(&str, &str); 12] = [
+    ("E001", "how_to.trouble.formula.fix"),
+    ("E002", "help.error.hangul"),
+    ("E003", "help.error.missing_products"),
+    ("E004", "help.error.ambiguous"),
+    ("E005", "help.error.unmatched_bracket"),
+    ("E010", "how_to.trouble.elements.fix"),
+    ("E011", "how_to.trouble.infinite.fix"),
+    ("E012", "help.error.verification_failed"),
+    ("E013", "help.error.inconsistent"),
+    ("E020", "help.error.quantity_invalid"),
+    ("E021", "help.error.unknown_unit"),
+    ("E022", "help.error.incompatible"),
+];
+
+/// The message key of the help for `code`.
+pub fn help_key(code: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|&(_, key)| key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::ParseError;
+    use crate::quantity::QuantityError;
+    use crate::reaction::ReactionError;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn every_error_has_help() {
+        let codes: Vec<&str> = [
+            ParseError::InvalidFormula {
+                formula: String::new(),
+                span: 0..0,
+            }
+            .code(),
+            ParseError::Hangul {
+                formula: String::new(),
+                span: 0..0,
+            }
+            .code(),
+            ParseError::MissingProducts.code(),
+            ParseError::Ambiguous {
+                name: String::new(),
+                span: 0..0,
+            }
+            .code(),
+            ParseError::UnmatchedBracket {
+                formula: String::new(),
+                span: 0..0,
+            }
+            .code(),
+            ReactionError::UnbalancedElements.code(),
+            ReactionError::InfiniteSolution.code(),
+            ReactionError::VerificationFailed.code(),
+            ReactionError::Inconsistent.code(),
+            QuantityError::Invalid {
+                input: String::new(),
+            }
+            .code(),
+            QuantityError::UnknownUnit {
+                unit: String::new(),
+            }
+            .code(),
+            QuantityError::Incompatible { from: "g", to: "L" }.code(),
+        ]
+        .to_vec();
+        let listed: Vec<&str> = ERROR_CODES.iter().map(|&(code, _)| code).collect();
+        assert_eq!(listed, codes);
+        assert_eq!(Some("help.error.hangul"), help_key("E002"));
+        assert_eq!(None, help_key("E999"));
+    }
+}
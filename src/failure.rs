@@ -0,0 +1,91 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Oldest failures are dropped past this many.
+pub const MAX_FAILURES: usize = 200;
+
+/// Opt-in log of inputs that failed, kept on the device so users can report
+/// parser gaps with reproducible examples. Nothing is recorded until enabled.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct FailureLog {
+    pub enabled: bool,
+    pub entries: Vec<Failure>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Failure {
+    pub input: String,
+    /// Message key of the error, such as `error.invalid_formula`.
+    pub category: String,
+}
+
+impl FailureLog {
+    pub fn record(&mut self, input: &str, category: &str) {
+        if !self.enabled {
+            return;
+        }
+        if self.entries.len() >= MAX_FAILURES {
+            self.entries.remove(0);
+        }
+        self.entries.push(Failure {
+            input: input.to_string(),
+            category: category.to_string(),
+        });
+    }
+
+    /// Number of failures in each category.
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for failure in &self.entries {
+            *counts.entry(failure.category.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// One `category<TAB>input` line per failure, oldest first.
+    pub fn export(&self) -> String {
+        let lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|failure| alloc::format!("{}\t{}", failure.category, failure.input))
+            .collect();
+        lines.join("\n")
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_only_when_enabled() {
+        let mut log = FailureLog::default();
+        log.record("H2 + O2", "error.missing_products");
+        assert!(log.entries.is_empty());
+
+        log.enabled = true;
+        log.record("H2 + O2", "error.missing_products");
+        log.record("2x = H2", "error.invalid_formula");
+        log.record("H2O", "error.missing_products");
+        assert_eq!(Some(&2), log.counts().get("error.missing_products"));
+        assert_eq!(
+            "error.missing_products\tH2 + O2\nerror.invalid_formula\t2x = H2\nerror.missing_products\tH2O",
+            log.export()
+        );
+
+        for _ in 0..MAX_FAILURES {
+            log.record("H2O", "error.missing_products");
+        }
+        assert_eq!(MAX_FAILURES, log.entries.len());
+        assert_eq!("H2O", log.entries[0].input);
+    }
+}
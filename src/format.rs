@@ -0,0 +1,341 @@
+//! Formatting of chemicals and reactions into renderer-independent parts,
+//! so the same markup can be produced in the browser or on a server.
+
+use crate::charge::format_charge;
+use crate::chemical::{Chemical, HYDRATE_SEPARATORS};
+use crate::condition::Condition;
+use crate::diagram::{element_bar_chart, mass_balance_scale, particle_diagram};
+use crate::i18n::I18n;
+use crate::number::NumberFormat;
+use crate::reaction::Reaction;
+use crate::renderer::{subscript, superscript};
+use crate::theme::THEMES;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// How coefficients stand out from the formulas they multiply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CoefficientStyle {
+    /// `<b>2</b>H2O`, the original look.
+    #[default]
+    Bold,
+    Normal,
+    /// Set apart by a space, as in `2 H2O`.
+    Spaced,
+}
+
+impl CoefficientStyle {
+    pub const ALL: [CoefficientStyle; 3] = [
+        CoefficientStyle::Bold,
+        CoefficientStyle::Normal,
+        CoefficientStyle::Spaced,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            CoefficientStyle::Bold => "bold",
+            CoefficientStyle::Normal => "normal",
+            CoefficientStyle::Spaced => "spaced",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|style| style.id() == id)
+    }
+
+    /// The coefficient as it goes before its species, empty when it is 1.
+    pub fn write(self, coefficient: i64) -> String {
+        if coefficient <= 1 {
+            String::new()
+        } else if self == CoefficientStyle::Spaced {
+            format!("{} ", coefficient)
+        } else {
+            coefficient.to_string()
+        }
+    }
+}
+
+/// How counts and charges are raised or lowered in markup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ScriptStyle {
+    /// `<sub>` and `<sup>` elements.
+    #[default]
+    Markup,
+    /// Unicode subscript and superscript characters, which survive copying as text.
+    Unicode,
+}
+
+impl ScriptStyle {
+    pub const ALL: [ScriptStyle; 2] = [ScriptStyle::Markup, ScriptStyle::Unicode];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            ScriptStyle::Markup => "markup",
+            ScriptStyle::Unicode => "unicode",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|style| style.id() == id)
+    }
+}
+
+/// Stylistic choices for writing out a reaction, which every renderer follows
+/// where its notation can. The same style always gives the same output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize), serde(default))]
+pub struct OutputStyle {
+    pub coefficients: CoefficientStyle,
+    pub scripts: ScriptStyle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormattedChemical {
+    /// Coefficient of the species with the given index, hidden when it is 1.
+    Coefficient(usize, i64),
+    Text(String),
+    Sub(String),
+    /// Charge of a species, like `2-`.
+    Sup(String),
+    Fragment(Vec<FormattedChemical>),
+    Species(usize, Vec<FormattedChemical>),
+    /// Equals sign, with the catalyst written over it and the conditions under it if any.
+    Arrow(Option<String>, Option<String>),
+}
+
+impl FormattedChemical {
+    /// Writes the part as HTML in `style`, matching the markup of the web view.
+    pub fn write_html(&self, style: &OutputStyle, out: &mut String) {
+        match self {
+            FormattedChemical::Coefficient(_, coefficient) => {
+                let written = style.coefficients.write(*coefficient);
+                if style.coefficients == CoefficientStyle::Bold && !written.is_empty() {
+                    out.push_str(&format!("<b>{}</b>", written));
+                } else {
+                    out.push_str(&written);
+                }
+            }
+            FormattedChemical::Text(s) => escape_into(s, out),
+            FormattedChemical::Sub(s) => match style.scripts {
+                ScriptStyle::Markup => {
+                    out.push_str("<sub>");
+                    escape_into(s, out);
+                    out.push_str("</sub>");
+                }
+                ScriptStyle::Unicode => out.extend(s.chars().map(subscript)),
+            },
+            FormattedChemical::Sup(s) => match style.scripts {
+                ScriptStyle::Markup => {
+                    out.push_str("<sup>");
+                    escape_into(s, out);
+                    out.push_str("</sup>");
+                }
+                ScriptStyle::Unicode => out.extend(s.chars().map(superscript)),
+            },
+            FormattedChemical::Fragment(inner) => write_span("fragment", inner, style, out),
+            FormattedChemical::Species(_, inner) => write_span("species", inner, style, out),
+            FormattedChemical::Arrow(None, None) => out.push_str(" = "),
+            FormattedChemical::Arrow(over, under) => {
+                out.push_str("<span class=\"arrow\"><small>");
+                escape_into(over.as_deref().unwrap_or_default(), out);
+                out.push_str("</small><span>=</span><small>");
+                escape_into(under.as_deref().unwrap_or_default(), out);
+                out.push_str("</small></span>");
+            }
+        }
+    }
+}
+
+fn write_span(class: &str, inner: &[FormattedChemical], style: &OutputStyle, out: &mut String) {
+    out.push_str(&format!("<span class=\"{}\">", class));
+    for part in inner {
+        part.write_html(style, out);
+    }
+    out.push_str("</span>");
+}
+
+pub(crate) fn escape_into(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Renders a balanced reaction and its diagrams as static HTML, for
+/// pre-rendering a shared permalink without a browser.
+pub fn render_to_string(reaction: &Reaction) -> String {
+    let theme = &THEMES[0];
+    let i18n = I18n::default();
+    let mut out = String::from("<div class=\"result\">");
+    for part in format_reaction(reaction) {
+        part.write_html(&OutputStyle::default(), &mut out);
+    }
+    out.push_str("</div><div class=\"diagram\">");
+    out.push_str(&particle_diagram(reaction, theme));
+    out.push_str(&element_bar_chart(reaction, theme, &i18n));
+    if let Some(svg) = mass_balance_scale(reaction, theme, &NumberFormat::default(), &i18n) {
+        out.push_str(&svg);
+    }
+    out.push_str("</div>");
+    out
+}
+
+pub fn format_chemicals(chemical: &str) -> Vec<FormattedChemical> {
+    let mut components: Vec<FormattedChemical> = Vec::new();
+    let mut stage = chemical;
+    while let Some(index) = stage.find(|c: char| c.is_numeric()) {
+        let text = &stage[..index];
+        components.push(FormattedChemical::Text(text.into()));
+        stage = &stage[index..];
+        let end = stage.find(|c: char| !c.is_numeric()).unwrap_or(stage.len());
+        // The count of water in a hydrate is written full size, as in `CuSO4·5H2O`
+        if text.ends_with(&HYDRATE_SEPARATORS[..]) {
+            components.push(FormattedChemical::Text(stage[..end].into()));
+        } else {
+            components.push(FormattedChemical::Sub(stage[..end].into()));
+        }
+        stage = &stage[end..];
+    }
+    if !stage.is_empty() {
+        components.push(FormattedChemical::Text(stage.into()));
+    }
+    components
+}
+
+/// Formats the input as typed, keeping the condition clause as plain text.
+pub fn format_input(input: &str) -> Vec<FormattedChemical> {
+    match input.find(';') {
+        Some(index) => {
+            let mut result = format_chemicals(&input[..index]);
+            result.push(FormattedChemical::Text(input[index..].into()));
+            result
+        }
+        None => format_chemicals(input),
+    }
+}
+
+pub fn format_reaction(reaction: &Reaction) -> Vec<FormattedChemical> {
+    let mut result = Vec::new();
+    for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+        if index == reaction.reagents.len() {
+            let conditions = if reaction.conditions.is_empty() {
+                None
+            } else {
+                let conditions: Vec<_> = reaction
+                    .conditions
+                    .iter()
+                    .map(Condition::to_string)
+                    .collect();
+                Some(conditions.join(", "))
+            };
+            result.push(FormattedChemical::Arrow(
+                reaction.catalyst.clone(),
+                conditions,
+            ));
+        } else if index > 0 {
+            result.push(FormattedChemical::Text(" + ".into()));
+        }
+        result.push(FormattedChemical::Coefficient(index, coefficient));
+        result.push(FormattedChemical::Species(
+            index,
+            format_fragments(chemical),
+        ));
+    }
+    result
+}
+
+pub fn format_fragments(chemical: &Chemical) -> Vec<FormattedChemical> {
+    let mut parts: Vec<_> = chemical
+        .fragments
+        .iter()
+        .map(|fragment| FormattedChemical::Fragment(format_chemicals(fragment)))
+        .collect();
+    if chemical.charge != 0 {
+        parts.push(FormattedChemical::Sup(format_charge(chemical.charge)));
+    }
+    if let Some(phase) = chemical.phase {
+        parts.push(FormattedChemical::Text(phase.symbol().into()));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+    #[test]
+    fn test_chem() {
+        assert_eq!(
+            vec![
+                FormattedChemical::Text("H".into()),
+                FormattedChemical::Sub("2".into()),
+            ],
+            format_chemicals("H2")
+        );
+    }
+
+    #[test]
+    fn hydrate_count_is_not_a_subscript() {
+        assert_eq!(
+            vec![
+                FormattedChemical::Text("·".into()),
+                FormattedChemical::Text("5".into()),
+                FormattedChemical::Text("H".into()),
+                FormattedChemical::Sub("2".into()),
+                FormattedChemical::Text("O".into()),
+            ],
+            format_chemicals("·5H2O")
+        );
+    }
+
+    #[test]
+    fn test_fragments() {
+        let chemical = parse_chemical("CH3OH").unwrap();
+        assert_eq!(
+            vec![
+                FormattedChemical::Fragment(vec![
+                    FormattedChemical::Text("CH".into()),
+                    FormattedChemical::Sub("3".into()),
+                ]),
+                FormattedChemical::Fragment(vec![FormattedChemical::Text("OH".into())]),
+            ],
+            format_fragments(&chemical)
+        );
+    }
+
+    #[test]
+    fn test_render_to_string() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("H2O").unwrap()],
+            vec![2, 1, 2],
+        );
+        let html = render_to_string(&reaction);
+        assert!(html.starts_with(
+            "<div class=\"result\"><b>2</b><span class=\"species\"><span class=\"fragment\">H<sub>2</sub></span></span> + "
+        ));
+        assert!(html.contains("<svg"));
+
+        let mut out = String::new();
+        FormattedChemical::Text("a<b".into()).write_html(&OutputStyle::default(), &mut out);
+        assert_eq!("a&lt;b", out);
+    }
+}
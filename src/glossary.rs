@@ -0,0 +1,116 @@
+//! Chemistry terms explained on hover.
+//!
+//! The Korean wording is built in. A catalog can localize an entry with the
+//! `glossary.<id>.term` and `glossary.<id>` messages.
+
+use alloc::vec::Vec;
+
+pub struct Entry {
+    pub id: &'static str,
+    pub term: &'static str,
+    pub explanation: &'static str,
+}
+
+pub static GLOSSARY: [Entry; 9] = [
+    Entry {
+        id: "coefficient",
+        term: "계수",
+        explanation: "화학식 앞에 붙어 반응에 참여하는 입자 수의 비를 나타내는 수",
+    },
+    Entry {
+        id: "reactant",
+        term: "반응물",
+        explanation: "반응식의 왼쪽에 적는, 반응하여 없어지는 물질",
+    },
+    Entry {
+        id: "product",
+        term: "생성물",
+        explanation: "반응식의 오른쪽에 적는, 반응하여 새로 생기는 물질",
+    },
+    Entry {
+        id: "undetermined_coefficients",
+        term: "미정계수법",
+        explanation: "계수를 미지수로 두고 원소마다 원자 수가 같다는 식을 세워 푸는 방법",
+    },
+    Entry {
+        id: "equation",
+        term: "반응식",
+        explanation: "화학 반응을 화학식과 등호로 나타낸 식",
+    },
+    Entry {
+        id: "formula",
+        term: "화학식",
+        explanation: "물질을 이루는 원소와 그 원자 수를 원소 기호로 나타낸 식",
+    },
+    Entry {
+        id: "catalyst",
+        term: "촉매",
+        explanation: "반응 속도를 바꾸지만 반응 전후에 그대로 남는 물질",
+    },
+    Entry {
+        id: "intermediate",
+        term: "중간체",
+        explanation: "여러 단계 반응에서 한 단계의 생성물이자 다음 단계의 반응물인 물질",
+    },
+    Entry {
+        id: "molar_mass",
+        term: "몰 질량",
+        explanation: "물질 1몰의 질량",
+    },
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    /// A glossary term, with the id of its entry.
+    Term(&'a str, &'a str),
+}
+
+/// Splits `text` at occurrences of the given `(id, term)` pairs, preferring the longest term at each position.
+pub fn mark_terms<'a>(text: &'a str, terms: &[(&'a str, &str)]) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let mut plain_start = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let found = terms
+            .iter()
+            .filter(|(_, term)| !term.is_empty() && text[index..].starts_with(term))
+            .max_by_key(|(_, term)| term.len());
+        match found {
+            Some(&(id, term)) => {
+                if plain_start < index {
+                    segments.push(Segment::Text(&text[plain_start..index]));
+                }
+                segments.push(Segment::Term(id, &text[index..index + term.len()]));
+                index += term.len();
+                plain_start = index;
+            }
+            None => {
+                index += text[index..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+    if plain_start < text.len() {
+        segments.push(Segment::Text(&text[plain_start..]));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mark_glossary_terms() {
+        let terms = [("reactant", "반응물"), ("coefficient", "계수")];
+        assert_eq!(
+            vec![
+                Segment::Term("reactant", "반응물"),
+                Segment::Text("의 "),
+                Segment::Term("coefficient", "계수"),
+                Segment::Text("를 맞춥니다."),
+            ],
+            mark_terms("반응물의 계수를 맞춥니다.", &terms)
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Standard half-reactions, recognized when typed with `e-` in either
+//! direction and paired into cell reactions.
+//!
+//! A typed half-reaction is matched against the library by its species,
+//! ignoring coefficients, and balanced from the library entry, which also
+//! gives its potential. Any other is left to `parse_equation`, where `e-` is
+//! a species carrying only charge.
+
+use crate::chemical::plain_superscript;
+use crate::equation::split_species;
+use crate::paste::normalize_symbols;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A reduction half-reaction written `oxidized form + n e- = reduced form`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalfReaction {
+    pub equation: &'static str,
+    /// Standard reduction potential at 25 °C, in volts.
+    pub potential: f64,
+}
+
+macro_rules! half_reactions {
+    ($(($equation:expr, $potential:expr)),* $(,)?) => {
+        [$(HalfReaction { equation: $equation, potential: $potential }),*]
+    };
+}
+
+/// Ordered from the strongest oxidizing agent down.
+pub const HALF_REACTIONS: [HalfReaction; 24] = half_reactions![
+    ("F2 + 2e- = 2F^-", 2.87),
+    ("H2O2 + 2H^+ + 2e- = 2H2O", 1.78),
+    ("MnO4^- + 8H^+ + 5e- = Mn^2+ + 4H2O", 1.51),
+    ("Au^3+ + 3e- = Au", 1.50),
+    ("Cl2 + 2e- = 2Cl^-", 1.36),
+    ("Cr2O7^2- + 14H^+ + 6e- = 2Cr^3+ + 7H2O", 1.33),
+    ("O2 + 4H^+ + 4e- = 2H2O", 1.23),
+    ("Br2 + 2e- = 2Br^-", 1.07),
+    ("Ag^+ + e- = Ag", 0.80),
+    ("Fe^3+ + e- = Fe^2+", 0.77),
+    ("I2 + 2e- = 2I^-", 0.54),
+    ("Cu^2+ + 2e- = Cu", 0.34),
+    ("2H^+ + 2e- = H2", 0.00),
+    ("Pb^2+ + 2e- = Pb", -0.13),
+    ("Sn^2+ + 2e- = Sn", -0.14),
+    ("Ni^2+ + 2e- = Ni", -0.25),
+    ("Fe^2+ + 2e- = Fe", -0.44),
+    ("Zn^2+ + 2e- = Zn", -0.76),
+    ("2H2O + 2e- = H2 + 2OH^-", -0.83),
+    ("Al^3+ + 3e- = Al", -1.66),
+    ("Mg^2+ + 2e- = Mg", -2.37),
+    ("Na^+ + e- = Na", -2.71),
+    ("K^+ + e- = K", -2.93),
+    ("Li^+ + e- = Li", -3.04),
+];
+
+const ELECTRON: &str = "e-";
+
+/// A species with its coefficient, as written in a library entry.
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    coefficient: u32,
+    species: String,
+}
+
+/// Splits off the coefficient of a species, with superscript charges
+/// written in plain text.
+fn term(written: &str) -> Term {
+    let written: String = written.chars().map(plain_superscript).collect();
+    let digits = written.len()
+        - written
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    Term {
+        coefficient: written[..digits].parse().unwrap_or(1),
+        species: String::from(&written[digits..]),
+    }
+}
+
+/// Compares species without the `^`, which users often leave out, so that
+/// `Cu2+` and `MnO4-` are the library's `Cu^2+` and `MnO4^-`.
+fn same_term(a: &Term, b: &Term) -> bool {
+    a.species
+        .chars()
+        .filter(|&c| c != '^')
+        .eq(b.species.chars().filter(|&c| c != '^'))
+}
+
+fn terms(side: &str) -> Vec<Term> {
+    split_species(side).into_iter().map(term).collect()
+}
+
+fn sides(equation: &str) -> Option<(Vec<Term>, Vec<Term>)> {
+    let normalized = normalize_symbols(equation);
+    let (left, right) = normalized.split_once('=')?;
+    Some((terms(left), terms(right)))
+}
+
+fn same_species(a: &[Term], b: &[Term]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|term| b.iter().any(|other| same_term(term, other)))
+}
+
+fn write_side(terms: &[Term]) -> String {
+    let written: Vec<String> = terms
+        .iter()
+        .map(|term| match term.coefficient {
+            1 => term.species.clone(),
+            coefficient => format!("{}{}", coefficient, term.species),
+        })
+        .collect();
+    written.join(" + ")
+}
+
+impl HalfReaction {
+    fn sides(&self) -> (Vec<Term>, Vec<Term>) {
+        sides(self.equation).expect("library half-reactions have `=`")
+    }
+
+    pub fn electrons(&self) -> u32 {
+        self.sides()
+            .0
+            .iter()
+            .find(|term| term.species == ELECTRON)
+            .map_or(0, |term| term.coefficient)
+    }
+}
+
+/// A library half-reaction found in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recognized {
+    /// Index into `HALF_REACTIONS`.
+    pub index: usize,
+    /// Whether it was typed as an oxidation, with the electrons on the right.
+    pub oxidation: bool,
+}
+
+/// Finds the library half-reaction with the same species as `input` on each
+/// side, whatever the coefficients. Only input with `e-` is considered.
+pub fn recognize(input: &str) -> Option<Recognized> {
+    let (left, right) = sides(input)?;
+    if !left
+        .iter()
+        .chain(&right)
+        .any(|term| term.species == ELECTRON)
+    {
+        return None;
+    }
+    HALF_REACTIONS.iter().enumerate().find_map(|(index, half)| {
+        let (oxidized, reduced) = half.sides();
+        if same_species(&left, &oxidized) && same_species(&right, &reduced) {
+            Some(Recognized {
+                index,
+                oxidation: false,
+            })
+        } else if same_species(&left, &reduced) && same_species(&right, &oxidized) {
+            Some(Recognized {
+                index,
+                oxidation: true,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+impl Recognized {
+    pub fn half_reaction(&self) -> &'static HalfReaction {
+        &HALF_REACTIONS[self.index]
+    }
+
+    /// The balanced half-reaction in the direction it was typed.
+    pub fn equation(&self) -> String {
+        let (oxidized, reduced) = self.half_reaction().sides();
+        if self.oxidation {
+            format!("{} = {}", write_side(&reduced), write_side(&oxidized))
+        } else {
+            String::from(self.half_reaction().equation)
+        }
+    }
+
+    /// Library half-reactions that make a cell with a positive potential
+    /// together with this one, the one typed as an oxidation being the anode.
+    pub fn partners(&self) -> Vec<usize> {
+        let potential = self.half_reaction().potential;
+        (0..HALF_REACTIONS.len())
+            .filter(|&index| {
+                let other = HALF_REACTIONS[index].potential;
+                if self.oxidation {
+                    other > potential
+                } else {
+                    other < potential
+                }
+            })
+            .collect()
+    }
+
+    /// The cell reaction of this half-reaction and the partner at `index`.
+    pub fn cell(&self, index: usize) -> CellReaction {
+        if self.oxidation {
+            cell_reaction(index, self.index)
+        } else {
+            cell_reaction(self.index, index)
+        }
+    }
+}
+
+/// A full reaction made of two half-reactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellReaction {
+    pub equation: String,
+    /// Standard cell potential in volts, positive when spontaneous.
+    pub potential: f64,
+}
+
+/// Combines a reduction at the `cathode` with the reverse of the one at the
+/// `anode`, both indices into `HALF_REACTIONS`, so that the electrons cancel.
+pub fn cell_reaction(cathode: usize, anode: usize) -> CellReaction {
+    let (cathode, anode) = (&HALF_REACTIONS[cathode], &HALF_REACTIONS[anode]);
+    let (gained, lost) = (cathode.electrons(), anode.electrons());
+    let electrons = gained / gcd(gained, lost) * lost;
+    let (cathode_left, cathode_right) = cathode.sides();
+    let (anode_left, anode_right) = anode.sides();
+    let mut left: Vec<Term> = Vec::new();
+    let mut right: Vec<Term> = Vec::new();
+    let scaled = [
+        (cathode_left, electrons / gained, true),
+        (cathode_right, electrons / gained, false),
+        (anode_right, electrons / lost, true),
+        (anode_left, electrons / lost, false),
+    ];
+    for (terms, factor, on_left) in scaled.iter() {
+        for term in terms.iter().filter(|term| term.species != ELECTRON) {
+            let side = if *on_left { &mut left } else { &mut right };
+            add_term(side, &term.species, term.coefficient * factor);
+        }
+    }
+    // Species on both sides, like H+ and H2O, only count once
+    for term in left.iter_mut() {
+        if let Some(other) = right.iter_mut().find(|other| other.species == term.species) {
+            let common = term.coefficient.min(other.coefficient);
+            term.coefficient -= common;
+            other.coefficient -= common;
+        }
+    }
+    left.retain(|term| term.coefficient > 0);
+    right.retain(|term| term.coefficient > 0);
+    CellReaction {
+        equation: format!("{} = {}", write_side(&left), write_side(&right)),
+        potential: cathode.potential - anode.potential,
+    }
+}
+
+fn add_term(side: &mut Vec<Term>, species: &str, coefficient: u32) {
+    match side.iter_mut().find(|term| term.species == species) {
+        Some(term) => term.coefficient += coefficient,
+        None => side.push(Term {
+            coefficient,
+            species: String::from(species),
+        }),
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recognize_typed_half_reactions() {
+        let copper = recognize("Cu2+ + e- = Cu").unwrap();
+        assert!(!copper.oxidation);
+        assert_eq!("Cu^2+ + 2e- = Cu", copper.equation());
+        let zinc = recognize("Zn → Zn²⁺ + e⁻").unwrap();
+        assert!(zinc.oxidation);
+        assert_eq!("Zn = Zn^2+ + 2e-", zinc.equation());
+        let permanganate = recognize("MnO4- + H+ + e- = Mn2+ + H2O").unwrap();
+        assert_eq!(1.51, permanganate.half_reaction().potential);
+        assert_eq!(5, permanganate.half_reaction().electrons());
+        assert_eq!(None, recognize("Cu^2+ + Zn = Cu + Zn^2+"));
+        assert_eq!(None, recognize("Cu2+ + e- = Ag"));
+    }
+
+    #[test]
+    fn pair_into_cells() {
+        let zinc = recognize("Zn = Zn^2+ + 2e-").unwrap();
+        let partners = zinc.partners();
+        let copper = recognize("Cu^2+ + 2e- = Cu").unwrap();
+        assert!(partners.contains(&copper.index));
+        assert!(!partners
+            .iter()
+            .any(|&index| HALF_REACTIONS[index].equation.contains("Mg")));
+
+        let daniell = zinc.cell(copper.index);
+        assert_eq!("Cu^2+ + Zn = Cu + Zn^2+", daniell.equation);
+        assert!((daniell.potential - 1.10).abs() < 1e-9);
+
+        let iron = recognize("Fe^2+ = Fe^3+ + e-").unwrap();
+        let permanganate = recognize("MnO4^- + H^+ + e- = Mn^2+ + H2O").unwrap();
+        assert_eq!(
+            "MnO4^- + 8H^+ + 5Fe^2+ = Mn^2+ + 4H2O + 5Fe^3+",
+            iron.cell(permanganate.index).equation
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Hints for balancing an equation by hand, read from the structure of its
+//! element-by-species matrix rather than kept for each known reaction.
+
+use crate::chemical::Chemical;
+use crate::i18n::I18n;
+use crate::reaction::{calculate_coefficients, ReactionError};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Coefficients from this size on are worth a warning.
+pub const LARGE_COEFFICIENT: i64 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn id(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn message_key(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Medium => "difficulty.medium",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hint {
+    /// Elements in a single species on each side, whose ratio follows at once.
+    BalanceFirst { elements: Vec<String> },
+    /// Elements spread over the most species, easiest once the rest is fixed.
+    BalanceLast { elements: Vec<String> },
+    /// Some coefficient is at least `LARGE_COEFFICIENT`.
+    LargeCoefficients,
+    /// Starting `species` at 1 leads to fractions, which all become whole
+    /// when multiplied by `multiplier`.
+    Fractions { species: String, multiplier: i64 },
+    /// The species allow more than one independent reaction.
+    NotUnique,
+}
+
+impl Hint {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Hint::BalanceFirst { .. } => "hint.balance_first",
+            Hint::BalanceLast { .. } => "hint.balance_last",
+            Hint::LargeCoefficients => "hint.large_coefficients",
+            Hint::Fractions { .. } => "hint.fractions",
+            Hint::NotUnique => "hint.not_unique",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            Hint::BalanceFirst { elements } | Hint::BalanceLast { elements } => {
+                i18n.format(self.message_key(), &[&elements.join(", ")])
+            }
+            Hint::Fractions {
+                species,
+                multiplier,
+            } => i18n.format(self.message_key(), &[species, multiplier]),
+            Hint::LargeCoefficients | Hint::NotUnique => i18n.t(self.message_key()).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub difficulty: Difficulty,
+    /// From the least to the most revealing.
+    pub hints: Vec<Hint>,
+}
+
+/// Analyzes an unbalanced equation, or gives `None` when the sides are made
+/// of different elements and no hint would help.
+pub fn analyze(reagents: &[Chemical], products: &[Chemical]) -> Option<Analysis> {
+    let coefficients = match calculate_coefficients(reagents, products) {
+        Ok(coefficients) => coefficients,
+        Err(ReactionError::InfiniteSolution) => {
+            return Some(Analysis {
+                difficulty: Difficulty::Hard,
+                hints: vec![Hint::NotUnique],
+            })
+        }
+        Err(_) => return None,
+    };
+
+    // Species containing each element on each side
+    let mut occurrences: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for chemical in reagents {
+        for element in chemical.parts.keys() {
+            occurrences.entry(element).or_default().0 += 1;
+        }
+    }
+    for chemical in products {
+        for element in chemical.parts.keys() {
+            occurrences.entry(element).or_default().1 += 1;
+        }
+    }
+    let elements_where = |keep: &dyn Fn(usize, usize) -> bool| -> Vec<String> {
+        occurrences
+            .iter()
+            .filter(|(_, &(left, right))| keep(left, right))
+            .map(|(&element, _)| String::from(element))
+            .collect()
+    };
+    let first = elements_where(&|left, right| left == 1 && right == 1);
+    let most = occurrences
+        .values()
+        .map(|&(left, right)| left + right)
+        .max()
+        .unwrap_or(0);
+    let last = if most > 2 {
+        elements_where(&|left, right| left + right == most)
+    } else {
+        Vec::new()
+    };
+
+    // Balancing by hand usually starts from the biggest species at 1
+    let atoms = |chemical: &Chemical| chemical.parts.values().sum::<usize>();
+    let all: Vec<&Chemical> = reagents.iter().chain(products).collect();
+    let mut anchor = 0;
+    for (index, chemical) in all.iter().enumerate() {
+        if atoms(chemical) > atoms(all[anchor]) {
+            anchor = index;
+        }
+    }
+    let largest = coefficients.iter().copied().max().unwrap_or(1);
+
+    let mut hints = Vec::new();
+    if !first.is_empty() {
+        hints.push(Hint::BalanceFirst { elements: first });
+    }
+    let spread = !last.is_empty();
+    if spread {
+        hints.push(Hint::BalanceLast { elements: last });
+    }
+    if largest >= LARGE_COEFFICIENT {
+        hints.push(Hint::LargeCoefficients);
+    }
+    let fractions = coefficients[anchor] > 1;
+    if fractions {
+        hints.push(Hint::Fractions {
+            species: all[anchor].display.clone(),
+            multiplier: coefficients[anchor],
+        });
+    }
+
+    let difficulty = if largest >= 2 * LARGE_COEFFICIENT || all.len() >= 6 {
+        Difficulty::Hard
+    } else if largest >= LARGE_COEFFICIENT || all.len() >= 5 || (spread && fractions) {
+        Difficulty::Medium
+    } else {
+        Difficulty::Easy
+    };
+    Some(Analysis { difficulty, hints })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+
+    fn analysis(input: &str) -> Option<Analysis> {
+        let equation = parse_equation(input).unwrap();
+        analyze(&equation.reagents, &equation.products)
+    }
+
+    #[test]
+    fn hints_from_structure() {
+        let ethane = analysis("C2H6 + O2 = CO2 + H2O").unwrap();
+        assert_eq!(
+            vec![
+                Hint::BalanceFirst {
+                    elements: vec!["C".into(), "H".into()]
+                },
+                Hint::BalanceLast {
+                    elements: vec!["O".into()]
+                },
+                Hint::LargeCoefficients,
+                Hint::Fractions {
+                    species: "C2H6".into(),
+                    multiplier: 2
+                },
+            ],
+            ethane.hints
+        );
+        assert_eq!(Difficulty::Medium, ethane.difficulty);
+
+        let water = analysis("H2 + O2 = H2O").unwrap();
+        assert_eq!(Difficulty::Easy, water.difficulty);
+        assert_eq!(2, water.hints.len());
+
+        let underdetermined = analysis("H2 + O2 = H2O + H2O2").unwrap();
+        assert_eq!(vec![Hint::NotUnique], underdetermined.hints);
+        assert_eq!(None, analysis("H2 = O2"));
+    }
+}
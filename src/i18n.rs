@@ -0,0 +1,495 @@
+//! Message catalogs for the UI text.
+//!
+//! The Korean catalog is compiled in and backs every lookup. Other catalogs are
+//! JSON documents such as `{ "locale": "en", "name": "English", "messages": { ... } }`
+//! registered at runtime, where `{0}`, `{1}`, ... in a message stand for its arguments.
+//! A catalog may also carry `"number_format": { "decimal": ",", "group": "." }`.
+
+use crate::number::NumberFormat;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LOCALE: &str = "ko";
+
+static BUILTIN_MESSAGES: [(&str, &str); 324] = [
+    ("how_to.title", "How to use?"),
+    ("how_to.equation", "입력칸에 A + B = C + D와 같은 형태로 계수를 맞출 반응식을 작성합니다."),
+    (
+        "how_to.method",
+        "계수는 미정계수법으로 구하며, 반응물과 생성물의 원소마다 원자 수가 같아지도록 정합니다.",
+    ),
+    ("how_to.state", "(g), (aq)와 같은 물질의 상태는 화학식 뒤에 적으면 결과에도 그대로 나타납니다."),
+    ("how_to.smiles", "유기 화합물은 smiles:CCO와 같이 SMILES로 입력할 수도 있습니다."),
+    ("how_to.catalyst", "촉매는 N2 + H2 =[Fe]= NH3와 같이 등호 위에 적을 수 있습니다."),
+    ("how_to.stats", "통계 보기에서 지금까지 맞춘 반응식 수와 정답률을 확인할 수 있습니다."),
+    ("how_to.template", "빈칸 채우기 틀을 고르면 빈칸을 누르고 물질을 골라 반응식을 완성할 수 있습니다."),
+    ("how_to.conditions", "반응 조건은 N2 + H2 = NH3; 450°C, 200 atm과 같이 세미콜론 뒤에 적거나 조건 칸에 입력합니다."),
+    ("how_to.syntax", "입력 문법"),
+    ("how_to.syntax.formula", "원소 기호 뒤에 원자 수를 적습니다."),
+    ("how_to.syntax.parentheses", "괄호로 묶은 원자단 뒤에 개수를 적을 수 있습니다."),
+    ("how_to.syntax.complex", "착이온은 [ ] 나 { } 로 묶고, 전하는 닫는 괄호 뒤에 적습니다."),
+    ("how_to.syntax.condensed", "시성식처럼 같은 원소가 여러 번 나와도 됩니다."),
+    ("how_to.syntax.hydrate", "수화물은 · 또는 . 이나 * 뒤에 결정수를 적습니다."),
+    ("how_to.syntax.charge", "이온의 전하는 ^ 뒤에 적고, Cu2+ 나 OH- 처럼 생략할 수도 있습니다."),
+    ("how_to.syntax.electron", "반쪽 반응의 전자는 e- 로 적으면 전하만 맞춥니다."),
+    ("how_to.examples", "예제 (누르면 입력칸에 채워집니다)"),
+    ("how_to.features", "그 밖의 기능"),
+    ("how_to.troubleshooting", "문제 해결"),
+    ("how_to.trouble.formula", "올바른 화학식이 아니라고 나옵니다."),
+    (
+        "how_to.trouble.formula.fix",
+        "원소 기호의 대소문자를 확인해 주세요. Co는 코발트, CO는 일산화 탄소입니다.",
+    ),
+    ("how_to.trouble.elements", "반응물과 생성물의 원소 종류가 일치하지 않는다고 나옵니다."),
+    (
+        "how_to.trouble.elements.fix",
+        "빠진 반응물이나 생성물이 없는지, 화학식에 오타가 없는지 확인해 주세요.",
+    ),
+    ("how_to.trouble.infinite", "계수가 하나로 정해지지 않는다고 나옵니다."),
+    (
+        "how_to.trouble.infinite.fix",
+        "서로 독립적인 반응 두 개가 한 반응식에 섞여 있을 수 있습니다. 반응식을 나누어 입력해 주세요.",
+    ),
+    ("how_to.error_codes", "오류 코드"),
+    ("error.code_help", "이 오류의 도움말 보기"),
+    ("help.error.hangul", "한/영 키로 영문 입력으로 바꾼 뒤 화학식을 다시 입력해 주세요."),
+    ("help.error.missing_products", "반응물과 생성물 사이에 = 를 적어 주세요. → 같은 화살표도 됩니다."),
+    ("help.error.ambiguous", "원소 기호와 이름이 같은 약어가 있습니다. 오류 아래에서 어느 쪽으로 읽을지 골라 주세요."),
+    ("help.error.unmatched_bracket", "여는 괄호 (, [, { 마다 같은 모양의 닫는 괄호가 순서대로 있어야 합니다. 예: K3[Fe(CN)6]"),
+    ("help.error.verification_failed", "계수가 너무 커서 정확히 계산하지 못했을 수 있습니다. 설정에서 다른 풀이 방법을 골라 보세요."),
+    ("help.error.inconsistent", "고정한 계수 가운데 일부를 풀거나 다른 값으로 바꿔 보세요."),
+    ("help.error.quantity_invalid", "2.5 mol, 250 mL처럼 수 뒤에 단위를 적어 주세요."),
+    ("help.error.unknown_unit", "g, mol, L, mL 같은 단위를 써 주세요."),
+    ("help.error.incompatible", "질량은 질량끼리, 부피는 부피끼리처럼 같은 종류의 단위로만 바꿀 수 있습니다."),
+    ("calculator.title", "반응식 균형 계산기"),
+    ("paste.cleaned", "붙여 넣은 반응식에서 번호와 화살표를 정리했습니다."),
+    ("input.recent_elements", "자주 쓰는 원소"),
+    ("input.tidy", "정리"),
+    ("input.tidy_description", "띄어쓰기와 기호를 맞추어 반응식을 다시 씁니다."),
+    ("input.swap", "좌우 바꾸기"),
+    ("input.swap_description", "반응물과 생성물을 서로 바꿉니다. (Ctrl+Shift+S)"),
+    ("practice.start", "연습"),
+    ("practice.description", "계수를 직접 하나씩 올리고 내리며 맞춰 봅니다."),
+    ("practice.title", "직접 맞추기"),
+    ("practice.element", "원소"),
+    ("practice.reagents", "반응물"),
+    ("practice.products", "생성물"),
+    ("practice.revealed", "정답을 보았습니다."),
+    ("practice.reveal", "정답 보기"),
+    ("practice.end", "그만하기"),
+    ("practice.seconds", "{0}초"),
+    ("practice.time_left", "{0}초 남음"),
+    ("practice.time_limit", "제한 시간"),
+    ("practice.no_limit", "시간 제한 없음"),
+    ("practice.limit", "{0}초 도전"),
+    ("practice.best", "최고 기록 {0}"),
+    ("practice.new_best", "새 기록!"),
+    ("practice.score", "점수 {0} · 연속 {1} · ×{2}"),
+    ("append.reagent", "반응물에"),
+    ("append.product", "생성물에"),
+    ("append.add", "추가하고 다시 맞추기"),
+    ("common.close", "닫기"),
+    ("settings.theme", "테마"),
+    ("settings.locale", "언어"),
+    ("settings.solver", "풀이 방법"),
+    ("settings.auto_close", "괄호 자동으로 닫기"),
+    ("settings.coefficients", "계수 모양"),
+    ("settings.coefficients.bold", "굵은 계수"),
+    ("settings.coefficients.normal", "보통 계수"),
+    ("settings.coefficients.spaced", "띄어 쓴 계수"),
+    ("settings.scripts", "아래첨자 모양"),
+    ("settings.scripts.markup", "서식으로 쓴 첨자"),
+    ("settings.scripts.unicode", "유니코드 첨자"),
+    ("settings.parser", "입력 문법"),
+    ("settings.parser.phases", "상태 표시"),
+    ("settings.parser.charges", "이온 전하"),
+    ("settings.parser.names", "별칭"),
+    ("settings.parser.lowercase", "소문자 화학식"),
+    ("settings.parser.abbreviations", "유기 작용기 약어"),
+    ("settings.parser.strict", "엄격하게"),
+    ("settings.parser.lenient", "너그럽게"),
+    ("solver.integer", "정수 소거법"),
+    ("solver.rational", "유리수 기약 행사다리꼴"),
+    ("solver.bareiss", "Bareiss 소거법"),
+    ("solver.time", "{0}ms"),
+    ("solver.diagnostics", "랭크 {0}, 영공간 차원 {1}"),
+    ("solver.unique", "(유일해)"),
+    ("solver.not_unique", "(해가 하나로 정해지지 않음)"),
+    ("corpus.add", "코퍼스에 추가"),
+    ("corpus.export", "추가한 {0}개 내보내기"),
+    ("text_size.small", "작게"),
+    ("text_size.medium", "보통"),
+    ("text_size.large", "크게"),
+    ("species.unknown_element", "알 수 없는 원소가 포함되어 있습니다."),
+    ("species.molar_mass", "몰 질량 : {0} g/mol"),
+    ("species.molar_mass_unknown", "몰 질량 : 알 수 없음"),
+    ("species.unsaturation", "불포화도 : {0}"),
+    ("species.element", "원소"),
+    ("species.count", "개수"),
+    ("species.mass_percent", "질량 백분율"),
+    ("species.pubchem", "PubChem에서 검색"),
+    ("species.charge", "전하 : {0}"),
+    ("species.phase", "상태 : {0}"),
+    ("phase.solid", "고체 (s)"),
+    ("phase.liquid", "액체 (l)"),
+    ("phase.gas", "기체 (g)"),
+    ("phase.aqueous", "수용액 (aq)"),
+    ("ratio.title", "몰비"),
+    ("ratio.species", "화학종 고르기"),
+    ("conversion.title", "차원 분석"),
+    ("conversion.unit", "단위 환산"),
+    ("conversion.molar_mass", "몰 질량"),
+    ("conversion.mole_ratio", "몰비"),
+    ("conversion.unavailable", "몰 질량을 알 수 없거나 질량·몰수가 아닌 단위입니다."),
+    ("datasets.loading", "자료를 불러오는 중"),
+    ("datasets.offline", "오프라인이라 추가 자료를 불러오지 못했습니다."),
+    ("sync.syncing", "동기화 중"),
+    ("sync.synced", "동기화됨"),
+    ("sync.failed", "동기화하지 못했습니다."),
+    ("sync.now", "지금 동기화"),
+    ("sync.stop", "동기화 끄기"),
+    ("sync.offer", "이 링크는 계산 기록을 {0}에 저장하고 불러오려고 합니다. 믿을 수 있는 곳일 때만 허용하세요."),
+    ("sync.accept", "허용"),
+    ("sync.decline", "거절"),
+    ("network.open", "반응 네트워크 보기"),
+    ("network.close", "반응 네트워크 닫기"),
+    ("network.empty", "한 반응의 생성물을 반응물로 사용하는 반응이 기록에 없습니다."),
+    ("network.overall", "전체 반응 : {0}"),
+    ("network.cancelled", "소거된 중간체 : {0}"),
+    ("network.leftover", "남은 중간체 : {0}"),
+    ("stats.open", "통계 보기"),
+    ("stats.close", "통계 닫기"),
+    ("recording.start", "입력 녹화"),
+    ("recording.stop", "녹화 끝내기"),
+    ("recording.recording", "녹화 중"),
+    ("recording.play", "재생"),
+    ("recording.stop_playback", "재생 멈추기"),
+    ("recording.summary", "{0}단계, {1}초"),
+    ("palette.placeholder", "명령 검색"),
+    ("palette.empty", "일치하는 명령이 없습니다"),
+    ("palette.close", "닫기"),
+    ("palette.balance", "계수 맞추기"),
+    ("palette.tidy", "입력 정리"),
+    ("palette.swap_sides", "반응물과 생성물 바꾸기"),
+    ("palette.practice", "직접 맞춰 보기"),
+    ("palette.network", "반응 네트워크 보기/닫기"),
+    ("palette.stats", "통계 보기/닫기"),
+    ("palette.compounds", "자주 쓴 화합물 보기/닫기"),
+    ("palette.failures", "실패 기록 보기/닫기"),
+    ("palette.worksheet", "학습지 만들기"),
+    ("palette.race", "경주 링크 만들기"),
+    ("palette.export_session", "세션 내보내기"),
+    ("palette.auto_close", "괄호 자동 닫기 켜기/끄기"),
+    ("palette.theme", "테마: {0}"),
+    ("palette.text_size", "글자 크기: {0}"),
+    ("page.calculator", "계산기"),
+    ("page.history", "기록"),
+    ("page.tools", "도구"),
+    ("page.settings", "설정"),
+    ("page.about", "도움말"),
+    ("tool.composition", "조성 백분율"),
+    ("tool.invalid_formula", "화학식을 읽을 수 없습니다."),
+    ("tool.weak_acid", "약산·약염기 평형"),
+    ("tool.unknown_constant", "{0}의 이온화 상수를 알 수 없습니다."),
+    ("tool.positive_concentration", "농도는 0보다 커야 합니다."),
+    ("palette.go_to", "이동: {0}"),
+    ("stats.balanced", "균형을 맞춘 반응식"),
+    ("stats.error_rate", "오류율"),
+    ("stats.average_species", "평균 물질 수"),
+    ("stats.accuracy", "과제 정답률"),
+    ("compounds.open", "내 화합물 보기"),
+    ("compounds.close", "내 화합물 닫기"),
+    ("compounds.empty", "아직 기록에 화합물이 없습니다."),
+    ("compounds.formula", "화학식"),
+    ("compounds.count", "사용 횟수"),
+    ("compounds.molar_mass", "몰 질량 (g/mol)"),
+    ("compounds.insert", "입력"),
+    ("failures.open", "실패 기록 보기"),
+    ("failures.close", "실패 기록 닫기"),
+    ("failures.privacy", "기록은 이 기기에만 저장되며 어디로도 보내지 않습니다."),
+    ("failures.enable", "실패한 입력 기록하기"),
+    ("failures.disable", "기록 중지"),
+    ("failures.empty", "기록된 실패가 없습니다."),
+    ("failures.export", "기록 내보내기"),
+    ("failures.clear", "기록 지우기"),
+    ("aliases.title", "별칭"),
+    ("aliases.description", "자주 쓰는 원자단이나 물질에 Ph = C6H5 처럼 이름을 붙여 화학식 안에서 쓸 수 있습니다. 이름은 대문자로 시작하는 영문자입니다."),
+    ("aliases.organic_groups", "유기 원자단 약어 (Me, Et, Ph, Bu)"),
+    ("aliases.element_groups", "원소와 겹치는 약어 (Ac, Pr)"),
+    ("aliases.element_groups_description", "켜면 Ac, Pr처럼 원소 기호와 같은 약어가 나올 때 어느 쪽인지 묻습니다."),
+    ("aliases.add", "추가"),
+    ("aliases.remove", "삭제"),
+    ("aliases.invalid", "이름 = 화학식 형식으로 입력하세요."),
+    ("ambiguity.question", "{0}는 무엇인가요?"),
+    ("ambiguity.element", "원소 {0} ({1})"),
+    ("ambiguity.alias", "약어 {0} = {1}"),
+    ("grade.correct", "정답입니다."),
+    ("grade.not_lowest", "균형은 맞지만 가장 간단한 정수비가 아닙니다."),
+    ("grade.unbalanced", "원소의 수가 맞지 않습니다."),
+    ("grade.pending", "채점 전"),
+    ("assignment.title", "과제"),
+    ("assignment.grade", "채점"),
+    ("assignment.export", "결과 내보내기"),
+    ("assignment.create", "과제 링크 만들기"),
+    ("assignment.from_history", "기록에서 가져오기"),
+    ("race.create", "경주 링크 만들기"),
+    ("race.description", "같은 링크를 연 학생은 모두 같은 문제를 풀게 되며, 인터넷 연결 없이도 됩니다."),
+    ("race.title", "경주 #{0}"),
+    ("race.finished", "모든 문제를 {0}초 만에 맞혔습니다!"),
+    ("difficulty.easy", "쉬움"),
+    ("difficulty.medium", "보통"),
+    ("difficulty.hard", "어려움"),
+    ("hint.show", "힌트 보기"),
+    ("hint.balance_first", "양쪽에 한 번씩만 나오는 {0}부터 맞춰 보세요."),
+    ("hint.balance_last", "여러 물질에 나오는 {0}는 마지막에 맞추는 것이 쉽습니다."),
+    ("hint.large_coefficients", "계수가 꽤 클 수 있습니다."),
+    ("hint.fractions", "{0}의 계수를 1로 두고 분수 계수를 허용한 뒤, 모든 계수에 {1}을 곱해 보세요."),
+    ("hint.not_unique", "이 물질들로는 서로 독립인 반응이 여럿 있어 계수가 하나로 정해지지 않습니다."),
+    ("worksheet.create", "학습지 만들기"),
+    ("worksheet.generate", "새로 만들기"),
+    ("worksheet.print", "인쇄"),
+    ("worksheet.title", "화학 반응식 계수 맞추기"),
+    ("worksheet.name", "이름 : ____________"),
+    ("worksheet.answer_key", "정답"),
+    ("history.more", "이전 기록 더 보기 ({0}개 남음)"),
+    ("history.select", "여러 개 선택하려면 Shift를 누른 채 누르세요"),
+    ("history.selected", "{0}개 선택됨"),
+    ("history.combine", "헤스 법칙으로 합치기"),
+    ("history.to_worksheet", "학습지에 넣기"),
+    ("history.export", "선택한 기록 내보내기"),
+    ("history.delete", "삭제"),
+    ("history.deselect", "선택 해제"),
+    ("history.combined", "합친 반응"),
+    ("history.close_combined", "닫기"),
+    ("history.duplicate", "{0}에서 이미 맞춘 반응식입니다."),
+    ("history.jump", "보기"),
+    ("tag.placeholder", "태그 (쉼표로 구분)"),
+    ("tag.add", "태그 달기"),
+    ("tag.remove", "눌러서 태그 떼기"),
+    ("tag.filter", "태그"),
+    ("tag.all", "전체"),
+    ("note.add", "메모"),
+    ("note.edit", "눌러서 메모 고치기"),
+    ("note.placeholder", "예: 12b번, 세 번 만에 맞춤"),
+    ("note.save", "저장"),
+    ("note.cancel", "취소"),
+    ("reaction_type.combustion", "연소"),
+    ("reaction_type.neutralization", "중화"),
+    ("reaction_type.redox", "산화 환원"),
+    ("reaction_type.precipitation", "앙금 생성"),
+    ("session.export", "기록 내보내기"),
+    ("session.download", "session.json 내려받기"),
+    ("gallery.title", "풀이 모음"),
+    ("gallery.loading", "풀이를 불러오는 중"),
+    ("gallery.failed", "풀이를 불러오지 못했습니다."),
+    ("error.prefix", "Error : {0}"),
+    ("error.invalid_formula", "{0}은(는) 올바른 화학식이 아닙니다."),
+    ("error.hangul", "한글이 포함되어 있습니다 — 화학식은 영문 대소문자로 입력해주세요."),
+    ("error.ambiguous", "{0}는 원소일 수도, 약어일 수도 있습니다. 어느 쪽인지 골라주세요."),
+    ("error.unmatched_bracket", "{0}의 괄호 짝이 맞지 않습니다."),
+    ("error.missing_products", "반응물1 + 반응물2 + ... = 생성물1 + 생성물2 + ... 형식으로 입력해주세요."),
+    ("error.unbalanced_elements", "반응물의 원소 종류와 생성물의 원소 종류가 일치하지 않습니다."),
+    ("error.infinite_solution", "계수가 하나로 정해지지 않습니다."),
+    ("error.inconsistent", "지정한 계수로는 반응식을 맞출 수 없습니다."),
+    ("error.verification_failed", "계산 결과를 검증하지 못했습니다. 계수가 너무 클 수 있습니다."),
+    ("warning.coefficient_replaced", "{0} 앞에 입력한 계수는 계산한 값으로 바꿨습니다."),
+    ("warning.input_normalized", "화살표와 특수 문자를 일반 기호로 바꿨습니다."),
+    ("warning.case_corrected", "소문자로 입력한 화학식을 {0}(으)로 읽었습니다."),
+    ("warning.ambiguous_charge", "{0} 끝의 숫자를 원자 개수로 읽었습니다. 전하라면 C2O4^2- 처럼 ^ 뒤에 적어 주세요."),
+    ("advice.unwritten_charge", "{0}는 보통 {1} 전하를 띠는 이온인데 전하 없이 적혀 있습니다. 양쪽의 전하가 맞는지 확인하세요."),
+    ("oxidation.title", "산화수 변화"),
+    ("oxidation.element", "원소"),
+    ("oxidation.before", "반응 전"),
+    ("oxidation.after", "반응 후"),
+    ("oxidation.per_atom", "원자 하나당"),
+    ("oxidation.total", "전체"),
+    ("oxidation.loses", "전자 {0}개 잃음"),
+    ("oxidation.gains", "전자 {0}개 얻음"),
+    ("oxidation.bridge_loses", "전자 {0}개 × {1} 잃음"),
+    ("oxidation.bridge_gains", "전자 {0}개 × {1} 얻음"),
+    ("oxidation.redox", "산화·환원 반응"),
+    ("oxidation.disproportionation", "불균등화 반응"),
+    ("oxidation.comproportionation", "균등화 반응"),
+    ("diagram.reagents", "반응물"),
+    ("diagram.products", "생성물"),
+    ("diagram.unbalanced", "계수 1"),
+    ("diagram.balanced", "균형"),
+    ("charges.title", "전하 합계"),
+    ("charges.species", "화학종"),
+    ("charges.reagents", "반응물"),
+    ("charges.products", "생성물"),
+    ("charges.total", "합계"),
+    ("charges.conserved", "양쪽의 전하가 같습니다."),
+    ("charges.not_conserved", "양쪽의 전하가 다릅니다."),
+    ("half_reaction.potential", "E° = {0} V (환원 전위)"),
+    ("half_reaction.pair", "짝지을 반쪽 반응: "),
+    ("half_reaction.cell_potential", "E°셀 = {0} V"),
+    ("ph.title", "혼합 용액의 pH"),
+    ("ph.hint", "각 산과 염기의 농도와 부피를 0.1 M, 25 mL처럼 입력하면 섞은 뒤의 pH를 계산합니다."),
+    ("ph.result", "pH = {0}, pOH = {1}"),
+    ("ph.weak", "{0}는 약산 또는 약염기라 이온화 상수 없이는 pH를 계산할 수 없습니다."),
+    ("ph.not_acid_or_base", "{0}는 알려진 산이나 염기가 아닙니다."),
+    ("ph.no_volume", "부피를 입력해주세요."),
+    ("ph.ice_initial", "초기"),
+    ("ph.ice_change", "변화"),
+    ("ph.ice_equilibrium", "평형"),
+    ("ph.ice_conjugate", "짝염기·짝산"),
+    ("ph.weak_result", "{0} 용액만 있을 때 pH = {1}, 이온화도 {2}%"),
+    ("thermo.title", "열역학적 자발성"),
+    ("thermo.temperature", "온도"),
+    ("thermo.missing", "다음 물질의 열역학 자료가 없습니다: {0}"),
+    ("thermo.spontaneous", "이 온도에서 자발적입니다."),
+    ("thermo.not_spontaneous", "이 온도에서 비자발적입니다."),
+    ("thermo.always", "ΔH° < 0, ΔS° > 0이므로 모든 온도에서 자발적입니다."),
+    ("thermo.never", "ΔH° > 0, ΔS° < 0이므로 어떤 온도에서도 자발적이지 않습니다."),
+    ("thermo.below", "{0} K보다 낮은 온도에서 자발적입니다."),
+    ("thermo.above", "{0} K보다 높은 온도에서 자발적입니다."),
+    ("thermo.range", "그래프 온도 범위"),
+    ("quantity.invalid", "{0}는 단위가 붙은 수가 아닙니다."),
+    ("quantity.unknown_unit", "{0}는 알 수 없는 단위입니다."),
+    ("quantity.incompatible", "{0}를 {1}로 바꿀 수 없습니다."),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Catalog {
+    pub locale: String,
+    /// Name of the language in itself, shown in the language picker.
+    pub name: String,
+    pub messages: BTreeMap<String, String>,
+    #[cfg_attr(feature = "json", serde(default))]
+    pub number_format: NumberFormat,
+}
+
+impl Catalog {
+    pub fn builtin() -> Self {
+        Self {
+            locale: DEFAULT_LOCALE.into(),
+            name: "한국어".into(),
+            messages: BUILTIN_MESSAGES
+                .iter()
+                .map(|&(key, message)| (key.into(), message.into()))
+                .collect(),
+            number_format: NumberFormat::default(),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Registered catalogs and the locale in use.
+#[derive(Debug, Clone)]
+pub struct I18n {
+    catalogs: Vec<Catalog>,
+    locale: String,
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self {
+            catalogs: vec![Catalog::builtin()],
+            locale: DEFAULT_LOCALE.into(),
+        }
+    }
+}
+
+impl I18n {
+    /// Adds a catalog, replacing the one already registered for its locale.
+    pub fn register(&mut self, catalog: Catalog) {
+        match self
+            .catalogs
+            .iter_mut()
+            .find(|registered| registered.locale == catalog.locale)
+        {
+            Some(registered) => *registered = catalog,
+            None => self.catalogs.push(catalog),
+        }
+    }
+
+    pub fn catalogs(&self) -> &[Catalog] {
+        &self.catalogs
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Switches to a registered locale, returning whether it was found.
+    pub fn set_locale(&mut self, locale: &str) -> bool {
+        let registered = self.catalogs.iter().any(|catalog| catalog.locale == locale);
+        if registered {
+            self.locale = locale.into();
+        }
+        registered
+    }
+
+    /// Looks the key up in the current catalog, then in the built-in one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let lookup = |locale: &str| {
+            self.catalogs
+                .iter()
+                .find(|catalog| catalog.locale == locale)
+                .and_then(|catalog| catalog.messages.get(key))
+        };
+        lookup(&self.locale)
+            .or_else(|| lookup(DEFAULT_LOCALE))
+            .map(String::as_str)
+    }
+
+    /// Number format of the current locale.
+    pub fn number_format(&self) -> NumberFormat {
+        self.catalogs
+            .iter()
+            .find(|catalog| catalog.locale == self.locale)
+            .map_or_else(NumberFormat::default, |catalog| catalog.number_format)
+    }
+
+    /// The message of the key, or the key itself when no catalog has it.
+    pub fn t<'a>(&'a self, key: &'a str) -> &'a str {
+        self.get(key).unwrap_or(key)
+    }
+
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut message = self.t(key).to_string();
+        for (index, arg) in args.iter().enumerate() {
+            let placeholder = alloc::format!("{{{}}}", index);
+            message = message.replace(&placeholder, &arg.to_string());
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fall_back_to_builtin() {
+        let mut i18n = I18n::default();
+        assert!(!i18n.set_locale("en"));
+        let mut messages = BTreeMap::new();
+        messages.insert("history.more".into(), "Show more ({0} left)".into());
+        i18n.register(Catalog {
+            locale: "en".into(),
+            name: "English".into(),
+            messages,
+            number_format: NumberFormat {
+                decimal: ',',
+                group: None,
+            },
+        });
+        assert!(i18n.set_locale("en"));
+        assert_eq!(',', i18n.number_format().decimal);
+        assert_eq!("Show more (3 left)", i18n.format("history.more", &[&3]));
+        assert_eq!("과제", i18n.t("assignment.title"));
+        assert_eq!("missing.key", i18n.t("missing.key"));
+    }
+}
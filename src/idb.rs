@@ -0,0 +1,124 @@
+//! Minimal IndexedDB access for caching text values that outgrow local storage.
+
+use crate::storage::AppStorage;
+use js_sys::{Array, Function, Promise};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode, IdbVersionChangeEvent,
+};
+
+const DATABASE: &str = "chemef";
+/// Bumped whenever a store is added to `STORES`.
+const VERSION: u32 = 2;
+/// Optional datasets keyed by their id.
+pub const DATASETS: &str = "datasets";
+/// Saved sessions, the current one under `CURRENT_SESSION`.
+pub const SESSIONS: &str = "sessions";
+pub const CURRENT_SESSION: &str = "current";
+const STORES: [&str; 2] = [DATASETS, SESSIONS];
+
+/// Resolves with the result of the request once it succeeds.
+async fn wait(request: IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve: Function, reject: Function| {
+        let target = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = target.result().unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::UNDEFINED, &result);
+        });
+        let on_error = Closure::once_into_js(move || {
+            let _ = reject.call0(&JsValue::UNDEFINED);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = seed::window()
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is unavailable"))?;
+    let request = factory.open_with_u32(DATABASE, VERSION)?;
+    let on_upgrade = Closure::once_into_js(move |event: IdbVersionChangeEvent| {
+        let database = event
+            .target()
+            .and_then(|target| target.unchecked_into::<IdbOpenDbRequest>().result().ok())
+            .map(|result| result.unchecked_into::<IdbDatabase>());
+        if let Some(database) = database {
+            for store in STORES.iter() {
+                // Stores from an earlier version already exist, which is fine
+                let _ = database.create_object_store(store);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(wait(request.unchecked_into()).await?.unchecked_into())
+}
+
+pub async fn get(store: &str, key: &str) -> Option<String> {
+    let database = open().await.ok()?;
+    let request = database
+        .transaction_with_str(store)
+        .ok()?
+        .object_store(store)
+        .ok()?
+        .get(&JsValue::from_str(key))
+        .ok()?;
+    wait(request).await.ok()?.as_string()
+}
+
+pub async fn put(store: &str, key: &str, value: &str) -> Result<(), JsValue> {
+    let database = open().await?;
+    let request = database
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?
+        .object_store(store)?
+        .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?;
+    wait(request).await.map(|_| ())
+}
+
+pub async fn keys(store: &str) -> Option<Vec<String>> {
+    let database = open().await.ok()?;
+    let request = database
+        .transaction_with_str(store)
+        .ok()?
+        .object_store(store)
+        .ok()?
+        .get_all_keys()
+        .ok()?;
+    let keys = Array::from(&wait(request).await.ok()?);
+    Some(keys.iter().filter_map(|key| key.as_string()).collect())
+}
+
+pub async fn delete(store: &str, key: &str) -> Result<(), JsValue> {
+    let database = open().await?;
+    let request = database
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?
+        .object_store(store)?
+        .delete(&JsValue::from_str(key))?;
+    wait(request).await.map(|_| ())
+}
+
+/// The browser's IndexedDB, for data that outgrows local storage.
+pub struct IndexedDb;
+
+impl AppStorage for IndexedDb {
+    type Error = JsValue;
+
+    async fn load(&self, store: &str, key: &str) -> Option<String> {
+        get(store, key).await
+    }
+
+    async fn save(&self, store: &str, key: &str, value: &str) -> Result<(), JsValue> {
+        put(store, key, value).await
+    }
+
+    /// IndexedDB already returns keys in ascending order.
+    async fn list(&self, store: &str) -> Vec<String> {
+        keys(store).await.unwrap_or_default()
+    }
+
+    async fn delete(&self, store: &str, key: &str) -> Result<(), JsValue> {
+        delete(store, key).await
+    }
+}
@@ -0,0 +1,49 @@
+//! The browser's local storage as an `AppStorage`, for small values that
+//! should be readable without waiting on IndexedDB.
+
+use crate::storage::AppStorage;
+use wasm_bindgen::JsValue;
+
+/// Keeps each value under `store/key`.
+pub struct LocalStorage;
+
+fn storage() -> Result<web_sys::Storage, JsValue> {
+    seed::window()
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("local storage is unavailable"))
+}
+
+fn item_key(store: &str, key: &str) -> String {
+    format!("{}/{}", store, key)
+}
+
+impl AppStorage for LocalStorage {
+    type Error = JsValue;
+
+    async fn load(&self, store: &str, key: &str) -> Option<String> {
+        storage().ok()?.get_item(&item_key(store, key)).ok()?
+    }
+
+    async fn save(&self, store: &str, key: &str, value: &str) -> Result<(), JsValue> {
+        storage()?.set_item(&item_key(store, key), value)
+    }
+
+    async fn list(&self, store: &str) -> Vec<String> {
+        let storage = match storage() {
+            Ok(storage) => storage,
+            Err(_) => return Vec::new(),
+        };
+        let prefix = item_key(store, "");
+        let length = storage.length().unwrap_or(0);
+        let mut keys: Vec<String> = (0..length)
+            .filter_map(|index| storage.key(index).ok()?)
+            .filter_map(|key| key.strip_prefix(&prefix).map(String::from))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    async fn delete(&self, store: &str, key: &str) -> Result<(), JsValue> {
+        storage()?.remove_item(&item_key(store, key))
+    }
+}
@@ -0,0 +1,196 @@
+use crate::reaction::{gcd, net_reaction, Reaction};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A product of step `from` that the later step `to` uses as a reagent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub from: usize,
+    pub to: usize,
+    pub formula: String,
+}
+
+/// Finds every species passed from one reaction to a later one, in step order.
+pub fn find_links(steps: &[&Reaction]) -> Vec<Link> {
+    let mut links = Vec::new();
+    for (from, step) in steps.iter().enumerate() {
+        for product in &step.products {
+            let formula = product.canonical_formula();
+            for (to, later) in steps.iter().enumerate().skip(from + 1) {
+                let consumed = later
+                    .reagents
+                    .iter()
+                    .any(|reagent| reagent.canonical_formula() == formula);
+                if consumed {
+                    links.push(Link {
+                        from,
+                        to,
+                        formula: formula.clone(),
+                    });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Steps taking part in at least one link, in ascending order.
+pub fn linked_steps(links: &[Link]) -> Vec<usize> {
+    let mut steps: Vec<usize> = links.iter().flat_map(|link| [link.from, link.to]).collect();
+    steps.sort_unstable();
+    steps.dedup();
+    steps
+}
+
+/// Overall reaction of a multi-step sequence.
+#[derive(Debug, Clone)]
+pub struct Overall {
+    pub reaction: Reaction,
+    /// Factor each step was multiplied by before adding them up.
+    pub factors: Vec<i64>,
+    /// Intermediates that cancel out completely.
+    pub cancelled: Vec<String>,
+    /// Intermediates left over in the overall reaction.
+    pub leftover: Vec<String>,
+}
+
+/// Adds up ordered steps, scaling each one so that a species produced by the
+/// earlier steps is exactly consumed by it (or vice versa).
+pub fn overall_reaction(steps: &[&Reaction]) -> Overall {
+    let mut factors: Vec<i64> = Vec::with_capacity(steps.len());
+    let mut net: BTreeMap<String, i64> = BTreeMap::new();
+    for step in steps {
+        let signed = signed_coefficients(step);
+        let matched = signed.iter().find_map(|(formula, coefficient)| {
+            let accumulated = net.get(formula).copied().unwrap_or(0);
+            if accumulated != 0 && accumulated.signum() != coefficient.signum() {
+                Some((accumulated.abs(), coefficient.abs()))
+            } else {
+                None
+            }
+        });
+        let factor = if let Some((accumulated, coefficient)) = matched {
+            let divisor = gcd(accumulated, coefficient);
+            let scale = coefficient / divisor;
+            factors.iter_mut().for_each(|factor| *factor *= scale);
+            net.values_mut().for_each(|value| *value *= scale);
+            accumulated / divisor
+        } else {
+            1
+        };
+        factors.push(factor);
+        for (formula, coefficient) in signed {
+            *net.entry(formula).or_insert(0) += coefficient * factor;
+        }
+    }
+    let divisor = factors.iter().fold(0, |divisor, &factor| {
+        if divisor == 0 {
+            factor
+        } else {
+            gcd(divisor, factor)
+        }
+    });
+    factors
+        .iter_mut()
+        .for_each(|factor| *factor /= divisor.max(1));
+
+    let weighted: Vec<(&Reaction, i64)> =
+        steps.iter().copied().zip(factors.iter().copied()).collect();
+    let reaction = net_reaction(&weighted);
+    let mut cancelled = Vec::new();
+    let mut leftover = Vec::new();
+    for formula in intermediates(steps) {
+        let remains = reaction
+            .species()
+            .any(|(chemical, _)| chemical.canonical_formula() == formula);
+        if remains {
+            leftover.push(formula);
+        } else {
+            cancelled.push(formula);
+        }
+    }
+    Overall {
+        reaction,
+        factors,
+        cancelled,
+        leftover,
+    }
+}
+
+/// Products count positive, reagents negative.
+fn signed_coefficients(step: &Reaction) -> Vec<(String, i64)> {
+    step.species()
+        .enumerate()
+        .map(|(index, (chemical, coefficient))| {
+            let sign = if index < step.reagents.len() { -1 } else { 1 };
+            (chemical.canonical_formula(), sign * coefficient)
+        })
+        .collect()
+}
+
+/// Species produced by one step and consumed by another.
+fn intermediates(steps: &[&Reaction]) -> Vec<String> {
+    let mut formulas: Vec<String> = Vec::new();
+    for step in steps {
+        for product in &step.products {
+            let formula = product.canonical_formula();
+            let consumed = steps.iter().any(|other| {
+                other
+                    .reagents
+                    .iter()
+                    .any(|reagent| reagent.canonical_formula() == formula)
+            });
+            if consumed && !formulas.contains(&formula) {
+                formulas.push(formula);
+            }
+        }
+    }
+    formulas
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+
+    fn reaction(reagents: &[&str], products: &[&str]) -> Reaction {
+        let parse = |formulas: &[&str]| -> Vec<_> {
+            formulas
+                .iter()
+                .map(|f| parse_chemical(f).unwrap())
+                .collect()
+        };
+        let coefficients = vec![1; reagents.len() + products.len()];
+        Reaction::new(parse(reagents), parse(products), coefficients)
+    }
+
+    #[test]
+    fn overall_reaction_scales_steps() {
+        let mut first = reaction(&["N2", "H2"], &["NH3"]);
+        first.coefficients = vec![1, 3, 2];
+        let mut second = reaction(&["NH3", "O2"], &["NO", "H2O"]);
+        second.coefficients = vec![4, 5, 4, 6];
+        let overall = overall_reaction(&[&first, &second]);
+        assert_eq!(vec![2, 1], overall.factors);
+        assert_eq!("2N2 + 6H2 + 5O2 = 4NO + 6H2O", overall.reaction.to_string());
+        assert_eq!(vec!["H3N".to_string()], overall.cancelled);
+        assert!(overall.leftover.is_empty());
+    }
+
+    #[test]
+    fn find_links_between_steps() {
+        let steps = [
+            reaction(&["C", "O2"], &["CO2"]),
+            reaction(&["H2", "O2"], &["H2O"]),
+            reaction(&["CO2", "H2O"], &["H2CO3"]),
+        ];
+        let links = find_links(&steps.iter().collect::<Vec<_>>());
+        assert_eq!(2, links.len());
+        assert_eq!(
+            (0, 2, "CO2"),
+            (links[0].from, links[0].to, &*links[0].formula)
+        );
+        assert_eq!(vec![0, 1, 2], linked_steps(&links));
+    }
+}
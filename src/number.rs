@@ -0,0 +1,79 @@
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Separators for writing numbers in a locale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct NumberFormat {
+    pub decimal: char,
+    /// Thousands separator, or `None` to leave the integer part ungrouped.
+    pub group: Option<char>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: Some(','),
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Writes `value` rounded to `decimals` places, e.g. `1,234.50` or `1.234,50`.
+    pub fn format(&self, value: f64, decimals: usize) -> String {
+        let rounded = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = match rounded.find('.') {
+            Some(index) => (&rounded[..index], Some(&rounded[index + 1..])),
+            None => (rounded.as_str(), None),
+        };
+
+        let mut output = String::new();
+        // Rounding can turn a small negative value into zero, which gets no sign
+        if value < 0.0 && rounded.bytes().any(|byte| matches!(byte, b'1'..=b'9')) {
+            output.push('-');
+        }
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                if let Some(group) = self.group {
+                    output.push(group);
+                }
+            }
+            output.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            output.push(self.decimal);
+            output.push_str(fraction);
+        }
+        output
+    }
+
+    /// Writes a ratio as a percentage, e.g. `12.5%` for `0.125`.
+    pub fn percent(&self, ratio: f64, decimals: usize) -> String {
+        let mut output = self.format(ratio * 100.0, decimals);
+        output.push('%');
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format_number() {
+        let point = NumberFormat::default();
+        assert_eq!("1,234,567.890", point.format(1234567.89, 3));
+        assert_eq!("-12.5", point.format(-12.46, 1));
+        assert_eq!("0.0", point.format(-0.01, 1));
+        assert_eq!("12.5%", point.percent(0.125, 1));
+        let comma = NumberFormat {
+            decimal: ',',
+            group: Some('.'),
+        };
+        assert_eq!("1.234,50", comma.format(1234.5, 2));
+        assert_eq!("100", comma.format(99.6, 0));
+    }
+}
@@ -0,0 +1,463 @@
+//! Oxidation states assigned by the usual textbook rules, and how they change
+//! over a reaction.
+
+use crate::charge::COMMON_IONS;
+use crate::chemical::{parse_chemical, Chemical};
+use crate::reaction::{gcd, Reaction};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+const ALKALI_METALS: [&str; 6] = ["Li", "Na", "K", "Rb", "Cs", "Fr"];
+const ALKALINE_EARTH_METALS: [&str; 6] = ["Be", "Mg", "Ca", "Sr", "Ba", "Ra"];
+const NONMETALS: [&str; 22] = [
+    "H", "He", "B", "C", "N", "O", "F", "Ne", "Si", "P", "S", "Cl", "Ar", "As", "Se", "Br", "Kr",
+    "Te", "I", "Xe", "At", "Rn",
+];
+
+/// An oxidation state, which is an average and so may be a fraction, like
+/// `+8/3` for iron in Fe3O4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct OxidationState {
+    numerator: i64,
+    /// Always positive.
+    denominator: i64,
+}
+
+impl OxidationState {
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        let divisor = if numerator == 0 {
+            denominator.abs()
+        } else {
+            gcd(numerator.abs(), denominator.abs())
+        } * denominator.signum();
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub fn integer(value: i64) -> Self {
+        Self::new(value, 1)
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.numerator > 0
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn as_integer(self) -> Option<i64> {
+        if self.denominator == 1 {
+            Some(self.numerator)
+        } else {
+            None
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        Self::new(self.numerator.abs(), self.denominator)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.numerator * other.denominator - other.numerator * self.denominator,
+            self.denominator * other.denominator,
+        )
+    }
+
+    fn times(self, count: i64) -> Self {
+        Self::new(self.numerator * count, self.denominator)
+    }
+}
+
+impl fmt::Display for OxidationState {
+    /// Writes the sign of nonzero states, like `+7`, `-2`, `0` or `+8/3`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.numerator > 0 {
+            write!(f, "+")?;
+        }
+        write!(f, "{}", self.numerator)?;
+        if self.denominator != 1 {
+            write!(f, "/{}", self.denominator)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_metal(element: &str) -> bool {
+    !NONMETALS.contains(&element)
+}
+
+/// The state an element takes by rule in a compound of `parts`, if it has one.
+fn fixed_state(element: &str, parts: &BTreeMap<String, usize>) -> Option<i64> {
+    let with = |other: &str| parts.contains_key(other);
+    match element {
+        "F" => Some(-1),
+        _ if ALKALI_METALS.contains(&element) => Some(1),
+        _ if ALKALINE_EARTH_METALS.contains(&element) => Some(2),
+        "Al" => Some(3),
+        // Metal hydrides
+        "H" if parts.keys().all(|other| other == "H" || is_metal(other)) => Some(-1),
+        "H" => Some(1),
+        "O" => Some(-2),
+        "Cl" | "Br" | "I" if !with("O") && !with("F") => Some(-1),
+        _ => None,
+    }
+}
+
+/// The elements ordered by how surely their rule applies, so that a rule
+/// lower down is given up first, like oxygen's in a peroxide.
+fn rule_order(element: &str) -> usize {
+    match element {
+        "F" => 0,
+        _ if ALKALI_METALS.contains(&element) || ALKALINE_EARTH_METALS.contains(&element) => 1,
+        "Al" => 2,
+        "H" => 3,
+        "O" => 4,
+        _ => 5,
+    }
+}
+
+/// Assigns states to `parts` with total `charge`, fixing states by rule until
+/// one element is left and solving that one from the total.
+fn assign(
+    parts: &BTreeMap<String, usize>,
+    charge: i64,
+) -> Option<BTreeMap<String, OxidationState>> {
+    if parts.len() == 1 {
+        let (element, &count) = parts.iter().next()?;
+        let mut states = BTreeMap::new();
+        states.insert(element.clone(), OxidationState::new(charge, count as i64));
+        return Some(states);
+    }
+    let mut elements: Vec<&String> = parts.keys().collect();
+    elements.sort_by_key(|element| rule_order(element));
+    let mut states = BTreeMap::new();
+    let mut fixed_total = 0;
+    let mut unknown = Vec::new();
+    for (index, element) in elements.iter().enumerate() {
+        let last = index + 1 == elements.len() && unknown.is_empty();
+        match fixed_state(element, parts) {
+            Some(state) if !last => {
+                fixed_total += state * parts[*element] as i64;
+                states.insert((*element).clone(), OxidationState::integer(state));
+            }
+            _ => unknown.push(*element),
+        }
+    }
+    match unknown.as_slice() {
+        [element] => {
+            let state = OxidationState::new(charge - fixed_total, parts[*element] as i64);
+            states.insert((*element).clone(), state);
+            Some(states)
+        }
+        _ => None,
+    }
+}
+
+/// Splits a salt like CuSO4 or Fe2(SO4)3 into one element and a common ion,
+/// giving the element the charge left over from the ion out of `charge`.
+fn assign_salt(
+    parts: &BTreeMap<String, usize>,
+    charge: i64,
+) -> Option<BTreeMap<String, OxidationState>> {
+    COMMON_IONS.iter().find_map(|&(ion, ion_charge)| {
+        let ion = parse_chemical(ion)?.parts;
+        let counterion: Vec<(&String, &usize)> = parts
+            .iter()
+            .filter(|(element, _)| !ion.contains_key(*element))
+            .collect();
+        let (element, &count) = match counterion.as_slice() {
+            [only] => *only,
+            _ => return None,
+        };
+        // The number of ions is the same for each of their elements
+        let ions = parts.get(ion.keys().next()?)? / ion.values().next()?;
+        let matches = ions > 0
+            && parts.len() == ion.len() + 1
+            && ion
+                .iter()
+                .all(|(part, &number)| parts.get(part) == Some(&(number * ions)));
+        if !matches {
+            return None;
+        }
+        let mut states = assign(&ion, ion_charge as i64)?;
+        let state = OxidationState::new(charge - ion_charge as i64 * ions as i64, count as i64);
+        states.insert(element.clone(), state);
+        Some(states)
+    })
+}
+
+/// The oxidation state of each element of a species, which add up to its
+/// charge, or `None` when the rules leave more than one element undecided.
+pub fn oxidation_states(chemical: &Chemical) -> Option<BTreeMap<String, OxidationState>> {
+    let charge = chemical.charge as i64;
+    assign(&chemical.parts, charge).or_else(|| assign_salt(&chemical.parts, charge))
+}
+
+/// Atoms of one element going from one oxidation state to another.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct OxidationChange {
+    pub element: String,
+    pub before: OxidationState,
+    pub after: OxidationState,
+    /// Number of atoms that change, counted with the coefficients.
+    pub atoms: i64,
+}
+
+impl OxidationChange {
+    /// Electrons each atom loses, negative when it gains them.
+    pub fn electrons_per_atom(&self) -> OxidationState {
+        self.after.sub(self.before)
+    }
+
+    /// Electrons lost by all the atoms, negative when they are gained.
+    pub fn electrons(&self) -> OxidationState {
+        self.electrons_per_atom().times(self.atoms)
+    }
+
+    pub fn is_oxidation(&self) -> bool {
+        self.electrons_per_atom().is_positive()
+    }
+}
+
+/// What kind of redox reaction a set of changes makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum RedoxKind {
+    /// One element is oxidized and another reduced.
+    Redox,
+    /// Atoms of one element at one state are both oxidized and reduced, like
+    /// chlorine in `Cl2 + NaOH`.
+    Disproportionation,
+    /// Atoms of one element at two states meet at one in between, like sulfur
+    /// in `H2S + SO2`.
+    Comproportionation,
+}
+
+impl RedoxKind {
+    pub fn message_key(self) -> &'static str {
+        match self {
+            RedoxKind::Redox => "oxidation.redox",
+            RedoxKind::Disproportionation => "oxidation.disproportionation",
+            RedoxKind::Comproportionation => "oxidation.comproportionation",
+        }
+    }
+}
+
+/// Classifies the changes of `oxidation_report`, `None` when there are none.
+pub fn redox_kind(changes: &[OxidationChange]) -> Option<RedoxKind> {
+    if changes.is_empty() {
+        return None;
+    }
+    for oxidation in changes.iter().filter(|change| change.is_oxidation()) {
+        for reduction in changes.iter().filter(|change| !change.is_oxidation()) {
+            if oxidation.element != reduction.element {
+                continue;
+            }
+            if oxidation.before == reduction.before {
+                return Some(RedoxKind::Disproportionation);
+            }
+            if oxidation.after == reduction.after {
+                return Some(RedoxKind::Comproportionation);
+            }
+        }
+    }
+    Some(RedoxKind::Redox)
+}
+
+/// Atom counts of an element at each oxidation state on one side, in order of appearance.
+type StateCounts = Vec<(OxidationState, i64)>;
+
+fn add_count(counts: &mut StateCounts, state: OxidationState, atoms: i64) {
+    match counts.iter_mut().find(|(known, _)| *known == state) {
+        Some((_, total)) => *total += atoms,
+        None => counts.push((state, atoms)),
+    }
+}
+
+/// Every change of oxidation state in a balanced reaction, empty when it is
+/// not a redox reaction. Fails when a species has no assignable states, or
+/// when an element is at several states on both sides, so that which atoms
+/// become which is unclear.
+pub fn oxidation_report(reaction: &Reaction) -> Option<Vec<OxidationChange>> {
+    let mut sides: BTreeMap<String, (StateCounts, StateCounts)> = BTreeMap::new();
+    for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+        for (element, state) in oxidation_states(chemical)? {
+            let atoms = chemical.parts[&element] as i64 * coefficient;
+            let (reagents, products) = sides.entry(element).or_default();
+            let side = if index < reaction.reagents.len() {
+                reagents
+            } else {
+                products
+            };
+            add_count(side, state, atoms);
+        }
+    }
+    let mut changes = Vec::new();
+    for (element, (reagents, products)) in sides {
+        let pairs: Vec<(OxidationState, OxidationState, i64)> =
+            match (reagents.as_slice(), products.as_slice()) {
+                ([(before, _)], _) => products
+                    .iter()
+                    .map(|&(after, atoms)| (*before, after, atoms))
+                    .collect(),
+                (_, [(after, _)]) => reagents
+                    .iter()
+                    .map(|&(before, atoms)| (before, *after, atoms))
+                    .collect(),
+                _ => return None,
+            };
+        for (before, after, atoms) in pairs {
+            if before != after {
+                changes.push(OxidationChange {
+                    element: element.clone(),
+                    before,
+                    after,
+                    atoms,
+                });
+            }
+        }
+    }
+    Some(changes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn states(formula: &str) -> Vec<(String, String)> {
+        oxidation_states(&parse_chemical(formula).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|(element, state)| (element, state.to_string()))
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(element, state)| (element.into(), state.into()))
+            .collect()
+    }
+
+    #[test]
+    fn assign_states() {
+        assert_eq!(
+            pairs(&[("K", "+1"), ("Mn", "+7"), ("O", "-2")]),
+            states("KMnO4")
+        );
+        assert_eq!(pairs(&[("H", "+1"), ("O", "-1")]), states("H2O2"));
+        assert_eq!(pairs(&[("F", "-1"), ("O", "+2")]), states("OF2"));
+        assert_eq!(pairs(&[("H", "-1"), ("Na", "+1")]), states("NaH"));
+        assert_eq!(pairs(&[("Fe", "+8/3"), ("O", "-2")]), states("Fe3O4"));
+        assert_eq!(pairs(&[("Cl", "0")]), states("Cl2"));
+        assert_eq!(
+            pairs(&[("Cu", "+2"), ("O", "-2"), ("S", "+6")]),
+            states("CuSO4")
+        );
+        assert_eq!(
+            pairs(&[("Fe", "+3"), ("O", "-2"), ("S", "+6")]),
+            states("Fe2(SO4)3")
+        );
+        assert_eq!(None, oxidation_states(&parse_chemical("FeS").unwrap()));
+    }
+
+    #[test]
+    fn assign_ion_states() {
+        assert_eq!(pairs(&[("Mn", "+7"), ("O", "-2")]), states("MnO4-"));
+        assert_eq!(pairs(&[("Fe", "+2")]), states("Fe2+"));
+        assert_eq!(pairs(&[("Fe", "+3")]), states("Fe3+"));
+        assert_eq!(pairs(&[("Cr", "+6"), ("O", "-2")]), states("Cr2O7^2-"));
+
+        let reaction = Reaction::new(
+            vec![
+                parse_chemical("Cu").unwrap(),
+                parse_chemical("Ag+").unwrap(),
+            ],
+            vec![
+                parse_chemical("Cu2+").unwrap(),
+                parse_chemical("Ag").unwrap(),
+            ],
+            vec![1, 2, 1, 2],
+        );
+        let report = oxidation_report(&reaction).unwrap();
+        assert_eq!(2, report.len());
+        assert_eq!("Ag", report[0].element);
+        assert_eq!(Some(-2), report[0].electrons().as_integer());
+        assert_eq!("Cu", report[1].element);
+        assert_eq!(Some(2), report[1].electrons().as_integer());
+    }
+
+    #[test]
+    fn report_changes() {
+        let species = |formulas: &[&str]| -> Vec<Chemical> {
+            formulas
+                .iter()
+                .map(|formula| parse_chemical(formula).unwrap())
+                .collect()
+        };
+        let reaction = Reaction::new(
+            species(&["Fe2O3", "CO"]),
+            species(&["Fe", "CO2"]),
+            vec![1, 3, 2, 3],
+        );
+        let report = oxidation_report(&reaction).unwrap();
+        assert_eq!(2, report.len());
+        assert_eq!("C", report[0].element);
+        assert_eq!(3, report[0].atoms);
+        assert!(report[0].is_oxidation());
+        assert_eq!(Some(6), report[0].electrons().as_integer());
+        assert_eq!("Fe", report[1].element);
+        assert_eq!(Some(-6), report[1].electrons().as_integer());
+
+        let neutralization = Reaction::new(
+            species(&["HCl", "NaOH"]),
+            species(&["NaCl", "H2O"]),
+            vec![1, 1, 1, 1],
+        );
+        assert_eq!(Some(vec![]), oxidation_report(&neutralization));
+        assert_eq!(Some(RedoxKind::Redox), redox_kind(&report));
+        assert_eq!(None, redox_kind(&[]));
+    }
+
+    #[test]
+    fn same_element_both_ways() {
+        let species = |formulas: &[&str]| -> Vec<Chemical> {
+            formulas
+                .iter()
+                .map(|formula| parse_chemical(formula).unwrap())
+                .collect()
+        };
+        let chlorine = Reaction::new(
+            species(&["Cl2", "NaOH"]),
+            species(&["NaCl", "NaClO3", "H2O"]),
+            vec![3, 6, 5, 1, 3],
+        );
+        let report = oxidation_report(&chlorine).unwrap();
+        // Chlorine at 0 splits into -1 and +5
+        assert_eq!(2, report.len());
+        assert_eq!(Some(5), report[0].electrons().as_integer().map(i64::abs));
+        assert_eq!(Some(5), report[1].electrons().as_integer().map(i64::abs));
+        assert_eq!(Some(RedoxKind::Disproportionation), redox_kind(&report));
+
+        let sulfur = Reaction::new(
+            species(&["H2S", "SO2"]),
+            species(&["S", "H2O"]),
+            vec![2, 1, 3, 2],
+        );
+        let report = oxidation_report(&sulfur).unwrap();
+        assert_eq!(Some(RedoxKind::Comproportionation), redox_kind(&report));
+    }
+}
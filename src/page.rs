@@ -0,0 +1,107 @@
+//! Pages of the app and the URL fragments that lead to them, so that each
+//! can be bookmarked and reached with the back button.
+
+use crate::query;
+use alloc::format;
+use alloc::string::String;
+
+/// Prefix of the id of each history entry, so that `#out-4` links to `Out[4]`.
+pub const ENTRY_ANCHOR: &str = "out-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Calculator,
+    History,
+    Tools,
+    Settings,
+    About,
+}
+
+impl Page {
+    pub const ALL: [Page; 5] = [
+        Page::Calculator,
+        Page::History,
+        Page::Tools,
+        Page::Settings,
+        Page::About,
+    ];
+
+    /// Path after `#/`, empty for the calculator so that plain links open it.
+    pub fn path(self) -> &'static str {
+        match self {
+            Page::Calculator => "",
+            Page::History => "history",
+            Page::Tools => "tools",
+            Page::Settings => "settings",
+            Page::About => "about",
+        }
+    }
+
+    pub fn message_key(self) -> &'static str {
+        match self {
+            Page::Calculator => "page.calculator",
+            Page::History => "page.history",
+            Page::Tools => "page.tools",
+            Page::Settings => "page.settings",
+            Page::About => "page.about",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Page(Page),
+    /// A history entry, shown on the history page.
+    Entry(usize),
+}
+
+impl Route {
+    /// Reads a URL fragment with or without the leading `#`. Unknown paths
+    /// lead to the calculator.
+    pub fn parse(fragment: &str) -> Self {
+        if let Some(index) = query::anchor_index(fragment, ENTRY_ANCHOR) {
+            return Route::Entry(index);
+        }
+        let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+        let path = fragment.strip_prefix('/').unwrap_or(fragment);
+        let page = Page::ALL
+            .iter()
+            .copied()
+            .find(|page| page.path() == path)
+            .unwrap_or(Page::Calculator);
+        Route::Page(page)
+    }
+
+    pub fn fragment(self) -> String {
+        match self {
+            Route::Page(page) => format!("#/{}", page.path()),
+            Route::Entry(index) => format!("#{}{}", ENTRY_ANCHOR, index),
+        }
+    }
+
+    pub fn page(self) -> Page {
+        match self {
+            Route::Page(page) => page,
+            Route::Entry(_) => Page::History,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_fragments() {
+        assert_eq!(Route::Page(Page::Calculator), Route::parse(""));
+        assert_eq!(Route::Page(Page::Calculator), Route::parse("#/"));
+        assert_eq!(Route::Page(Page::Tools), Route::parse("#/tools"));
+        assert_eq!(Route::Page(Page::Calculator), Route::parse("#/nowhere"));
+        assert_eq!(Route::Entry(4), Route::parse("#out-4"));
+        assert_eq!(Page::History, Route::Entry(4).page());
+        for &page in Page::ALL.iter() {
+            let route = Route::Page(page);
+            assert_eq!(route, Route::parse(&route.fragment()));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Fuzzy search over the commands of the command palette.
+
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Scores how well `query` matches `text` when its characters appear in it in
+/// order, ignoring case and spaces, or gives `None` when they do not. Matches
+/// in a row and at the start of a word score more.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text = text.to_lowercase();
+    let mut rest = text.chars();
+    let mut previous: Option<char> = None;
+    let mut in_row = false;
+    let mut score = 0;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let c = rest.next()?;
+            let word_start = !matches!(previous, Some(previous) if previous.is_alphanumeric());
+            previous = Some(c);
+            if c == wanted {
+                score += 1;
+                if in_row {
+                    score += 2;
+                }
+                if word_start {
+                    score += 3;
+                }
+                in_row = true;
+                break;
+            }
+            in_row = false;
+        }
+    }
+    Some(score)
+}
+
+/// Keeps the items whose label matches `query`, the best match first and
+/// otherwise in their given order.
+pub fn rank<S: AsRef<str>, T>(query: &str, items: Vec<(S, T)>) -> Vec<(S, T)> {
+    let mut scored: Vec<(u32, (S, T))> = items
+        .into_iter()
+        .filter_map(|item| Some((fuzzy_score(query, item.0.as_ref())?, item)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| Reverse(score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn fuzzy_ranking() {
+        assert_eq!(Some(0), fuzzy_score("", "Balance"));
+        assert_eq!(None, fuzzy_score("bx", "Balance"));
+        assert!(fuzzy_score("bal", "Balance") > fuzzy_score("bln", "Balance"));
+        assert!(fuzzy_score("SD", "Show drafts") > fuzzy_score("sd", "Swap sides"));
+        assert_eq!(Some(7), fuzzy_score("통계", "통계 보기"));
+
+        let items = vec![("Show statistics", 0), ("Swap sides", 1), ("Balance", 2)];
+        let ranked: Vec<i32> = rank("sw", items.clone())
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+        assert_eq!(vec![1, 0], ranked);
+        assert_eq!(items, rank("", items.clone()));
+    }
+}
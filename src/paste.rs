@@ -0,0 +1,84 @@
+//! Cleanup of equations pasted from textbooks and web pages.
+
+use crate::chemical::plain_subscript;
+use alloc::string::String;
+
+/// Arrows written for a reaction, all meaning the equals sign here.
+pub(crate) const ARROWS: [&str; 10] = ["<=>", "<->", "->", "=>", "→", "⟶", "⇌", "⇄", "⟷", "↔"];
+
+/// Strips problem numbering, gas and precipitate arrows and the trailing
+/// period, and turns reaction arrows and Unicode digits into plain text.
+/// Phase labels are kept, as the parser reads them.
+pub fn clean_pasted(input: &str) -> String {
+    let mut text = strip_numbering(input.trim()).trim_end();
+    text = text.strip_suffix('.').unwrap_or(text).trim_end();
+    normalize_symbols(text)
+}
+
+/// Turns reaction arrows, Unicode digits and fullwidth signs into plain text
+/// and drops gas and precipitate arrows.
+pub fn normalize_symbols(input: &str) -> String {
+    let mut normalized = String::from(input);
+    for arrow in ARROWS.iter() {
+        normalized = normalized.replace(arrow, "=");
+    }
+    normalized
+        .chars()
+        .filter_map(|c| match c {
+            '↑' | '↓' => None,
+            '₀'..='₉' => Some(plain_subscript(c)),
+            '＋' => Some('+'),
+            '＝' => Some('='),
+            _ => Some(c),
+        })
+        .collect()
+}
+
+/// Removes a leading `(3)`, `3.`, `3)` or circled number.
+fn strip_numbering(input: &str) -> &str {
+    let rest = if let Some(inner) = input.strip_prefix('(') {
+        let digits = inner.trim_start_matches(|c: char| c.is_ascii_digit());
+        match digits.strip_prefix(')') {
+            // `(OH)` and the like start formulas, so only digits count as numbering
+            Some(rest) if digits.len() < inner.len() => rest,
+            _ => input,
+        }
+    } else if input.starts_with(|c| ('①'..='⑳').contains(&c)) {
+        &input['①'.len_utf8()..]
+    } else {
+        let digits = input.trim_start_matches(|c: char| c.is_ascii_digit());
+        if digits.len() == input.len() {
+            input
+        } else {
+            digits
+                .strip_prefix('.')
+                .or_else(|| digits.strip_prefix(')'))
+                // A formula may start with a digit only as a coefficient, which is never followed by `.` or `)`
+                .filter(|rest| rest.starts_with(' '))
+                .unwrap_or(input)
+        }
+    };
+    rest.trim_start()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clean_textbook_equations() {
+        assert_eq!(
+            "2H2(g) + O2(g) = 2H2O(l)",
+            clean_pasted("(3) 2H₂(g) + O₂(g) → 2H₂O(l).")
+        );
+        assert_eq!(
+            "CaCO3(s) = CaO(s) + CO2",
+            clean_pasted("12. CaCO3(s) ⇌ CaO(s) + CO2↑")
+        );
+        assert_eq!(
+            "Ca(OH)2 + CO2 = CaCO3 + H2O",
+            clean_pasted("① Ca(OH)2 + CO2 -> CaCO3↓ + H2O")
+        );
+        assert_eq!("2H2 + O2 = 2H2O", clean_pasted("2H2 + O2 = 2H2O"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Balancing by hand: every coefficient starts at 1 and is stepped up or
+//! down, with the element counts checked after each step.
+
+use crate::assignment::{self, Grade};
+use crate::equation::Equation;
+use crate::hint::{analyze, Difficulty};
+use crate::reaction::{calculate_coefficients, Reaction, ReactionError};
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone)]
+pub struct Practice {
+    /// The equation with the coefficients stepped to so far.
+    pub reaction: Reaction,
+    /// The smallest balance, only shown when asked for.
+    answer: Vec<i64>,
+    pub revealed: bool,
+    pub difficulty: Difficulty,
+}
+
+impl Practice {
+    /// Starts from all coefficients 1. Fails like balancing would, so that
+    /// no one practices on an equation without a unique answer.
+    pub fn new(equation: Equation) -> Result<Self, ReactionError> {
+        let answer = calculate_coefficients(&equation.reagents, &equation.products)?;
+        let difficulty = analyze(&equation.reagents, &equation.products)
+            .map_or(Difficulty::Hard, |analysis| analysis.difficulty);
+        let ones = vec![1; answer.len()];
+        Ok(Self {
+            reaction: equation.into_reaction(ones),
+            answer,
+            revealed: false,
+            difficulty,
+        })
+    }
+
+    /// Adds `delta` to the coefficient of `species`, which stays at least 1.
+    pub fn step(&mut self, species: usize, delta: i64) {
+        if let Some(coefficient) = self.reaction.coefficients.get_mut(species) {
+            *coefficient = (*coefficient + delta).max(1);
+        }
+    }
+
+    pub fn grade(&self) -> Grade {
+        assignment::grade(&self.reaction)
+    }
+
+    /// Sets the coefficients to the smallest balance.
+    pub fn reveal(&mut self) {
+        self.reaction.coefficients = self.answer.clone();
+        self.revealed = true;
+    }
+}
+
+/// Seconds a challenge may allow for each equation.
+pub const TIME_LIMITS: [u32; 3] = [30, 60, 120];
+
+/// Equations solved in a row before each further step of the score multiplier.
+pub const STREAK_STEP: u32 = 3;
+
+/// A run of practices against the clock, scored with a bonus for solving
+/// several in a row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Challenge {
+    /// Seconds allowed for each equation.
+    pub time_limit: u32,
+    pub streak: u32,
+    pub score: u32,
+}
+
+impl Challenge {
+    pub fn new(time_limit: u32) -> Self {
+        Self {
+            time_limit,
+            streak: 0,
+            score: 0,
+        }
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        1 + self.streak / STREAK_STEP
+    }
+
+    pub fn is_expired(&self, seconds: f64) -> bool {
+        seconds >= self.time_limit as f64
+    }
+
+    /// Scores an equation solved in `seconds`: 10 points and one for each
+    /// second left, times the multiplier of the streak it extends.
+    pub fn record_solved(&mut self, seconds: f64) -> u32 {
+        self.streak += 1;
+        let left = (self.time_limit as f64 - seconds).max(0.0) as u32;
+        let points = (10 + left) * self.multiplier();
+        self.score += points;
+        points
+    }
+
+    /// Ends the streak on an equation given up on or out of time.
+    pub fn record_missed(&mut self) {
+        self.streak = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+
+    #[test]
+    fn step_to_balance() {
+        let mut practice = Practice::new(parse_equation("H2 + O2 = H2O").unwrap()).unwrap();
+        assert_eq!(vec![1, 1, 1], practice.reaction.coefficients);
+        assert_eq!(Grade::Unbalanced, practice.grade());
+        practice.step(1, -1);
+        assert_eq!(1, practice.reaction.coefficients[1]);
+        practice.step(0, 1);
+        practice.step(2, 1);
+        assert_eq!(Grade::Correct, practice.grade());
+        practice.step(0, 2);
+        practice.step(1, 1);
+        practice.step(2, 2);
+        assert_eq!(Grade::NotLowest, practice.grade());
+        assert!(!practice.revealed);
+        practice.reveal();
+        assert_eq!(vec![2, 1, 2], practice.reaction.coefficients);
+
+        assert_eq!(Difficulty::Easy, practice.difficulty);
+        assert!(matches!(
+            Practice::new(parse_equation("H2 + O2 = H2O + H2O2").unwrap()),
+            Err(ReactionError::InfiniteSolution)
+        ));
+    }
+
+    #[test]
+    fn challenge_streaks() {
+        let mut challenge = Challenge::new(30);
+        assert_eq!(40, challenge.record_solved(0.0));
+        assert_eq!(29, challenge.record_solved(10.5));
+        assert_eq!(2 * 10, challenge.record_solved(45.0));
+        assert_eq!(89, challenge.score);
+        assert!(challenge.is_expired(30.0));
+        challenge.record_missed();
+        assert_eq!(1, challenge.multiplier());
+    }
+}
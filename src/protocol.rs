@@ -0,0 +1,76 @@
+//! Messages exchanged with a host page through `postMessage`.
+//!
+//! Every message is a JSON string with a `type` field. A host page sends
+//! `{"type":"balance","equation":"H2 + O2 = H2O","id":"1"}` to balance an
+//! equation, and the calculator answers `{"type":"result","result":{...},"id":"1"}`
+//! with a `crate::schema::BalanceResult`, or `{"type":"error","message":"...","id":"1"}`.
+//! The `id` is optional and echoed back as given.
+
+use crate::schema::BalanceResult;
+use serde::{Deserialize, Serialize};
+
+/// Sent from the host page to the calculator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HostRequest {
+    Balance {
+        equation: String,
+        #[serde(default)]
+        id: Option<String>,
+    },
+}
+
+impl HostRequest {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Sent from the calculator to the host page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HostMessage {
+    Result {
+        result: BalanceResult,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+}
+
+impl HostMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("HostMessage is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn host_message_json() {
+        let message = HostMessage::Error {
+            message: "invalid".into(),
+            id: None,
+        };
+        assert_eq!(r#"{"type":"error","message":"invalid"}"#, message.to_json());
+    }
+
+    #[test]
+    fn host_request_json() {
+        let request = HostRequest::from_json(r#"{"type":"balance","equation":"H2 + O2 = H2O"}"#);
+        assert_eq!(
+            HostRequest::Balance {
+                equation: "H2 + O2 = H2O".into(),
+                id: None,
+            },
+            request.unwrap()
+        );
+        assert!(HostRequest::from_json(r#"{"type":"result"}"#).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Values with a unit, so that calculators accept what was typed in any unit
+//! of the right kind and cannot mix up, say, millilitres and litres.
+
+use crate::i18n::I18n;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::ops::{Add, Div, Mul, Sub};
+
+/// What a unit measures. Only units of the same dimension convert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Dimension {
+    Mass,
+    Amount,
+    Volume,
+    Pressure,
+    Temperature,
+    Concentration,
+    Energy,
+    MolarMass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub dimension: Dimension,
+    /// Size of the unit in the base unit of its dimension.
+    scale: f64,
+    /// Base value of the zero of the unit, for °C.
+    offset: f64,
+}
+
+macro_rules! units {
+    ($(($symbol:expr, $dimension:ident, $scale:expr $(, $offset:expr)?)),* $(,)?) => {
+        [$(Unit {
+            symbol: $symbol,
+            dimension: Dimension::$dimension,
+            scale: $scale,
+            offset: 0.0 $(+ $offset)?,
+        }),*]
+    };
+}
+
+/// The first unit of each dimension is its base unit.
+pub const UNITS: [Unit; 30] = units![
+    ("g", Mass, 1.0),
+    ("mg", Mass, 1e-3),
+    ("μg", Mass, 1e-6),
+    ("kg", Mass, 1e3),
+    ("mol", Amount, 1.0),
+    ("mmol", Amount, 1e-3),
+    ("kmol", Amount, 1e3),
+    ("L", Volume, 1.0),
+    ("mL", Volume, 1e-3),
+    ("μL", Volume, 1e-6),
+    ("cm3", Volume, 1e-3),
+    ("dm3", Volume, 1.0),
+    ("m3", Volume, 1e3),
+    ("kPa", Pressure, 1.0),
+    ("Pa", Pressure, 1e-3),
+    ("atm", Pressure, 101.325),
+    ("bar", Pressure, 100.0),
+    ("mmHg", Pressure, 0.133_322_387),
+    ("torr", Pressure, 101.325 / 760.0),
+    ("K", Temperature, 1.0),
+    ("°C", Temperature, 1.0, 273.15),
+    ("M", Concentration, 1.0),
+    ("mM", Concentration, 1e-3),
+    ("mol/L", Concentration, 1.0),
+    ("J", Energy, 1.0),
+    ("kJ", Energy, 1e3),
+    ("cal", Energy, 4.184),
+    ("kcal", Energy, 4184.0),
+    ("g/mol", MolarMass, 1.0),
+    ("kg/mol", MolarMass, 1e3),
+];
+
+/// Other ways of typing a unit of `UNITS`.
+const UNIT_ALIASES: [(&str, &str); 9] = [
+    ("l", "L"),
+    ("ml", "mL"),
+    ("ug", "μg"),
+    ("uL", "μL"),
+    ("cm³", "cm3"),
+    ("dm³", "dm3"),
+    ("m³", "m3"),
+    ("℃", "°C"),
+    ("Torr", "torr"),
+];
+
+pub fn find_unit(symbol: &str) -> Option<&'static Unit> {
+    let symbol = UNIT_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == symbol)
+        .map_or(symbol, |(_, symbol)| symbol);
+    UNITS.iter().find(|unit| unit.symbol == symbol)
+}
+
+fn base_unit(dimension: Dimension) -> &'static Unit {
+    UNITS
+        .iter()
+        .find(|unit| unit.dimension == dimension)
+        .expect("every dimension has a unit")
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum QuantityError {
+    #[error("`{input}` is not a number with a unit")]
+    Invalid { input: String },
+    #[error("`{unit}` is not a known unit")]
+    UnknownUnit { unit: String },
+    #[error("`{from}` cannot be converted to `{to}`")]
+    Incompatible {
+        from: &'static str,
+        to: &'static str,
+    },
+}
+
+impl QuantityError {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            QuantityError::Invalid { .. } => "quantity.invalid",
+            QuantityError::UnknownUnit { .. } => "quantity.unknown_unit",
+            QuantityError::Incompatible { .. } => "quantity.incompatible",
+        }
+    }
+
+    /// The stable code of the error, listed in `error_code::ERROR_CODES`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            QuantityError::Invalid { .. } => "E020",
+            QuantityError::UnknownUnit { .. } => "E021",
+            QuantityError::Incompatible { .. } => "E022",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            QuantityError::Invalid { input: text } | QuantityError::UnknownUnit { unit: text } => {
+                i18n.format(self.message_key(), &[text])
+            }
+            QuantityError::Incompatible { from, to } => {
+                i18n.format(self.message_key(), &[from, to])
+            }
+        }
+    }
+}
+
+/// A value in a unit, such as `250 mL`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: &'static Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, symbol: &str) -> Result<Self, QuantityError> {
+        let unit = find_unit(symbol).ok_or_else(|| QuantityError::UnknownUnit {
+            unit: symbol.to_string(),
+        })?;
+        Ok(Self { value, unit })
+    }
+
+    /// Reads a number followed by a unit, as in `250 mL` or `25°C`.
+    pub fn parse(input: &str) -> Result<Self, QuantityError> {
+        Self::parse_or(input, None)
+    }
+
+    /// Reads a number with an optional unit, taking `default` when there is
+    /// none, as for a bare number in a field labelled with its unit.
+    pub fn parse_or(
+        input: &str,
+        default: impl Into<Option<&'static str>>,
+    ) -> Result<Self, QuantityError> {
+        let input = input.trim();
+        let invalid = || QuantityError::Invalid {
+            input: input.to_string(),
+        };
+        let number_end = input
+            .char_indices()
+            .find(|&(index, c)| {
+                !(c.is_ascii_digit()
+                    || c == '.'
+                    || (matches!(c, '-' | '+') && (index == 0 || input[..index].ends_with('e')))
+                    || (c == 'e'
+                        && index > 0
+                        && input[index + 1..]
+                            .starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')))
+            })
+            .map_or(input.len(), |(index, _)| index);
+        let value: f64 = input[..number_end].parse().map_err(|_| invalid())?;
+        match (input[number_end..].trim(), default.into()) {
+            ("", Some(default)) => Self::new(value, default),
+            ("", None) => Err(invalid()),
+            (symbol, _) => Self::new(value, symbol),
+        }
+    }
+
+    fn base_value(&self) -> f64 {
+        self.value * self.unit.scale + self.unit.offset
+    }
+
+    fn from_base(value: f64, unit: &'static Unit) -> Self {
+        Self {
+            value: (value - unit.offset) / unit.scale,
+            unit,
+        }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension
+    }
+
+    /// The same quantity in the unit `symbol`, which must measure the same thing.
+    pub fn convert(&self, symbol: &str) -> Result<Self, QuantityError> {
+        let unit = Self::new(0.0, symbol)?.unit;
+        if unit.dimension != self.unit.dimension {
+            return Err(QuantityError::Incompatible {
+                from: self.unit.symbol,
+                to: unit.symbol,
+            });
+        }
+        Ok(Self::from_base(self.base_value(), unit))
+    }
+
+    /// The value in the unit `symbol`.
+    pub fn value_in(&self, symbol: &str) -> Result<f64, QuantityError> {
+        self.convert(symbol).map(|quantity| quantity.value)
+    }
+
+    /// Multiplies two quantities whose product is one of the known kinds,
+    /// such as a concentration by a volume, giving the base unit.
+    pub fn times(&self, other: &Quantity) -> Result<Self, QuantityError> {
+        use Dimension::*;
+        let dimension = match (self.dimension(), other.dimension()) {
+            (Concentration, Volume) | (Volume, Concentration) => Amount,
+            (Amount, MolarMass) | (MolarMass, Amount) => Mass,
+            _ => return Err(self.incompatible(other)),
+        };
+        let value = self.value * self.unit.scale * other.value * other.unit.scale;
+        Ok(Self::from_base(value, base_unit(dimension)))
+    }
+
+    /// Divides two quantities whose ratio is one of the known kinds, such as
+    /// an amount by a volume, giving the base unit.
+    pub fn per(&self, other: &Quantity) -> Result<Self, QuantityError> {
+        use Dimension::*;
+        let dimension = match (self.dimension(), other.dimension()) {
+            (Amount, Volume) => Concentration,
+            (Amount, Concentration) => Volume,
+            (Mass, MolarMass) => Amount,
+            (Mass, Amount) => MolarMass,
+            _ => return Err(self.incompatible(other)),
+        };
+        let value = self.value * self.unit.scale / (other.value * other.unit.scale);
+        Ok(Self::from_base(value, base_unit(dimension)))
+    }
+
+    fn incompatible(&self, other: &Quantity) -> QuantityError {
+        QuantityError::Incompatible {
+            from: self.unit.symbol,
+            to: other.unit.symbol,
+        }
+    }
+}
+
+/// Adds in the unit of the left side. Differences of temperatures are not
+/// told apart from temperatures, so `°C` adds like any other unit.
+impl Add for Quantity {
+    type Output = Result<Quantity, QuantityError>;
+
+    fn add(self, other: Quantity) -> Self::Output {
+        let other = other.convert(self.unit.symbol)?;
+        Ok(Quantity {
+            value: self.value + other.value,
+            unit: self.unit,
+        })
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Result<Quantity, QuantityError>;
+
+    fn sub(self, other: Quantity) -> Self::Output {
+        let other = other.convert(self.unit.symbol)?;
+        Ok(Quantity {
+            value: self.value - other.value,
+            unit: self.unit,
+        })
+    }
+}
+
+impl Mul<f64> for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, factor: f64) -> Quantity {
+        Quantity {
+            value: self.value * factor,
+            unit: self.unit,
+        }
+    }
+}
+
+impl Div<f64> for Quantity {
+    type Output = Quantity;
+
+    fn div(self, divisor: f64) -> Quantity {
+        Quantity {
+            value: self.value / divisor,
+            unit: self.unit,
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.unit.symbol)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn close(expected: f64, actual: f64) -> bool {
+        (expected - actual).abs() < 1e-9 * expected.abs().max(1.0)
+    }
+
+    #[test]
+    fn parse_and_convert() {
+        let volume = Quantity::parse("250 mL").unwrap();
+        assert_eq!(Dimension::Volume, volume.dimension());
+        assert!(close(0.25, volume.value_in("L").unwrap()));
+        assert!(close(2.0, Quantity::parse("2atm").unwrap().value));
+        assert!(close(
+            298.15,
+            Quantity::parse("25 ℃").unwrap().value_in("K").unwrap()
+        ));
+        assert!(close(1.5e-3, Quantity::parse("1.5e-3 mol").unwrap().value));
+        assert!(close(
+            0.1,
+            Quantity::parse_or("0.1", "M")
+                .unwrap()
+                .value_in("mol/L")
+                .unwrap()
+        ));
+        assert_eq!(
+            Err(QuantityError::Incompatible {
+                from: "mL",
+                to: "g"
+            }),
+            volume.convert("g")
+        );
+        assert_eq!(
+            Err(QuantityError::UnknownUnit {
+                unit: "parsec".into()
+            }),
+            Quantity::parse("3 parsec")
+        );
+        assert!(matches!(
+            Quantity::parse("0.5"),
+            Err(QuantityError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn arithmetic_keeps_units() {
+        let sum = (Quantity::parse("1 L").unwrap() + Quantity::parse("250 mL").unwrap()).unwrap();
+        assert_eq!("L", sum.unit.symbol);
+        assert!(close(1.25, sum.value));
+        assert!((Quantity::parse("1 L").unwrap() - Quantity::parse("1 g").unwrap()).is_err());
+
+        let amount = Quantity::parse("0.5 M")
+            .unwrap()
+            .times(&Quantity::parse("200 mL").unwrap())
+            .unwrap();
+        assert_eq!(Dimension::Amount, amount.dimension());
+        assert!(close(0.1, amount.value));
+        let mass = amount
+            .times(&Quantity::new(58.44, "g/mol").unwrap())
+            .unwrap();
+        assert!(close(5.844, mass.value_in("g").unwrap()));
+        let concentration = amount.per(&Quantity::parse("2 L").unwrap()).unwrap();
+        assert!(close(50.0, concentration.value_in("mM").unwrap()));
+        assert!(amount.per(&mass).is_err());
+        assert!(close(0.2, (amount * 2.0).value));
+    }
+}
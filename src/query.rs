@@ -0,0 +1,99 @@
+//! Query string encoding for links into the app.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Decoded value of the first `key=value` pair with the given key, in a query string with or without the leading `?`.
+pub fn param(query: &str, key: &str) -> Option<String> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let value = query.split('&').find_map(|pair| {
+        let mut pair = pair.splitn(2, '=');
+        if pair.next()? == key {
+            Some(pair.next().unwrap_or(""))
+        } else {
+            None
+        }
+    })?;
+    percent_decode(value)
+}
+
+/// Number after `prefix` in a URL fragment with or without the leading `#`, as `4` in `#out-4`.
+pub fn anchor_index(fragment: &str, prefix: &str) -> Option<usize> {
+    let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+    fragment.strip_prefix(prefix)?.parse().ok()
+}
+
+/// The `scheme://host[:port]` origin of an absolute URL, as `https://example.com` of `https://example.com/page?q`.
+pub fn origin(url: &str) -> Option<&str> {
+    let scheme = url.find("://")?;
+    let authority = &url[scheme + 3..];
+    let end = authority.find(['/', '?', '#']).unwrap_or(authority.len());
+    if end == 0 {
+        return None;
+    }
+    Some(&url[..scheme + 3 + end])
+}
+
+pub fn percent_encode(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                output.push(byte as char)
+            }
+            _ => {
+                let _ = write!(output, "%{:02X}", byte);
+            }
+        }
+    }
+    output
+}
+
+/// Returns `None` on a malformed escape or a non-UTF-8 result.
+pub fn percent_decode(input: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut iter = input.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'%' => {
+                let high = (iter.next()? as char).to_digit(16)?;
+                let low = (iter.next()? as char).to_digit(16)?;
+                bytes.push((high * 16 + low) as u8);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_param() {
+        let query = format!("?embed&equation={}", percent_encode("H2 + O2 = H2O"));
+        assert_eq!(Some("".into()), param(&query, "embed"));
+        assert_eq!(Some("H2 + O2 = H2O".into()), param(&query, "equation"));
+        assert_eq!(None, param(&query, "assignment"));
+        assert_eq!(Some("a b".into()), param("q=a+b", "q"));
+
+        assert_eq!(Some(4), anchor_index("#out-4", "out-"));
+        assert_eq!(Some(12), anchor_index("out-12", "out-"));
+        assert_eq!(None, anchor_index("#out-", "out-"));
+        assert_eq!(None, anchor_index("#stats", "out-"));
+
+        assert_eq!(
+            Some("https://example.com"),
+            origin("https://example.com/page?q=1")
+        );
+        assert_eq!(
+            Some("http://localhost:8080"),
+            origin("http://localhost:8080")
+        );
+        assert_eq!(None, origin("https:///page"));
+        assert_eq!(None, origin("example.com/page"));
+    }
+}
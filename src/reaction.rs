@@ -1,22 +1,390 @@
 use crate::chemical::*;
+use crate::condition::Condition;
+use crate::format::CoefficientStyle;
+use crate::i18n::I18n;
+use crate::solver::{self, Diagnostics, SolverStrategy};
 
-pub type Result<T> = std::result::Result<T, ReactionError>;
+pub type Result<T> = core::result::Result<T, ReactionError>;
 
-use std::collections::HashSet;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum ReactionError {
+    #[error("the reagents and products are made of different elements")]
     UnbalancedElements,
+    #[error("the coefficients are not determined uniquely")]
     InfiniteSolution,
+    /// The solution disagreed with the modular cross-check.
+    #[error("the solution failed verification, the coefficients may be too large")]
+    VerificationFailed,
+    /// No positive integer solution has the fixed coefficients.
+    #[error("no solution has the fixed coefficients")]
+    Inconsistent,
+}
+
+impl ReactionError {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            ReactionError::UnbalancedElements => "error.unbalanced_elements",
+            ReactionError::InfiniteSolution => "error.infinite_solution",
+            ReactionError::VerificationFailed => "error.verification_failed",
+            ReactionError::Inconsistent => "error.inconsistent",
+        }
+    }
+
+    /// The stable code of the error, listed in `error_code::ERROR_CODES`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReactionError::UnbalancedElements => "E010",
+            ReactionError::InfiniteSolution => "E011",
+            ReactionError::VerificationFailed => "E012",
+            ReactionError::Inconsistent => "E013",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        i18n.t(self.message_key()).into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Side {
+    Reagent,
+    Product,
+}
+
+/// A balanced reaction whose `coefficients` list reagents first, then products.
+#[derive(Debug, Clone)]
+pub struct Reaction {
+    pub reagents: Vec<Chemical>,
+    pub products: Vec<Chemical>,
+    pub coefficients: Vec<i64>,
+    /// Catalyst written over the arrow, which takes no part in balancing.
+    pub catalyst: Option<String>,
+    pub conditions: Vec<Condition>,
+}
+
+impl Reaction {
+    pub fn new(reagents: Vec<Chemical>, products: Vec<Chemical>, coefficients: Vec<i64>) -> Self {
+        assert_eq!(reagents.len() + products.len(), coefficients.len());
+        Self {
+            reagents,
+            products,
+            coefficients,
+            catalyst: None,
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Iterates every species with its coefficient, reagents first.
+    pub fn species(&self) -> impl Iterator<Item = (&Chemical, i64)> {
+        self.reagents
+            .iter()
+            .chain(self.products.iter())
+            .zip(self.coefficients.iter().copied())
+    }
+
+    pub fn species_at(&self, index: usize) -> Option<&Chemical> {
+        if index < self.reagents.len() {
+            self.reagents.get(index)
+        } else {
+            self.products.get(index - self.reagents.len())
+        }
+    }
+
+    pub fn reagent_coefficients(&self) -> &[i64] {
+        &self.coefficients[..self.reagents.len()]
+    }
+
+    pub fn product_coefficients(&self) -> &[i64] {
+        &self.coefficients[self.reagents.len()..]
+    }
+
+    /// Atom count of each element on the reagent and product side.
+    pub fn element_counts(&self) -> BTreeMap<String, (i64, i64)> {
+        self.count_elements(&self.coefficients)
+    }
+
+    /// Atom counts as they were before balancing, with every coefficient 1.
+    pub fn unbalanced_element_counts(&self) -> BTreeMap<String, (i64, i64)> {
+        self.count_elements(&vec![1; self.coefficients.len()])
+    }
+
+    /// Whether every coefficient is positive and each element and the charge
+    /// have the same count on both sides.
+    pub fn is_balanced(&self) -> bool {
+        let (reagent_charge, product_charge) = self.charges();
+        self.coefficients.iter().all(|&coefficient| coefficient > 0)
+            && self
+                .element_counts()
+                .values()
+                .all(|(reagent, product)| reagent == product)
+            && reagent_charge == product_charge
+    }
+
+    /// Total charge on the reagent and product side.
+    pub fn charges(&self) -> (i64, i64) {
+        let mut charges = (0, 0);
+        for (index, (chemical, coefficient)) in self.species().enumerate() {
+            let charge = chemical.charge as i64 * coefficient;
+            if index < self.reagents.len() {
+                charges.0 += charge;
+            } else {
+                charges.1 += charge;
+            }
+        }
+        charges
+    }
+
+    /// Total mass of one formula unit per coefficient on each side, in grams.
+    /// Returns `None` if any species contains an unknown element.
+    pub fn side_masses(&self) -> Option<(f64, f64)> {
+        let mut masses = (0.0, 0.0);
+        for (index, (chemical, coefficient)) in self.species().enumerate() {
+            let mass = chemical.molar_mass()? * coefficient as f64;
+            if index < self.reagents.len() {
+                masses.0 += mass;
+            } else {
+                masses.1 += mass;
+            }
+        }
+        Some(masses)
+    }
+
+    /// Ratio of the amounts of species `a` and `b`, indexed reagents first,
+    /// or `None` if either index is out of range.
+    pub fn mole_ratio(&self, a: usize, b: usize) -> Option<Ratio> {
+        Some(Ratio::new(
+            *self.coefficients.get(a)?,
+            *self.coefficients.get(b)?,
+        ))
+    }
+
+    /// Identifies the reaction whatever order its species were written in and
+    /// however their formulas were grouped, ignoring catalyst and conditions.
+    pub fn canonical_key(&self) -> String {
+        let mut sides = (Vec::new(), Vec::new());
+        for (index, (chemical, coefficient)) in self.species().enumerate() {
+            let term = format!("{} {}", coefficient, chemical.canonical_formula());
+            if index < self.reagents.len() {
+                sides.0.push(term);
+            } else {
+                sides.1.push(term);
+            }
+        }
+        sides.0.sort_unstable();
+        sides.1.sort_unstable();
+        format!("{}={}", sides.0.join("+"), sides.1.join("+"))
+    }
+
+    /// Writes the reaction as it would be typed, with coefficients in `style`.
+    pub fn write_plain(&self, f: &mut impl fmt::Write, style: CoefficientStyle) -> fmt::Result {
+        for (index, (chemical, coefficient)) in self.species().enumerate() {
+            if index > 0 && index == self.reagents.len() {
+                match &self.catalyst {
+                    Some(catalyst) => write!(f, " =[{}]= ", catalyst)?,
+                    None => f.write_str(" = ")?,
+                }
+            } else if index > 0 {
+                f.write_str(" + ")?;
+            }
+            f.write_str(&style.write(coefficient))?;
+            f.write_str(&chemical.display)?;
+            if let Some(phase) = chemical.phase {
+                f.write_str(phase.symbol())?;
+            }
+        }
+        for (index, condition) in self.conditions.iter().enumerate() {
+            f.write_str(if index == 0 { "; " } else { ", " })?;
+            write!(f, "{}", condition)?;
+        }
+        Ok(())
+    }
+
+    fn count_elements(&self, coefficients: &[i64]) -> BTreeMap<String, (i64, i64)> {
+        let mut counts = BTreeMap::new();
+        let species = self.reagents.iter().chain(self.products.iter());
+        for (index, (chemical, &coefficient)) in species.zip(coefficients).enumerate() {
+            for (name, &count) in &chemical.parts {
+                let entry = counts.entry(name.clone()).or_insert((0, 0));
+                let atoms = count as i64 * coefficient;
+                if index < self.reagents.len() {
+                    entry.0 += atoms;
+                } else {
+                    entry.1 += atoms;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Ratio of two amounts in lowest terms, written like `1 : 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    pub first: i64,
+    pub second: i64,
+}
+
+impl Ratio {
+    pub fn new(first: i64, second: i64) -> Self {
+        let divisor = if first > 0 && second > 0 {
+            gcd(first, second)
+        } else {
+            1
+        };
+        Ratio {
+            first: first / divisor,
+            second: second / divisor,
+        }
+    }
+
+    /// The amount in the second place for `amount` in the first, e.g. 4 mol of
+    /// water for 2 mol of oxygen when the ratio is `1 : 2`.
+    pub fn convert(&self, amount: f64) -> f64 {
+        amount * self.second as f64 / self.first as f64
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} : {}", self.first, self.second)
+    }
+}
+
+impl fmt::Display for Reaction {
+    /// Writes the reaction as it would be typed, e.g. `2H2 + O2 = 2H2O`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_plain(f, CoefficientStyle::default())
+    }
+}
+
+/// Adds up reactions, each multiplied by its factor, cancelling species that
+/// appear on both sides. Coefficients of the sum are reduced to lowest terms.
+pub fn net_reaction(steps: &[(&Reaction, i64)]) -> Reaction {
+    // Products count positive and reagents negative, in order of first appearance
+    let mut net: Vec<(String, &Chemical, i64)> = Vec::new();
+    for &(reaction, factor) in steps {
+        for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+            let signed = if index < reaction.reagents.len() {
+                -coefficient * factor
+            } else {
+                coefficient * factor
+            };
+            let key = chemical.canonical_formula();
+            if let Some(entry) = net.iter_mut().find(|(existing, _, _)| *existing == key) {
+                entry.2 += signed;
+            } else {
+                net.push((key, chemical, signed));
+            }
+        }
+    }
+
+    let divisor = net
+        .iter()
+        .map(|&(_, _, coefficient)| coefficient.abs())
+        .filter(|&coefficient| coefficient != 0)
+        .fold(0, |divisor, coefficient| {
+            if divisor == 0 {
+                coefficient
+            } else {
+                gcd(divisor, coefficient)
+            }
+        });
+    let mut reagents = Vec::new();
+    let mut products = Vec::new();
+    let mut reagent_coefficients = Vec::new();
+    let mut product_coefficients = Vec::new();
+    for (_, chemical, coefficient) in net {
+        if coefficient < 0 {
+            reagents.push(chemical.clone());
+            reagent_coefficients.push(-coefficient / divisor);
+        } else if coefficient > 0 {
+            products.push(chemical.clone());
+            product_coefficients.push(coefficient / divisor);
+        }
+    }
+    reagent_coefficients.append(&mut product_coefficients);
+    Reaction::new(reagents, products, reagent_coefficients)
 }
 
 pub fn calculate_coefficients(reagents: &[Chemical], products: &[Chemical]) -> Result<Vec<i64>> {
+    calculate_coefficients_with(reagents, products, SolverStrategy::default())
+}
+
+pub fn calculate_coefficients_with(
+    reagents: &[Chemical],
+    products: &[Chemical],
+    strategy: SolverStrategy,
+) -> Result<Vec<i64>> {
+    let linear_system = create_linear_equation(reagents, products)?;
+    match strategy {
+        SolverStrategy::IntegerElimination => integer_gauss(linear_system),
+        SolverStrategy::RationalRref => {
+            solver::rational_rref(&linear_system.matrix, linear_system.columns)
+        }
+        SolverStrategy::Bareiss => solver::bareiss(&linear_system.matrix, linear_system.columns),
+    }
+}
+
+/// Balances with the coefficients of some species fixed, given as species
+/// index (reagents first) and value.
+pub fn calculate_coefficients_fixed(
+    reagents: &[Chemical],
+    products: &[Chemical],
+    fixed: &[(usize, i64)],
+) -> Result<Vec<i64>> {
+    let linear_system = create_linear_equation(reagents, products)?;
+    solver::solve_fixed(&linear_system.matrix, linear_system.columns, fixed)
+}
+
+/// Rank and null space dimension of the reaction matrix.
+pub fn diagnose(reagents: &[Chemical], products: &[Chemical]) -> Result<Diagnostics> {
+    let linear_system = create_linear_equation(reagents, products)?;
+    Ok(solver::diagnose(
+        &linear_system.matrix,
+        linear_system.columns,
+    ))
+}
+
+/// Independent reactions among the species as a reduced integer basis, where
+/// a negative coefficient puts the species on the other side.
+pub fn independent_reactions(
+    reagents: &[Chemical],
+    products: &[Chemical],
+) -> Result<Vec<Vec<i64>>> {
+    let linear_system = create_linear_equation(reagents, products)?;
+    solver::nullspace_basis(&linear_system.matrix, linear_system.columns)
+}
+
+/// Checks coefficients against the system re-solved modulo large primes.
+pub fn verify_coefficients(
+    reagents: &[Chemical],
+    products: &[Chemical],
+    coefficients: &[i64],
+) -> Result<()> {
     let linear_system = create_linear_equation(reagents, products)?;
-    integer_gauss(linear_system)
+    if solver::cross_check(&linear_system.matrix, linear_system.columns, coefficients) {
+        Ok(())
+    } else {
+        Err(ReactionError::VerificationFailed)
+    }
 }
 
 fn get_elements_involved(reagents: &[Chemical], products: &[Chemical]) -> Result<Vec<String>> {
-    let mut element_list = HashSet::new();
+    let mut element_list = BTreeSet::new();
     for reagent in reagents {
         for element in reagent.parts.keys() {
             element_list.insert(element.clone());
@@ -51,6 +419,15 @@ fn create_linear_equation(reagents: &[Chemical], products: &[Chemical]) -> Resul
             matrix.push(-coefficient);
         }
     }
+    // Charge is conserved like an element, once any species carries one
+    let charged = reagents
+        .iter()
+        .chain(products)
+        .any(|chemical| chemical.charge != 0);
+    if charged {
+        matrix.extend(reagents.iter().map(|reagent| reagent.charge as i64));
+        matrix.extend(products.iter().map(|product| -product.charge as i64));
+    }
 
     Ok(ReactionMatrix { matrix, columns })
 }
@@ -142,35 +519,37 @@ fn cancel_row(vec: &mut Vec<i64>, row1: usize, row2: usize, columns: usize, firs
 fn lcm(a: i64, b: i64) -> i64 {
     assert!(a > 0, "a must be bigger than 0, found: {}", a);
     assert!(b > 0, "b must be bigger than 0, found: {}", b);
-    fn gcd(mut a: i64, mut b: i64) -> i64 {
-        if a == b {
-            a
-        } else {
-            let mut shift = 0;
-            while ((a | b) & 1) == 0 {
-                shift += 1;
-                a >>= 1;
+    a * b / gcd(a, b)
+}
+
+/// Greatest common divisor of two positive integers.
+pub fn gcd(mut a: i64, mut b: i64) -> i64 {
+    if a == b {
+        a
+    } else {
+        let mut shift = 0;
+        while ((a | b) & 1) == 0 {
+            shift += 1;
+            a >>= 1;
+            b >>= 1;
+        }
+        while a & 1 == 0 {
+            a >>= 1;
+        }
+        loop {
+            while (b & 1) == 0 {
                 b >>= 1;
             }
-            while a & 1 == 0 {
-                a >>= 1;
+            if a > b {
+                core::mem::swap(&mut a, &mut b);
             }
-            loop {
-                while (b & 1) == 0 {
-                    b >>= 1;
-                }
-                if a > b {
-                    std::mem::swap(&mut a, &mut b);
-                }
-                b -= a;
-                if b == 0 {
-                    break;
-                }
+            b -= a;
+            if b == 0 {
+                break;
             }
-            a << shift
         }
+        a << shift
     }
-    a * b / gcd(a, b)
 }
 
 #[cfg(test)]
@@ -185,6 +564,44 @@ mod test {
         assert_eq!(vec![2, 2, 1], solution);
     }
 
+    #[test]
+    fn calculate_ionic() {
+        let reagents = vec![
+            parse_chemical("Cu").unwrap(),
+            parse_chemical("Ag+").unwrap(),
+        ];
+        let products = vec![
+            parse_chemical("Cu2+").unwrap(),
+            parse_chemical("Ag").unwrap(),
+        ];
+        let solution = calculate_coefficients(&reagents, &products).unwrap();
+        assert_eq!(vec![1, 2, 1, 2], solution);
+        let reaction = Reaction::new(reagents, products, solution);
+        assert!(reaction.is_balanced());
+        assert_eq!((2, 2), reaction.charges());
+    }
+
+    #[test]
+    fn mole_ratio() {
+        let reaction = Reaction::new(
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("H2O").unwrap()],
+            vec![2, 1, 2],
+        );
+        let ratio = reaction.mole_ratio(1, 2).unwrap();
+        assert_eq!(
+            Ratio {
+                first: 1,
+                second: 2
+            },
+            ratio
+        );
+        assert_eq!("1 : 2", ratio.to_string());
+        assert_eq!(Some(Ratio::new(1, 1)), reaction.mole_ratio(0, 2));
+        assert_eq!(None, reaction.mole_ratio(0, 3));
+        assert_eq!(4.0, ratio.convert(2.0));
+    }
+
     #[test]
     fn calculate_complicated() {
         let reagents = vec![
@@ -198,4 +615,53 @@ mod test {
         let solution = calculate_coefficients(&reagents, &products).unwrap();
         assert_eq!(vec![2, 1, 1, 2], solution);
     }
+
+    #[test]
+    fn element_counts() {
+        let reagents = vec![parse_chemical("H2O").unwrap()];
+        let products = vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()];
+        let reaction = Reaction::new(reagents, products, vec![2, 2, 1]);
+        assert_eq!((4, 4), reaction.element_counts()["H"]);
+        assert_eq!((1, 2), reaction.unbalanced_element_counts()["O"]);
+        let (left, right) = reaction.side_masses().unwrap();
+        assert!((left - right).abs() < 1e-9);
+        assert_eq!("2H2O = 2H2 + O2", reaction.to_string());
+        let mut reaction = reaction;
+        reaction.catalyst = Some("MnO2".into());
+        assert_eq!("2H2O =[MnO2]= 2H2 + O2", reaction.to_string());
+        reaction.conditions = vec![Condition::Heat];
+        assert_eq!("2H2O =[MnO2]= 2H2 + O2; Δ", reaction.to_string());
+
+        let reordered = Reaction::new(
+            vec![parse_chemical("OH2").unwrap()],
+            vec![parse_chemical("O2").unwrap(), parse_chemical("H2").unwrap()],
+            vec![2, 1, 2],
+        );
+        assert_eq!(reaction.canonical_key(), reordered.canonical_key());
+        let reversed = Reaction::new(
+            reaction.products.clone(),
+            reaction.reagents.clone(),
+            vec![2, 1, 2],
+        );
+        assert_ne!(reaction.canonical_key(), reversed.canonical_key());
+    }
+
+    #[test]
+    fn net_reaction_cancels_intermediates() {
+        let first = Reaction::new(
+            vec![parse_chemical("C").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("CO2").unwrap()],
+            vec![1, 1, 1],
+        );
+        let second = Reaction::new(
+            vec![
+                parse_chemical("CO2").unwrap(),
+                parse_chemical("H2O").unwrap(),
+            ],
+            vec![parse_chemical("H2CO3").unwrap()],
+            vec![1, 1, 1],
+        );
+        let net = net_reaction(&[(&first, 1), (&second, 1)]);
+        assert_eq!("C + O2 + H2O = H2CO3", net.to_string());
+    }
 }
@@ -0,0 +1,83 @@
+//! Recordings of what was typed and balanced, replayed later with the same
+//! timing as a demonstration.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// The longest pause kept between two steps on replay, in milliseconds, so
+/// that a break while recording does not stall the demonstration.
+pub const MAX_PAUSE: f64 = 3000.0;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum Action {
+    SetInput(String),
+    SetConditions(String),
+    Balance,
+    Reset,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Step {
+    /// Milliseconds since the recording started.
+    pub at: f64,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Recording {
+    pub steps: Vec<Step>,
+}
+
+impl Recording {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Appends `action` taken `at` milliseconds since the recording started.
+    pub fn push(&mut self, at: f64, action: Action) {
+        self.steps.push(Step { at, action });
+    }
+
+    /// Milliseconds to wait before the step at `index` on replay, counted
+    /// from the step before it and capped at `MAX_PAUSE`.
+    pub fn delay_before(&self, index: usize) -> Option<u32> {
+        let at = self.steps.get(index)?.at;
+        let previous = match index {
+            0 => 0.0,
+            index => self.steps[index - 1].at,
+        };
+        Some((at - previous).clamp(0.0, MAX_PAUSE) as u32)
+    }
+
+    /// Length of the replay in milliseconds.
+    pub fn duration(&self) -> f64 {
+        (0..self.steps.len())
+            .filter_map(|index| self.delay_before(index))
+            .map(f64::from)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replay_timing() {
+        let mut recording = Recording::default();
+        assert!(recording.is_empty());
+        recording.push(500.0, Action::SetInput("H2".into()));
+        recording.push(800.0, Action::SetInput("H2 + O2 = H2O".into()));
+        recording.push(60_000.0, Action::Balance);
+        assert_eq!(Some(500), recording.delay_before(0));
+        assert_eq!(Some(300), recording.delay_before(1));
+        assert_eq!(Some(MAX_PAUSE as u32), recording.delay_before(2));
+        assert_eq!(None, recording.delay_before(3));
+        assert_eq!(3800.0, recording.duration());
+    }
+}
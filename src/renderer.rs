@@ -0,0 +1,419 @@
+//! Renderers writing a balanced reaction out in one notation each, built on
+//! the parts of `format_reaction` so that every notation lays it out alike.
+//! Each follows the `OutputStyle` as far as its notation can: only HTML has
+//! bold coefficients or a choice of scripts, but all of them space coefficients.
+
+use crate::chemical::Chemical;
+use crate::format::{
+    format_fragments, format_reaction, CoefficientStyle, FormattedChemical, OutputStyle,
+};
+use crate::reaction::Reaction;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub trait Renderer {
+    /// Name of the notation, e.g. `latex`.
+    fn name(&self) -> &'static str;
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String;
+}
+
+/// Every renderer, in the order they are offered.
+pub const RENDERERS: [&dyn Renderer; 5] = [&PlainText, &Unicode, &Html, &Latex, &MathMl];
+
+/// The reaction as it would be typed, e.g. `2H2 + O2 = 2H2O`.
+pub struct PlainText;
+
+impl Renderer for PlainText {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String {
+        let mut out = String::new();
+        // Writing to a `String` never fails
+        let _ = reaction.write_plain(&mut out, style.coefficients);
+        out
+    }
+}
+
+/// Plain text with subscript digits and an arrow, e.g. `2H₂ + O₂ → 2H₂O`.
+pub struct Unicode;
+
+impl Renderer for Unicode {
+    fn name(&self) -> &'static str {
+        "unicode"
+    }
+
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String {
+        let mut out = String::new();
+        for part in format_reaction(reaction) {
+            write_unicode(&part, style.coefficients, &mut out);
+        }
+        out
+    }
+}
+
+/// A single species as the `Unicode` renderer writes it, e.g. `SO₄²⁻`, for
+/// places that take no markup.
+pub fn unicode_species(chemical: &Chemical) -> String {
+    let mut out = String::new();
+    for part in format_fragments(chemical) {
+        write_unicode(&part, CoefficientStyle::default(), &mut out);
+    }
+    out
+}
+
+fn write_unicode(part: &FormattedChemical, coefficients: CoefficientStyle, out: &mut String) {
+    match part {
+        FormattedChemical::Coefficient(_, coefficient) => {
+            out.push_str(&coefficients.write(*coefficient))
+        }
+        FormattedChemical::Text(text) => out.push_str(text),
+        FormattedChemical::Sub(digits) => out.extend(digits.chars().map(subscript)),
+        FormattedChemical::Sup(charge) => out.extend(charge.chars().map(superscript)),
+        FormattedChemical::Fragment(inner) | FormattedChemical::Species(_, inner) => {
+            for part in inner {
+                write_unicode(part, coefficients, out);
+            }
+        }
+        FormattedChemical::Arrow(over, under) => {
+            let notes: Vec<&str> = over.iter().chain(under).map(String::as_str).collect();
+            if notes.is_empty() {
+                out.push_str(" → ");
+            } else {
+                out.push_str(&format!(" →[{}] ", notes.join("; ")));
+            }
+        }
+    }
+}
+
+/// The subscript form of a digit, or `c` itself if there is none.
+pub fn subscript(c: char) -> char {
+    match c.to_digit(10) {
+        Some(digit) => core::char::from_u32('₀' as u32 + digit).unwrap_or(c),
+        None => c,
+    }
+}
+
+/// The superscript form of a digit or sign, or `c` itself if there is none.
+pub fn superscript(c: char) -> char {
+    match c {
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '0' | '4'..='9' => core::char::from_u32('⁰' as u32 + (c as u32 - '0' as u32)).unwrap_or(c),
+        '+' => '⁺',
+        '-' => '⁻',
+        _ => c,
+    }
+}
+
+/// The markup of the result in the web view.
+pub struct Html;
+
+impl Renderer for Html {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String {
+        let mut out = String::new();
+        for part in format_reaction(reaction) {
+            part.write_html(style, &mut out);
+        }
+        out
+    }
+}
+
+/// An mhchem `\ce` command, which subscripts the digits of formulas itself.
+pub struct Latex;
+
+impl Renderer for Latex {
+    fn name(&self) -> &'static str {
+        "latex"
+    }
+
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String {
+        let mut out = String::from("\\ce{");
+        for part in format_reaction(reaction) {
+            write_latex(&part, style.coefficients, &mut out);
+        }
+        out.push('}');
+        out
+    }
+}
+
+fn write_latex(part: &FormattedChemical, coefficients: CoefficientStyle, out: &mut String) {
+    match part {
+        FormattedChemical::Arrow(over, under) => {
+            out.push_str(" ->");
+            if over.is_some() || under.is_some() {
+                out.push_str(&format!("[{}]", over.as_deref().unwrap_or_default()));
+            }
+            if let Some(under) = under {
+                out.push_str(&format!("[{}]", under));
+            }
+            out.push(' ');
+        }
+        FormattedChemical::Fragment(inner) | FormattedChemical::Species(_, inner) => {
+            for part in inner {
+                write_latex(part, coefficients, out);
+            }
+        }
+        FormattedChemical::Sup(charge) => out.push_str(&format!("^{{{}}}", charge)),
+        // mhchem writes the dot of a hydrate as `*`
+        FormattedChemical::Text(text) => out.push_str(&text.replace('·', "*")),
+        FormattedChemical::Coefficient(_, coefficient) => {
+            out.push_str(&coefficients.write(*coefficient))
+        }
+        FormattedChemical::Sub(text) => out.push_str(text),
+    }
+}
+
+/// Presentation MathML, with each element symbol an identifier.
+pub struct MathMl;
+
+impl Renderer for MathMl {
+    fn name(&self) -> &'static str {
+        "mathml"
+    }
+
+    fn render(&self, reaction: &Reaction, style: &OutputStyle) -> String {
+        let mut out = String::from("<math>");
+        write_mathml(&format_reaction(reaction), style.coefficients, &mut out);
+        out.push_str("</math>");
+        out
+    }
+}
+
+fn write_mathml(parts: &[FormattedChemical], coefficients: CoefficientStyle, out: &mut String) {
+    let mut parts = parts.iter().peekable();
+    while let Some(part) = parts.next() {
+        match part {
+            FormattedChemical::Coefficient(_, coefficient) if *coefficient > 1 => {
+                out.push_str(&format!("<mn>{}</mn>", coefficient));
+                if coefficients == CoefficientStyle::Spaced {
+                    out.push_str("<mspace width=\"0.25em\"/>");
+                }
+            }
+            FormattedChemical::Coefficient(..) => {}
+            FormattedChemical::Text(text) => {
+                let mut tokens = math_tokens(text);
+                if let Some(FormattedChemical::Sub(digits)) = parts.peek() {
+                    let base = tokens.pop().unwrap_or_else(|| "<mrow></mrow>".into());
+                    tokens.push(format!("<msub>{}<mn>{}</mn></msub>", base, digits));
+                    parts.next();
+                }
+                out.extend(tokens);
+            }
+            FormattedChemical::Sub(digits) => {
+                out.push_str(&format!("<msub><mrow></mrow><mn>{}</mn></msub>", digits))
+            }
+            FormattedChemical::Sup(charge) => out.push_str(&format!(
+                "<msup><mrow></mrow><mrow>{}</mrow></msup>",
+                math_tokens(charge).concat()
+            )),
+            FormattedChemical::Fragment(inner) | FormattedChemical::Species(_, inner) => {
+                write_mathml(inner, coefficients, out)
+            }
+            FormattedChemical::Arrow(over, under) => {
+                let text = |note: &str| format!("<mtext>{}</mtext>", escape(note));
+                match (over, under) {
+                    (None, None) => out.push_str("<mo>→</mo>"),
+                    (Some(over), None) => {
+                        out.push_str(&format!("<mover><mo>→</mo>{}</mover>", text(over)))
+                    }
+                    (None, Some(under)) => {
+                        out.push_str(&format!("<munder><mo>→</mo>{}</munder>", text(under)))
+                    }
+                    (Some(over), Some(under)) => out.push_str(&format!(
+                        "<munderover><mo>→</mo>{}{}</munderover>",
+                        text(under),
+                        text(over)
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Splits text into element symbols as identifiers, numbers, and other
+/// characters as operators, leaving out spaces.
+fn math_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphabetic() {
+            let mut symbol = c.to_string();
+            while let Some(&next) = chars.peek() {
+                if !next.is_lowercase() {
+                    break;
+                }
+                symbol.push(next);
+                chars.next();
+            }
+            tokens.push(format!(
+                "<mi mathvariant=\"normal\">{}</mi>",
+                escape(&symbol)
+            ));
+        } else if c.is_ascii_digit() {
+            let mut number = c.to_string();
+            while let Some(&next) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                number.push(next);
+                chars.next();
+            }
+            tokens.push(format!("<mn>{}</mn>", number));
+        } else if !c.is_whitespace() {
+            tokens.push(format!("<mo>{}</mo>", escape(&c.to_string())));
+        }
+    }
+    tokens
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+    use crate::format::ScriptStyle;
+    use crate::reaction::calculate_coefficients;
+
+    const SNAPSHOT_PATH: &str = "snapshots/renderers.txt";
+    const EQUATIONS: [&str; 10] = [
+        "H2 + O2 = H2O",
+        "CH3CH2OH + O2 = CO2 + H2O",
+        "Ca(OH)2 + HCl = CaCl2 + H2O",
+        "Fe2O3 + C = Fe + CO2",
+        "H2O2 =[MnO2]= H2O + O2",
+        "CaCO3 = CaO + CO2; Δ",
+        "N2 + H2 = NH3; 450°C, 200 atm",
+        "KMnO4 + HCl = KCl + MnCl2 + H2O + Cl2",
+        "CuSO4·5H2O = CuSO4 + H2O",
+        "Cu + Ag+ = Cu^2+ + Ag",
+    ];
+
+    #[test]
+    fn unicode_species_scripts() {
+        let sulfate = crate::chemical::parse_chemical("SO4^2-").unwrap();
+        assert_eq!("SO₄²⁻", unicode_species(&sulfate));
+    }
+
+    /// Unicode output pasted back in balances to the same coefficients. Catalysts and
+    /// conditions are left out, as the arrow they are written over is not input syntax.
+    #[test]
+    fn unicode_output_parses_back() {
+        let balance = |input: &str| {
+            let equation = parse_equation(input).unwrap();
+            let coefficients =
+                calculate_coefficients(&equation.reagents, &equation.products).unwrap();
+            equation.into_reaction(coefficients)
+        };
+        for input in EQUATIONS
+            .iter()
+            .filter(|input| !input.contains(&['[', ';'][..]))
+        {
+            let reaction = balance(input);
+            let pasted = Unicode.render(&reaction, &OutputStyle::default());
+            assert_eq!(
+                reaction.coefficients,
+                balance(&pasted).coefficients,
+                "{}",
+                pasted
+            );
+        }
+    }
+
+    /// Compares every renderer's output for `EQUATIONS` with the snapshot,
+    /// which `UPDATE_SNAPSHOTS=1 cargo test` rewrites instead.
+    #[test]
+    fn renderer_snapshots() {
+        let mut actual = String::new();
+        for input in &EQUATIONS {
+            let equation = parse_equation(input).unwrap();
+            let coefficients =
+                calculate_coefficients(&equation.reagents, &equation.products).unwrap();
+            let reaction = equation.into_reaction(coefficients);
+            actual.push_str(input);
+            actual.push('\n');
+            for renderer in &RENDERERS {
+                actual.push_str(&format!(
+                    "  {}: {}\n",
+                    renderer.name(),
+                    renderer.render(&reaction, &OutputStyle::default())
+                ));
+            }
+        }
+        // `file!()` is relative to the crate root that tests run in, or absolute
+        let root = std::path::Path::new(file!())
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap();
+        let path = root.join(SNAPSHOT_PATH);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            expected == actual,
+            "renderer output differs from {}, rerun with UPDATE_SNAPSHOTS=1 if intended:\n{}",
+            path.display(),
+            actual
+        );
+    }
+
+    #[test]
+    fn styled_output() {
+        let equation = parse_equation("Cu + Ag+ = Cu2+ + Ag").unwrap();
+        let coefficients = calculate_coefficients(&equation.reagents, &equation.products).unwrap();
+        let reaction = equation.into_reaction(coefficients);
+        let spaced = OutputStyle {
+            coefficients: CoefficientStyle::Spaced,
+            scripts: ScriptStyle::Unicode,
+        };
+        assert_eq!(
+            "Cu + 2 Ag+ = Cu2+ + 2 Ag",
+            PlainText.render(&reaction, &spaced)
+        );
+        assert_eq!(
+            "Cu + 2 Ag⁺ → Cu²⁺ + 2 Ag",
+            Unicode.render(&reaction, &spaced)
+        );
+        assert_eq!(
+            "<span class=\"species\"><span class=\"fragment\">Cu</span></span> + 2 <span class=\"species\"><span class=\"fragment\">Ag</span>⁺</span>",
+            Html.render(&reaction, &spaced).split(" = ").next().unwrap()
+        );
+        let normal = OutputStyle {
+            coefficients: CoefficientStyle::Normal,
+            ..OutputStyle::default()
+        };
+        assert!(Html.render(&reaction, &normal).contains("+ 2<span"));
+        assert_eq!(
+            Latex.render(&reaction, &OutputStyle::default()),
+            Latex.render(&reaction, &normal)
+        );
+    }
+
+    #[test]
+    fn subscript_digits() {
+        assert_eq!('₀', subscript('0'));
+        assert_eq!('₉', subscript('9'));
+        assert_eq!('x', subscript('x'));
+    }
+
+    #[test]
+    fn superscript_charges() {
+        assert_eq!("²⁻", "2-".chars().map(superscript).collect::<String>());
+        assert_eq!("¹⁰⁺", "10+".chars().map(superscript).collect::<String>());
+    }
+}
@@ -0,0 +1,215 @@
+//! Versioned JSON representation of a balanced reaction.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "species": [
+//!     { "formula": "H2O", "side": "reagent", "composition": { "H": 2, "O": 1 } },
+//!     { "formula": "H2", "side": "product", "composition": { "H": 2 } },
+//!     { "formula": "O2", "side": "product", "composition": { "O": 2 }, "phase": "gas" }
+//!   ],
+//!   "coefficients": [2, 2, 1],
+//!   "catalyst": "Pt",
+//!   "conditions": ["Δ"]
+//! }
+//! ```
+//!
+//! `coefficients[i]` belongs to `species[i]`, and `phase`, `catalyst` and `conditions` are omitted when absent.
+//! Fields may be added without notice, but changing the meaning of an existing
+//! field bumps `version`.
+
+use crate::chemical::{parse_chemical, Phase};
+use crate::condition::{is_plain_text, Condition};
+use crate::equation::Warning;
+use crate::oxidation::{oxidation_report, redox_kind, OxidationChange, RedoxKind};
+use crate::reaction::Reaction;
+pub use crate::reaction::Side;
+use crate::solver::Diagnostics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Longest catalyst kept when it is plain text rather than a formula.
+const MAX_CATALYST_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceResult {
+    pub version: u32,
+    pub species: Vec<Species>,
+    pub coefficients: Vec<i64>,
+    /// Catalyst written over the arrow, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catalyst: Option<String>,
+    /// Reaction conditions such as `Δ` or `200 atm`, as displayed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<String>,
+    /// Solving steps, present only when the producer records them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<String>>,
+    /// Rank and nullity of the reaction matrix, present only when the producer records them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Diagnostics>,
+    /// Non-fatal notes about how the input was read.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// Changes of oxidation state, omitted when there are none or they could not be assigned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub oxidation: Vec<OxidationChange>,
+    /// Kind of redox reaction the changes make, omitted along with them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redox: Option<RedoxKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Species {
+    pub formula: String,
+    pub side: Side,
+    pub composition: BTreeMap<String, usize>,
+    /// State written after the formula, omitted when there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<Phase>,
+}
+
+impl BalanceResult {
+    pub fn new(reaction: &Reaction) -> Self {
+        let reagents = reaction
+            .reagents
+            .iter()
+            .map(|chemical| (chemical, Side::Reagent));
+        let products = reaction
+            .products
+            .iter()
+            .map(|chemical| (chemical, Side::Product));
+        let species = reagents
+            .chain(products)
+            .map(|(chemical, side)| Species {
+                formula: chemical.display.clone(),
+                side,
+                composition: chemical.parts.clone(),
+                phase: chemical.phase,
+            })
+            .collect();
+        let oxidation = oxidation_report(reaction).unwrap_or_default();
+        Self {
+            version: SCHEMA_VERSION,
+            species,
+            coefficients: reaction.coefficients.clone(),
+            catalyst: reaction.catalyst.clone(),
+            conditions: reaction
+                .conditions
+                .iter()
+                .map(|condition| condition.to_string())
+                .collect(),
+            trace: None,
+            diagnostics: None,
+            warnings: Vec::new(),
+            redox: redox_kind(&oxidation),
+            oxidation,
+        }
+    }
+
+    /// Rebuilds the reaction, failing if a formula no longer parses, a
+    /// condition contains markup or the catalyst is neither a formula nor
+    /// short plain text.
+    pub fn to_reaction(&self) -> Option<Reaction> {
+        if !self
+            .conditions
+            .iter()
+            .all(|condition| is_plain_text(condition))
+        {
+            return None;
+        }
+        if let Some(catalyst) = &self.catalyst {
+            let short_text =
+                catalyst.chars().count() <= MAX_CATALYST_LENGTH && is_plain_text(catalyst);
+            if parse_chemical(catalyst).is_none() && !short_text {
+                return None;
+            }
+        }
+        let mut reagents = Vec::new();
+        let mut products = Vec::new();
+        for species in &self.species {
+            let mut chemical = parse_chemical(&species.formula)?;
+            chemical.phase = species.phase;
+            match species.side {
+                Side::Reagent => reagents.push(chemical),
+                Side::Product => products.push(chemical),
+            }
+        }
+        if reagents.len() + products.len() != self.coefficients.len() {
+            return None;
+        }
+        let mut reaction = Reaction::new(reagents, products, self.coefficients.clone());
+        reaction.catalyst = self.catalyst.clone();
+        reaction.conditions = self
+            .conditions
+            .iter()
+            .map(|condition| Condition::parse(condition))
+            .collect();
+        Some(reaction)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("BalanceResult is always serializable")
+    }
+
+    /// Fails on malformed JSON and on versions newer than this build understands.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let result: Self = serde_json::from_str(json)?;
+        if result.version > SCHEMA_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported schema version {}",
+                result.version
+            )));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let reagents = vec![parse_chemical("H2O").unwrap()];
+        let products = vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()];
+        let result = BalanceResult::new(&Reaction::new(reagents, products, vec![2, 2, 1]));
+        let json = result.to_json();
+        assert!(json.starts_with(r#"{"version":1,"species":[{"formula":"H2O","side":"reagent""#));
+        assert_eq!(result, BalanceResult::from_json(&json).unwrap());
+        let reaction = result.to_reaction().unwrap();
+        assert_eq!(result, BalanceResult::new(&reaction));
+    }
+
+    #[test]
+    fn reject_markup_in_conditions() {
+        let json = r#"{"version":1,"species":[{"formula":"H2","side":"reagent","composition":{"H":2}},{"formula":"H2","side":"product","composition":{"H":2}}],"coefficients":[1,1],"conditions":["<img src=x onerror=alert(1)>"]}"#;
+        assert!(BalanceResult::from_json(json)
+            .unwrap()
+            .to_reaction()
+            .is_none());
+    }
+
+    #[test]
+    fn validate_catalyst() {
+        let with_catalyst = |catalyst: &str| {
+            let json = format!(
+                r#"{{"version":1,"species":[{{"formula":"H2","side":"reagent","composition":{{"H":2}}}},{{"formula":"H2","side":"product","composition":{{"H":2}}}}],"coefficients":[1,1],"catalyst":{}}}"#,
+                serde_json::to_string(catalyst).unwrap()
+            );
+            BalanceResult::from_json(&json).unwrap().to_reaction()
+        };
+        assert!(with_catalyst("MnO2").is_some());
+        assert!(with_catalyst("platinum gauze").is_some());
+        assert!(with_catalyst("</text><script>alert(1)</script>").is_none());
+        assert!(with_catalyst(&"a".repeat(MAX_CATALYST_LENGTH + 1)).is_none());
+    }
+
+    #[test]
+    fn reject_future_version() {
+        let json = r#"{"version":2,"species":[],"coefficients":[]}"#;
+        assert!(BalanceResult::from_json(json).is_err());
+    }
+}
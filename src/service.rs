@@ -0,0 +1,178 @@
+//! Balancing typed input into a history, kept apart from the web app so that
+//! the whole pipeline runs and is tested natively.
+
+use crate::alias::Aliases;
+use crate::balancer::Balancer;
+use crate::charge::{unwritten_charges, UnwrittenCharge};
+use crate::chemical::ParserConfig;
+use crate::classify::{classify, ReactionType};
+use crate::equation::{parse_equation_with_config, ParseError, Warning};
+use crate::half_reaction::{self, Recognized};
+use crate::i18n::I18n;
+use crate::reaction::{Reaction, ReactionError};
+
+/// An input balanced and added to a history.
+#[derive(Debug, Clone)]
+pub struct Balanced {
+    /// The input as typed, conditions included.
+    pub source: String,
+    pub reaction: Reaction,
+    /// Notes about how the input was read.
+    pub warnings: Vec<Warning>,
+    pub unwritten_charges: Vec<UnwrittenCharge>,
+    /// Kinds the reaction was recognized as, to tag its entry with.
+    pub kinds: Vec<ReactionType>,
+}
+
+/// Balanced reactions kept in order, such as the entries the app renders.
+pub trait History {
+    fn reactions(&self) -> Vec<&Reaction>;
+    fn push(&mut self, balanced: &Balanced);
+}
+
+impl History for Vec<Balanced> {
+    fn reactions(&self) -> Vec<&Reaction> {
+        self.iter().map(|balanced| &balanced.reaction).collect()
+    }
+
+    fn push(&mut self, balanced: &Balanced) {
+        Vec::push(self, balanced.clone());
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BalanceError {
+    #[error(transparent)]
+    Parse(ParseError),
+    #[error(transparent)]
+    Reaction(ReactionError),
+}
+
+impl BalanceError {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            BalanceError::Parse(error) => error.message_key(),
+            BalanceError::Reaction(error) => error.message_key(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            BalanceError::Parse(error) => error.code(),
+            BalanceError::Reaction(error) => error.code(),
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            BalanceError::Parse(error) => error.localize(i18n),
+            BalanceError::Reaction(error) => error.localize(i18n),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BalanceOutcome {
+    /// The input is a library half-reaction, shown instead of being balanced.
+    HalfReaction(Recognized),
+    Balanced {
+        balanced: Balanced,
+        /// Earlier entry of the history with the same reaction.
+        duplicate_of: Option<usize>,
+    },
+    Failed(BalanceError),
+}
+
+/// Reads `input` as `config` allows and balances it, adding the result to `history`.
+pub fn perform_balance(
+    input: &str,
+    aliases: &Aliases,
+    config: &ParserConfig,
+    balancer: &Balancer,
+    history: &mut impl History,
+) -> BalanceOutcome {
+    if let Some(recognized) = half_reaction::recognize(input) {
+        return BalanceOutcome::HalfReaction(recognized);
+    }
+    let equation = match parse_equation_with_config(input, aliases, config) {
+        Ok(equation) => equation,
+        Err(error) => return BalanceOutcome::Failed(BalanceError::Parse(error)),
+    };
+    let coefficients = match balancer.balance(&equation.reagents, &equation.products) {
+        Ok(coefficients) => coefficients,
+        Err(error) => return BalanceOutcome::Failed(BalanceError::Reaction(error)),
+    };
+    let warnings = equation.warnings.clone();
+    let reaction = equation.into_reaction(coefficients);
+    let key = reaction.canonical_key();
+    let duplicate_of = history
+        .reactions()
+        .iter()
+        .position(|earlier| earlier.canonical_key() == key);
+    let balanced = Balanced {
+        source: input.into(),
+        unwritten_charges: unwritten_charges(reaction.reagents.iter().chain(&reaction.products)),
+        kinds: classify(&reaction),
+        reaction,
+        warnings,
+    };
+    history.push(&balanced);
+    BalanceOutcome::Balanced {
+        balanced,
+        duplicate_of,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn balance_into_history() {
+        let aliases = Aliases::default();
+        let config = ParserConfig::default();
+        let balancer = Balancer::default();
+        let mut history: Vec<Balanced> = Vec::new();
+        let mut balance =
+            |input| perform_balance(input, &aliases, &config, &balancer, &mut history);
+
+        match balance("CH4 + O2 = CO2 + H2O") {
+            BalanceOutcome::Balanced {
+                balanced,
+                duplicate_of: None,
+            } => {
+                assert_eq!(vec![1, 2, 1, 2], balanced.reaction.coefficients);
+                assert_eq!(
+                    vec![ReactionType::Combustion, ReactionType::Redox],
+                    balanced.kinds
+                );
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(matches!(
+            balance("O2 + CH4 = H2O + CO2"),
+            BalanceOutcome::Balanced {
+                duplicate_of: Some(0),
+                ..
+            }
+        ));
+        assert!(matches!(
+            balance("Cu2+ + e- = Cu"),
+            BalanceOutcome::HalfReaction(_)
+        ));
+        let half = match balance("Fe = Fe^3+ + e-") {
+            BalanceOutcome::Balanced { balanced, .. } => balanced.reaction,
+            _ => panic!("a half-reaction outside the library is balanced"),
+        };
+        assert_eq!(vec![1, 1, 3], half.coefficients);
+        assert!(matches!(
+            balance("H2 = O2"),
+            BalanceOutcome::Failed(BalanceError::Reaction(_))
+        ));
+        assert!(matches!(
+            balance("H2 + O2 = H2O + 2x"),
+            BalanceOutcome::Failed(BalanceError::Parse(_))
+        ));
+        assert_eq!(3, history.len());
+    }
+}
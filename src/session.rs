@@ -0,0 +1,167 @@
+//! Everything worth keeping between visits, saved as one versioned JSON document.
+
+use crate::query;
+use crate::schema::BalanceResult;
+use serde::{Deserialize, Serialize};
+
+pub const SESSION_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    /// Milliseconds since the Unix epoch at the last save, compared when syncing.
+    #[serde(default)]
+    pub saved_at: f64,
+    /// Balanced equations, oldest first.
+    #[serde(default)]
+    pub history: Vec<HistoryRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// The equation as typed, conditions included.
+    pub input: String,
+    pub result: BalanceResult,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Free text kept with the entry, such as where the problem came from.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            version: SESSION_VERSION,
+            saved_at: 0.0,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Session is always serializable")
+    }
+
+    /// Fails on malformed JSON and on versions newer than this build understands.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let session: Self = serde_json::from_str(json)?;
+        if session.version > SESSION_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported session version {}",
+                session.version
+            )));
+        }
+        Ok(session)
+    }
+}
+
+/// Picks the session saved last, preferring `local` on a tie.
+pub fn latest(local: Session, remote: Session) -> Session {
+    if remote.saved_at > local.saved_at {
+        remote
+    } else {
+        local
+    }
+}
+
+/// A sync endpoint set up by a classroom deployment with `?sync=<url>&token=<token>`.
+/// The session is read with `GET` and replaced with `PUT` at `url`, sending the
+/// token as a bearer token. An endpoint from a link is only offered; it is used
+/// once the user accepts it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+}
+
+impl SyncConfig {
+    /// The endpoint offered by a link, if it is an `http` or `https` URL.
+    pub fn from_query(search: &str) -> Option<Self> {
+        let config = Self {
+            url: query::param(search, "sync")?,
+            token: query::param(search, "token").unwrap_or_default(),
+        };
+        config.host()?;
+        Some(config)
+    }
+
+    /// The host the session is sent to, with its port, named when asking the user.
+    pub fn host(&self) -> Option<&str> {
+        let rest = self
+            .url
+            .strip_prefix("https://")
+            .or_else(|| self.url.strip_prefix("http://"))?;
+        let authority = rest.split(&['/', '?', '#'][..]).next()?;
+        let host = authority.rsplit('@').next()?;
+        if host.is_empty() {
+            None
+        } else {
+            Some(host)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+    use crate::reaction::calculate_coefficients;
+
+    #[test]
+    fn round_trip() {
+        let input = "H2 + O2 = H2O; Δ";
+        let equation = parse_equation(input).unwrap();
+        let coefficients = calculate_coefficients(&equation.reagents, &equation.products).unwrap();
+        let reaction = equation.into_reaction(coefficients);
+        let mut session = Session::default();
+        session.history.push(HistoryRecord {
+            input: input.into(),
+            result: BalanceResult::new(&reaction),
+            tags: vec!["chapter 7".into()],
+            note: "problem 12b".into(),
+        });
+        let restored = Session::from_json(&session.to_json()).unwrap();
+        assert_eq!(session, restored);
+        let reaction = restored.history[0].result.to_reaction().unwrap();
+        assert_eq!(vec![2, 1, 2], reaction.coefficients);
+        assert_eq!("Δ", reaction.conditions[0].to_string());
+        assert_eq!(vec!["chapter 7"], restored.history[0].tags);
+        assert_eq!("problem 12b", restored.history[0].note);
+        assert!(Session::from_json(r#"{"version":2}"#).is_err());
+    }
+
+    #[test]
+    fn last_write_wins() {
+        let local = Session {
+            saved_at: 2.0,
+            ..Session::default()
+        };
+        let remote = Session {
+            saved_at: 1.0,
+            ..Session::default()
+        };
+        assert_eq!(local, latest(local.clone(), remote.clone()));
+        assert_eq!(local, latest(remote, local.clone()));
+    }
+
+    #[test]
+    fn sync_from_query() {
+        let config =
+            SyncConfig::from_query("?sync=https%3A%2F%2Fschool.example%2Fs%2F12&token=abc")
+                .unwrap();
+        assert_eq!("https://school.example/s/12", config.url);
+        assert_eq!("abc", config.token);
+        assert_eq!(Some("school.example"), config.host());
+        assert_eq!(None, SyncConfig::from_query("?equation=H2"));
+        assert_eq!(None, SyncConfig::from_query("?sync="));
+        assert_eq!(None, SyncConfig::from_query("?sync=javascript%3Aalert(1)"));
+        let config = SyncConfig {
+            url: "http://user@localhost:8080/s".into(),
+            token: String::new(),
+        };
+        assert_eq!(Some("localhost:8080"), config.host());
+    }
+}
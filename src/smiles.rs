@@ -0,0 +1,231 @@
+use crate::chemical::{hill_formula, Chemical};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+struct Atom {
+    symbol: String,
+    aromatic: bool,
+    bond_sum: usize,
+    explicit_hydrogen: Option<usize>,
+}
+
+impl Atom {
+    fn implicit_hydrogen(&self) -> usize {
+        if let Some(count) = self.explicit_hydrogen {
+            return count;
+        }
+        let valences: &[usize] = match self.symbol.as_str() {
+            "B" => &[3],
+            "C" => &[4],
+            "N" => &[3, 5],
+            "O" => &[2],
+            "P" => &[3, 5],
+            "S" => &[2, 4, 6],
+            "F" | "Cl" | "Br" | "I" => &[1],
+            _ => &[],
+        };
+        // An aromatic atom spends one more valence on the delocalized bond
+        let used = self.bond_sum + self.aromatic as usize;
+        valences
+            .iter()
+            .find(|&&valence| valence >= used)
+            .map_or(0, |valence| valence - used)
+    }
+}
+
+/// Parses a SMILES string without stereochemistry into element counts, including implicit hydrogens.
+///
+/// The resulting `Chemical` is displayed by its Hill formula, since the SMILES string is not a formula.
+pub fn parse_smiles(input: impl AsRef<str>) -> Option<Chemical> {
+    let input = input.as_ref();
+    let mut atoms: Vec<Atom> = Vec::new();
+    let mut previous: Option<usize> = None;
+    let mut branches = Vec::new();
+    let mut pending_bond: Option<usize> = None;
+    let mut rings: BTreeMap<u32, (usize, Option<usize>)> = BTreeMap::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            'B' if chars.peek() == Some(&'r') => {
+                chars.next();
+                Some(organic_atom("Br", false))
+            }
+            'C' if chars.peek() == Some(&'l') => {
+                chars.next();
+                Some(organic_atom("Cl", false))
+            }
+            'B' | 'C' | 'N' | 'O' | 'P' | 'S' | 'F' | 'I' => {
+                Some(organic_atom(&c.to_string(), false))
+            }
+            'b' | 'c' | 'n' | 'o' | 'p' | 's' => Some(organic_atom(&c.to_string(), true)),
+            '[' => Some(bracket_atom(&mut chars)?),
+            '(' => {
+                branches.push(previous?);
+                None
+            }
+            ')' => {
+                previous = Some(branches.pop()?);
+                None
+            }
+            '-' | ':' => {
+                pending_bond = Some(1);
+                None
+            }
+            '=' => {
+                pending_bond = Some(2);
+                None
+            }
+            '#' => {
+                pending_bond = Some(3);
+                None
+            }
+            '.' => {
+                previous = None;
+                None
+            }
+            '0'..='9' | '%' => {
+                let number = if c == '%' {
+                    let tens = chars.next()?.to_digit(10)?;
+                    let ones = chars.next()?.to_digit(10)?;
+                    tens * 10 + ones
+                } else {
+                    c.to_digit(10)?
+                };
+                let current = previous?;
+                if let Some((opening, opening_bond)) = rings.remove(&number) {
+                    let order = pending_bond.or(opening_bond).unwrap_or(1);
+                    atoms[opening].bond_sum += order;
+                    atoms[current].bond_sum += order;
+                } else {
+                    rings.insert(number, (current, pending_bond));
+                }
+                pending_bond = None;
+                None
+            }
+            _ => return None,
+        };
+
+        if let Some(mut atom) = atom {
+            let index = atoms.len();
+            if let Some(previous) = previous {
+                let order = pending_bond.take().unwrap_or(1);
+                atoms[previous].bond_sum += order;
+                atom.bond_sum += order;
+            } else if pending_bond.is_some() {
+                return None;
+            }
+            atoms.push(atom);
+            previous = Some(index);
+        }
+    }
+
+    if atoms.is_empty() || !branches.is_empty() || !rings.is_empty() || pending_bond.is_some() {
+        return None;
+    }
+
+    let mut parts = BTreeMap::new();
+    for atom in &atoms {
+        *parts.entry(atom.symbol.clone()).or_insert(0) += 1;
+        let hydrogen = atom.implicit_hydrogen();
+        if hydrogen > 0 {
+            *parts.entry("H".to_string()).or_insert(0) += hydrogen;
+        }
+    }
+    let display = hill_formula(&parts);
+
+    Some(Chemical {
+        parts,
+        fragments: vec![display.clone()],
+        display,
+        charge: 0,
+        phase: None,
+    })
+}
+
+fn organic_atom(symbol: &str, aromatic: bool) -> Atom {
+    let mut chars = symbol.chars();
+    let symbol = chars
+        .next()
+        .map(|first| first.to_ascii_uppercase())
+        .into_iter()
+        .chain(chars)
+        .collect();
+    Atom {
+        symbol,
+        aromatic,
+        bond_sum: 0,
+        explicit_hydrogen: None,
+    }
+}
+
+fn bracket_atom(chars: &mut core::iter::Peekable<core::str::Chars>) -> Option<Atom> {
+    // Isotope mass does not change the element counts
+    while chars.next_if(char::is_ascii_digit).is_some() {}
+    let first = chars.next()?;
+    let mut symbol = first.to_string();
+    if first.is_ascii_uppercase() {
+        if let Some(&c) = chars.peek() {
+            if c.is_ascii_lowercase() {
+                symbol.push(c);
+                chars.next();
+            }
+        }
+    } else if !matches!(first, 'b' | 'c' | 'n' | 'o' | 'p' | 's') {
+        return None;
+    }
+    let mut atom = organic_atom(&symbol, first.is_ascii_lowercase());
+    atom.explicit_hydrogen = Some(0);
+
+    loop {
+        match chars.next()? {
+            'H' => {
+                // `H` alone is one hydrogen, while a written count, even `H0`, is taken as is
+                let mut count = None;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    count = Some(count.unwrap_or(0) * 10 + digit as usize);
+                    chars.next();
+                }
+                atom.explicit_hydrogen = Some(count.unwrap_or(1));
+            }
+            ':' => {
+                // Atom class, which carries no chemical meaning
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+            }
+            ']' => break,
+            // Stereo markers and charges are outside the supported subset
+            _ => return None,
+        }
+    }
+    Some(atom)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_smiles_test_chain() {
+        let output = parse_smiles("CC(=O)O").unwrap();
+        assert_eq!("C2H4O2", output.display);
+        assert_eq!(4, output.parts["H"]);
+    }
+
+    #[test]
+    fn parse_smiles_test_ring() {
+        assert_eq!("C6H6", parse_smiles("c1ccccc1").unwrap().display);
+        assert_eq!("C3H6", parse_smiles("C%10CC%10").unwrap().display);
+        assert_eq!("C4H5N", parse_smiles("c1cc[nH]c1").unwrap().display);
+        assert!(parse_smiles("C1CC").is_none());
+    }
+
+    #[test]
+    fn parse_smiles_test_bracket_hydrogen() {
+        assert_eq!(4, parse_smiles("[CH4]").unwrap().parts["H"]);
+        assert_eq!(2, parse_smiles("[OH2]").unwrap().parts["H"]);
+        assert_eq!(4, parse_smiles("[OH]C").unwrap().parts["H"]);
+        assert!(!parse_smiles("[CH0]").unwrap().parts.contains_key("H"));
+    }
+}
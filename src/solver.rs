@@ -0,0 +1,537 @@
+//! Alternative ways to find the null space of a reaction matrix, so the
+//! results and speed of each can be compared on the same input.
+
+use crate::reaction::{ReactionError, Result};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolverStrategy {
+    /// Row elimination scaled by least common multiples, the original solver.
+    #[default]
+    IntegerElimination,
+    /// Reduced row echelon form over exact fractions.
+    RationalRref,
+    /// Fraction-free elimination keeping every entry an integer.
+    Bareiss,
+}
+
+impl SolverStrategy {
+    pub const ALL: [SolverStrategy; 3] = [
+        SolverStrategy::IntegerElimination,
+        SolverStrategy::RationalRref,
+        SolverStrategy::Bareiss,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            SolverStrategy::IntegerElimination => "integer",
+            SolverStrategy::RationalRref => "rational",
+            SolverStrategy::Bareiss => "bareiss",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|strategy| strategy.id() == id)
+    }
+}
+
+/// Shape of the solution space, explaining why a reaction has no unique balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Diagnostics {
+    pub rank: usize,
+    /// Number of independent reactions among the species.
+    pub nullity: usize,
+    /// Whether the coefficients are determined up to scaling.
+    pub unique: bool,
+}
+
+pub fn diagnose(matrix: &[i64], columns: usize) -> Diagnostics {
+    let rank = reduce_rows(matrix, columns).1.len();
+    let nullity = columns - rank;
+    Diagnostics {
+        rank,
+        nullity,
+        unique: nullity == 1,
+    }
+}
+
+/// Solves a row-major matrix with a one-dimensional null space by reduced
+/// row echelon form over fractions.
+pub fn rational_rref(matrix: &[i64], columns: usize) -> Result<Vec<i64>> {
+    let (rows, pivots) = reduce_rows(matrix, columns);
+    let free = single_free_column(&pivots, columns)?;
+    normalize(null_vector(&rows, &pivots, free, columns))
+}
+
+/// Solves the system with the coefficients of some columns fixed, failing
+/// with `Inconsistent` when no positive integer solution has those values.
+pub fn solve_fixed(matrix: &[i64], columns: usize, fixed: &[(usize, i64)]) -> Result<Vec<i64>> {
+    if fixed.iter().any(|&(column, _)| column >= columns) {
+        return Err(ReactionError::Inconsistent);
+    }
+    // Augment with the fixed values as a last column and a row per fixed column
+    let mut augmented: Vec<i64> = matrix
+        .chunks(columns)
+        .flat_map(|row| row.iter().copied().chain(Some(0)))
+        .collect();
+    for &(column, value) in fixed {
+        augmented.extend((0..columns).map(|other| (other == column) as i64));
+        augmented.push(value);
+    }
+    let (rows, pivots) = reduce_rows(&augmented, columns + 1);
+    if pivots.contains(&columns) {
+        return Err(ReactionError::Inconsistent);
+    }
+    if pivots.len() < columns {
+        return Err(ReactionError::InfiniteSolution);
+    }
+    rows.iter()
+        .take(columns)
+        .map(|row| row[columns])
+        .map(|value| {
+            if value.den == 1 && value.num > 0 && value.num <= i64::MAX as i128 {
+                Ok(value.num as i64)
+            } else {
+                Err(ReactionError::Inconsistent)
+            }
+        })
+        .collect()
+}
+
+/// Integer basis of the null space with coefficients kept small by lattice
+/// reduction, one vector per independent reaction. Fails when a coefficient
+/// is still too large for an `i64` after reduction.
+pub fn nullspace_basis(matrix: &[i64], columns: usize) -> Result<Vec<Vec<i64>>> {
+    let (rows, pivots) = reduce_rows(matrix, columns);
+    let mut basis: Vec<Vec<i128>> = (0..columns)
+        .filter(|column| !pivots.contains(column))
+        .map(|free| null_vector(&rows, &pivots, free, columns))
+        .collect();
+    reduce_lattice(&mut basis);
+    basis
+        .into_iter()
+        .map(|vector| {
+            vector
+                .into_iter()
+                .map(|value| i64::try_from(value).map_err(|_| ReactionError::VerificationFailed))
+                .collect()
+        })
+        .collect()
+}
+
+/// Reduced row echelon form over fractions, with the pivot column of each row.
+fn reduce_rows(matrix: &[i64], columns: usize) -> (Vec<Vec<Fraction>>, Vec<usize>) {
+    let mut rows: Vec<Vec<Fraction>> = matrix
+        .chunks(columns)
+        .map(|row| row.iter().map(|&value| Fraction::from(value)).collect())
+        .collect();
+
+    let mut pivots = Vec::new();
+    for column in 0..columns {
+        let rank = pivots.len();
+        let pivot = match (rank..rows.len()).find(|&row| !rows[row][column].is_zero()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        let divisor = rows[rank][column];
+        for value in rows[rank].iter_mut() {
+            *value = value.div(divisor);
+        }
+        let pivot_row = rows[rank].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if index != rank && !factor.is_zero() {
+                for (value, &pivot_value) in row.iter_mut().zip(&pivot_row) {
+                    *value = value.sub(pivot_value.mul(factor));
+                }
+            }
+        }
+        pivots.push(column);
+    }
+    (rows, pivots)
+}
+
+/// Integer null space vector with 1 in the `free` column before clearing denominators.
+fn null_vector(rows: &[Vec<Fraction>], pivots: &[usize], free: usize, columns: usize) -> Vec<i128> {
+    // Each pivot variable is the negated entry in the free column of its row
+    let mut solution = vec![Fraction::from(0); columns];
+    solution[free] = Fraction::from(1);
+    for (row, &pivot) in pivots.iter().enumerate() {
+        solution[pivot] = Fraction::from(0).sub(rows[row][free]);
+    }
+    let denominator = solution
+        .iter()
+        .fold(1, |lcm, value| lcm / gcd(lcm, value.den) * value.den);
+    solution
+        .iter()
+        .map(|value| value.num * (denominator / value.den))
+        .collect()
+}
+
+/// Shortens basis vectors by subtracting whole multiples of the others until
+/// no vector gets shorter, then divides out common factors and fixes signs.
+fn reduce_lattice(basis: &mut [Vec<i128>]) {
+    let dot = |a: &[i128], b: &[i128]| -> i128 { a.iter().zip(b).map(|(x, y)| x * y).sum() };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        basis.sort_by_key(|vector| dot(vector, vector));
+        for i in 0..basis.len() {
+            for j in 0..basis.len() {
+                let norm = dot(&basis[j], &basis[j]);
+                if i == j || norm == 0 {
+                    continue;
+                }
+                // Nearest whole multiple of basis[j] to project out of basis[i]
+                let projection = dot(&basis[i], &basis[j]);
+                let multiple = (2 * projection + norm).div_euclid(2 * norm);
+                if multiple == 0 {
+                    continue;
+                }
+                let reduced: Vec<i128> = basis[i]
+                    .iter()
+                    .zip(&basis[j])
+                    .map(|(a, b)| a - multiple * b)
+                    .collect();
+                if dot(&reduced, &reduced) < dot(&basis[i], &basis[i]) {
+                    basis[i] = reduced;
+                    changed = true;
+                }
+            }
+        }
+    }
+    for vector in basis.iter_mut() {
+        let divisor = vector.iter().fold(0, |divisor, &value| gcd(divisor, value));
+        let sign = match vector.iter().find(|&&value| value != 0) {
+            Some(&first) if first < 0 => -1,
+            _ => 1,
+        };
+        if divisor > 0 {
+            vector
+                .iter_mut()
+                .for_each(|value| *value = *value / divisor * sign);
+        }
+    }
+}
+
+/// Solves a row-major matrix with a one-dimensional null space by
+/// fraction-free Bareiss elimination.
+pub fn bareiss(matrix: &[i64], columns: usize) -> Result<Vec<i64>> {
+    let mut rows: Vec<Vec<i128>> = matrix
+        .chunks(columns)
+        .map(|row| row.iter().map(|&value| value as i128).collect())
+        .collect();
+
+    let mut pivots = Vec::new();
+    let mut previous = 1i128;
+    for column in 0..columns {
+        let rank = pivots.len();
+        let pivot = match (rank..rows.len()).find(|&row| rows[row][column] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        for row in rank + 1..rows.len() {
+            for other in column + 1..columns {
+                // Exact by Sylvester's identity
+                rows[row][other] = (rows[rank][column] * rows[row][other]
+                    - rows[row][column] * rows[rank][other])
+                    / previous;
+            }
+            rows[row][column] = 0;
+        }
+        previous = rows[rank][column];
+        pivots.push(column);
+    }
+
+    let free = single_free_column(&pivots, columns)?;
+    let mut solution = vec![0i128; columns];
+    solution[free] = 1;
+    for (row, &pivot) in pivots.iter().enumerate().rev() {
+        let sum: i128 = (pivot + 1..columns)
+            .map(|column| rows[row][column] * solution[column])
+            .sum();
+        // pivot * x + sum = 0, scaling the known values until x is whole
+        let coefficient = rows[row][pivot];
+        let divisor = gcd(sum, coefficient);
+        let scale = (coefficient / divisor).abs();
+        solution.iter_mut().for_each(|value| *value *= scale);
+        solution[pivot] = -sum * scale / coefficient;
+    }
+    normalize(solution)
+}
+
+/// Large primes the solution is re-solved under by [`cross_check`].
+const CHECK_PRIMES: [i128; 2] = [2_147_483_647, 1_000_000_007];
+
+/// Re-solves the system modulo a few large primes and checks that `solution`
+/// agrees with each, which catches a result corrupted by overflow.
+pub fn cross_check(matrix: &[i64], columns: usize, solution: &[i64]) -> bool {
+    solution.len() == columns
+        && CHECK_PRIMES
+            .iter()
+            .all(|&prime| agrees_modulo(matrix, columns, solution, prime))
+}
+
+fn agrees_modulo(matrix: &[i64], columns: usize, solution: &[i64], prime: i128) -> bool {
+    let reduce = |value: i128| value.rem_euclid(prime);
+    let mut rows: Vec<Vec<i128>> = matrix
+        .chunks(columns)
+        .map(|row| row.iter().map(|&value| reduce(value as i128)).collect())
+        .collect();
+
+    let mut pivots = Vec::new();
+    for column in 0..columns {
+        let rank = pivots.len();
+        let pivot = match (rank..rows.len()).find(|&row| rows[row][column] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        let inverse = power(rows[rank][column], prime - 2, prime);
+        for value in rows[rank].iter_mut() {
+            *value = reduce(*value * inverse);
+        }
+        let pivot_row = rows[rank].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if index != rank && factor != 0 {
+                for (value, &pivot_value) in row.iter_mut().zip(&pivot_row) {
+                    *value = reduce(*value - pivot_value * factor);
+                }
+            }
+        }
+        pivots.push(column);
+    }
+
+    let solution: Vec<i128> = solution
+        .iter()
+        .map(|&value| reduce(value as i128))
+        .collect();
+    let free = match single_free_column(&pivots, columns) {
+        Ok(free) => free,
+        // The prime divides a minor, so it says nothing about the solution
+        Err(ReactionError::InfiniteSolution) => return true,
+        Err(_) => return false,
+    };
+    // The null space modulo the prime is spanned by the vector with 1 in the free column
+    pivots
+        .iter()
+        .enumerate()
+        .all(|(row, &pivot)| solution[pivot] == reduce(-rows[row][free] * solution[free]))
+        && solution[free] != 0
+}
+
+fn power(mut base: i128, mut exponent: i128, modulus: i128) -> i128 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result
+}
+
+/// The only column without a pivot, which the rest of the solution follows.
+fn single_free_column(pivots: &[usize], columns: usize) -> Result<usize> {
+    let mut free = (0..columns).filter(|column| !pivots.contains(column));
+    match (free.next(), free.next()) {
+        (Some(column), None) => Ok(column),
+        (None, _) => Err(ReactionError::UnbalancedElements),
+        (Some(_), Some(_)) => Err(ReactionError::InfiniteSolution),
+    }
+}
+
+/// Reduces a null space vector to the smallest positive integers.
+fn normalize(solution: Vec<i128>) -> Result<Vec<i64>> {
+    let divisor = solution
+        .iter()
+        .fold(0, |divisor, &value| gcd(divisor, value));
+    let sign = if solution.first().is_some_and(|&value| value < 0) {
+        -1
+    } else {
+        1
+    };
+    solution
+        .into_iter()
+        .map(|value| value / divisor * sign)
+        .map(|value| {
+            if value > 0 && value <= i64::MAX as i128 {
+                Ok(value as i64)
+            } else {
+                Err(ReactionError::UnbalancedElements)
+            }
+        })
+        .collect()
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fraction {
+    num: i128,
+    /// Always positive.
+    den: i128,
+}
+
+impl From<i64> for Fraction {
+    fn from(value: i64) -> Self {
+        Fraction {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl Fraction {
+    fn new(num: i128, den: i128) -> Self {
+        let divisor = gcd(num, den) * den.signum();
+        Fraction {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.num == 0
+    }
+
+    fn sub(self, other: Fraction) -> Fraction {
+        Fraction::new(
+            self.num * other.den - other.num * self.den,
+            self.den * other.den,
+        )
+    }
+
+    fn mul(self, other: Fraction) -> Fraction {
+        Fraction::new(self.num * other.num, self.den * other.den)
+    }
+
+    fn div(self, other: Fraction) -> Fraction {
+        Fraction::new(self.num * other.den, self.den * other.num)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+    use crate::reaction::calculate_coefficients_with;
+
+    fn solve_all(reagents: &[&str], products: &[&str]) -> Vec<Result<Vec<i64>>> {
+        let reagents: Vec<_> = reagents
+            .iter()
+            .map(|f| parse_chemical(f).unwrap())
+            .collect();
+        let products: Vec<_> = products
+            .iter()
+            .map(|f| parse_chemical(f).unwrap())
+            .collect();
+        SolverStrategy::ALL
+            .iter()
+            .map(|&strategy| calculate_coefficients_with(&reagents, &products, strategy))
+            .collect()
+    }
+
+    #[test]
+    fn strategies_agree() {
+        for result in solve_all(&["KMnO4", "HCl"], &["KCl", "MnCl2", "H2O", "Cl2"]) {
+            assert_eq!(vec![2, 16, 2, 2, 8, 5], result.unwrap());
+        }
+        for result in solve_all(&["C3H8", "O2"], &["CO2", "H2O"]) {
+            assert_eq!(vec![1, 5, 3, 4], result.unwrap());
+        }
+    }
+
+    #[test]
+    fn cross_check_catches_wrong_solution() {
+        // 2H2 + O2 = 2H2O, with one row per element
+        let matrix = [2, 0, -2, 0, 2, -1];
+        assert!(cross_check(&matrix, 3, &[2, 1, 2]));
+        assert!(!cross_check(&matrix, 3, &[2, 1, 3]));
+        assert!(!cross_check(&matrix, 3, &[2_147_483_649, 1, 2]));
+    }
+
+    #[test]
+    fn basis_of_independent_reactions() {
+        // H2 + O2 = H2O + H2O2 combines 2H2 + O2 = 2H2O and H2 + O2 = H2O2
+        let matrix = [2, 0, -2, -2, 0, 2, -1, -2];
+        let basis = nullspace_basis(&matrix, 4).unwrap();
+        assert_eq!(2, basis.len());
+        for vector in &basis {
+            assert!(vector.iter().all(|value| value.abs() <= 2));
+            let rows = matrix.chunks(4);
+            assert!(rows.into_iter().all(|row| row
+                .iter()
+                .zip(vector)
+                .map(|(a, b)| a * b)
+                .sum::<i64>()
+                == 0));
+        }
+    }
+
+    #[test]
+    fn fixed_coefficients() {
+        let matrix = [2, 0, -2, -2, 0, 2, -1, -2];
+        assert_eq!(
+            vec![3, 2, 2, 1],
+            solve_fixed(&matrix, 4, &[(2, 2), (3, 1)]).unwrap()
+        );
+        assert!(matches!(
+            solve_fixed(&matrix, 4, &[(2, 2)]),
+            Err(ReactionError::InfiniteSolution)
+        ));
+        // 2H2 + O2 = 2H2O cannot have as much H2 as O2, nor half an H2
+        let matrix = [2, 0, -2, 0, 2, -1];
+        assert_eq!(vec![10, 5, 10], solve_fixed(&matrix, 3, &[(1, 5)]).unwrap());
+        assert!(matches!(
+            solve_fixed(&matrix, 3, &[(0, 1), (1, 1)]),
+            Err(ReactionError::Inconsistent)
+        ));
+        assert!(matches!(
+            solve_fixed(&matrix, 3, &[(0, 1)]),
+            Err(ReactionError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn rank_and_nullity() {
+        let diagnostics = diagnose(&[2, 0, -2, -2, 0, 2, -1, -2], 4);
+        assert_eq!(
+            (2, 2, false),
+            (diagnostics.rank, diagnostics.nullity, diagnostics.unique)
+        );
+        assert!(diagnose(&[2, 0, -2, 0, 2, -1], 3).unique);
+    }
+
+    #[test]
+    fn ambiguous_reaction_rejected() {
+        let results = solve_all(&["H2", "O2"], &["H2O", "H2O2"]);
+        for result in &results {
+            assert!(matches!(result, Err(ReactionError::InfiniteSolution)));
+        }
+        assert_eq!(
+            Some(SolverStrategy::Bareiss),
+            SolverStrategy::from_id("bareiss")
+        );
+    }
+}
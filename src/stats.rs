@@ -0,0 +1,199 @@
+use crate::reaction::Reaction;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Cumulative usage statistics, persisted across sessions by the app.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Stats {
+    pub balanced: u32,
+    pub failed: u32,
+    /// Sum of species counts of the balanced equations.
+    pub species_total: u32,
+    pub graded: u32,
+    pub correct: u32,
+    /// Number of balanced equations each element symbol appeared in.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub elements: BTreeMap<String, u32>,
+    /// Fastest practice in seconds for each difficulty id.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub best_times: BTreeMap<String, f64>,
+}
+
+impl Stats {
+    pub fn record_balanced(&mut self, species_count: usize) {
+        self.balanced += 1;
+        self.species_total += species_count as u32;
+    }
+
+    pub fn record_elements(&mut self, reaction: &Reaction) {
+        let symbols: BTreeSet<&String> = reaction
+            .reagents
+            .iter()
+            .chain(&reaction.products)
+            .flat_map(|chemical| chemical.parts.keys())
+            .collect();
+        for symbol in symbols {
+            *self.elements.entry(symbol.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Up to `count` element symbols used most, most used first.
+    pub fn top_elements(&self, count: usize) -> Vec<&str> {
+        let mut elements: Vec<(&String, &u32)> = self.elements.iter().collect();
+        // Ties keep the alphabetical order of the map
+        elements.sort_by(|a, b| b.1.cmp(a.1));
+        elements
+            .into_iter()
+            .take(count)
+            .map(|(symbol, _)| symbol.as_str())
+            .collect()
+    }
+
+    pub fn record_failed(&mut self) {
+        self.failed += 1;
+    }
+
+    pub fn record_graded(&mut self, correct: bool) {
+        self.graded += 1;
+        self.correct += correct as u32;
+    }
+
+    /// Records a practice solved in `seconds`, returning whether it is the
+    /// fastest yet at that difficulty.
+    pub fn record_practice_time(&mut self, difficulty: &str, seconds: f64) -> bool {
+        match self.best_times.get_mut(difficulty) {
+            Some(best) if *best <= seconds => false,
+            Some(best) => {
+                *best = seconds;
+                true
+            }
+            None => {
+                self.best_times.insert(difficulty.into(), seconds);
+                true
+            }
+        }
+    }
+
+    /// Ratio of failed attempts, or `None` before the first attempt.
+    pub fn error_rate(&self) -> Option<f64> {
+        ratio(self.failed, self.balanced + self.failed)
+    }
+
+    pub fn average_species_count(&self) -> Option<f64> {
+        ratio(self.species_total, self.balanced)
+    }
+
+    pub fn accuracy(&self) -> Option<f64> {
+        ratio(self.correct, self.graded)
+    }
+}
+
+/// How often a compound appears in the history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundUsage {
+    /// The formula as first written.
+    pub formula: String,
+    /// Number of reactions the compound appears in.
+    pub count: usize,
+    pub molar_mass: Option<f64>,
+}
+
+/// Compounds of `reactions` keyed by canonical formula, most frequent first.
+pub fn compound_usage<'a>(reactions: impl IntoIterator<Item = &'a Reaction>) -> Vec<CompoundUsage> {
+    let mut usage: BTreeMap<String, CompoundUsage> = BTreeMap::new();
+    for reaction in reactions {
+        let mut seen = BTreeSet::new();
+        for chemical in reaction.reagents.iter().chain(&reaction.products) {
+            let key = chemical.canonical_formula();
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            usage
+                .entry(key)
+                .or_insert_with(|| CompoundUsage {
+                    formula: chemical.display.clone(),
+                    count: 0,
+                    molar_mass: chemical.molar_mass(),
+                })
+                .count += 1;
+        }
+    }
+    let mut usage: Vec<CompoundUsage> = usage.into_values().collect();
+    usage.sort_by_key(|compound| Reverse(compound.count));
+    usage
+}
+
+fn ratio(numerator: u32, denominator: u32) -> Option<f64> {
+    if denominator == 0 {
+        None
+    } else {
+        Some(numerator as f64 / denominator as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats_accumulate() {
+        let mut stats = Stats::default();
+        assert_eq!(None, stats.error_rate());
+        stats.record_balanced(3);
+        stats.record_balanced(4);
+        stats.record_failed();
+        stats.record_graded(true);
+        stats.record_graded(false);
+        assert_eq!(Some(3.5), stats.average_species_count());
+        assert_eq!(Some(1.0 / 3.0), stats.error_rate());
+        assert_eq!(Some(0.5), stats.accuracy());
+    }
+
+    #[test]
+    fn best_practice_times() {
+        let mut stats = Stats::default();
+        assert!(stats.record_practice_time("easy", 20.0));
+        assert!(!stats.record_practice_time("easy", 25.0));
+        assert!(stats.record_practice_time("easy", 12.5));
+        assert!(stats.record_practice_time("hard", 90.0));
+        assert_eq!(Some(&12.5), stats.best_times.get("easy"));
+    }
+
+    #[test]
+    fn most_used_elements() {
+        let mut stats = Stats::default();
+        for input in ["CH4 + O2 = CO2 + H2O", "H2 + O2 = H2O", "Na + Cl2 = NaCl"].iter() {
+            let equation = crate::equation::parse_equation(input).unwrap();
+            let species = equation.reagents.len() + equation.products.len();
+            stats.record_elements(&equation.into_reaction(vec![1; species]));
+        }
+        assert_eq!(Some(&2), stats.elements.get("O"));
+        assert_eq!(vec!["H", "O", "C"], stats.top_elements(3));
+    }
+
+    #[test]
+    fn compounds_by_frequency() {
+        let reactions: Vec<Reaction> = ["H2 + O2 = H2O", "CH4 + O2 = CO2 + H2O", "OH2 = H2 + O2"]
+            .iter()
+            .map(|input| {
+                let equation = crate::equation::parse_equation(input).unwrap();
+                let species = equation.reagents.len() + equation.products.len();
+                equation.into_reaction(vec![1; species])
+            })
+            .collect();
+        let usage = compound_usage(&reactions);
+        assert_eq!(
+            vec![("H2O", 3), ("O2", 3), ("H2", 2), ("CH4", 1), ("CO2", 1)],
+            usage
+                .iter()
+                .map(|compound| (compound.formula.as_str(), compound.count))
+                .collect::<Vec<_>>()
+        );
+        assert!((usage[0].molar_mass.unwrap() - 18.015).abs() < 0.01);
+    }
+}
@@ -0,0 +1,200 @@
+//! Dimensional analysis from an amount of one species of a reaction to the
+//! amount of another, kept as a chain of conversion factors so that each
+//! step of the calculation can be shown and not just its answer.
+
+use crate::quantity::{find_unit, Dimension, Quantity};
+use crate::reaction::Reaction;
+use alloc::vec::Vec;
+
+/// A value in a unit of one species, like `32.00 g` of O₂.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Term {
+    pub value: f64,
+    pub unit: &'static str,
+    /// Index of the species, reagents first.
+    pub species: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactorKind {
+    /// Between two units of the same dimension, like `1 g / 1000 mg`.
+    Unit,
+    MolarMass,
+    MoleRatio,
+}
+
+impl FactorKind {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            FactorKind::Unit => "conversion.unit",
+            FactorKind::MolarMass => "conversion.molar_mass",
+            FactorKind::MoleRatio => "conversion.mole_ratio",
+        }
+    }
+}
+
+/// A conversion factor, `numerator / denominator`, which equals one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Factor {
+    pub kind: FactorKind,
+    pub numerator: Term,
+    pub denominator: Term,
+}
+
+/// The given amount times every factor in turn, which gives the result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain {
+    pub given: Term,
+    pub factors: Vec<Factor>,
+    pub result: Term,
+}
+
+/// Converts `given`, a mass or an amount of species `from`, to the amount of
+/// species `to` in the unit `unit`, through the mole ratio of `reaction`.
+/// Returns `None` for units other than mass and amount, for a species not in
+/// the reaction, or when a needed molar mass is unknown.
+pub fn conversion_chain(
+    reaction: &Reaction,
+    from: usize,
+    given: Quantity,
+    to: usize,
+    unit: &str,
+) -> Option<Chain> {
+    let target = find_unit(unit)?;
+    let base = |dimension| match dimension {
+        Dimension::Mass => Some("g"),
+        Dimension::Amount => Some("mol"),
+        _ => None,
+    };
+    let given_base = base(given.dimension())?;
+    let target_base = base(target.dimension)?;
+    let term = |value, unit, species| Term {
+        value,
+        unit,
+        species,
+    };
+    let mut factors = Vec::new();
+    if given.unit.symbol != given_base {
+        factors.push(Factor {
+            kind: FactorKind::Unit,
+            numerator: term(
+                Quantity {
+                    value: 1.0,
+                    ..given
+                }
+                .value_in(given_base)
+                .ok()?,
+                given_base,
+                from,
+            ),
+            denominator: term(1.0, given.unit.symbol, from),
+        });
+    }
+    if given_base == "g" {
+        factors.push(Factor {
+            kind: FactorKind::MolarMass,
+            numerator: term(1.0, "mol", from),
+            denominator: term(molar_mass(reaction, from)?, "g", from),
+        });
+    }
+    let ratio = reaction.mole_ratio(from, to)?;
+    factors.push(Factor {
+        kind: FactorKind::MoleRatio,
+        numerator: term(ratio.second as f64, "mol", to),
+        denominator: term(ratio.first as f64, "mol", from),
+    });
+    if target_base == "g" {
+        factors.push(Factor {
+            kind: FactorKind::MolarMass,
+            numerator: term(molar_mass(reaction, to)?, "g", to),
+            denominator: term(1.0, "mol", to),
+        });
+    }
+    if target.symbol != target_base {
+        factors.push(Factor {
+            kind: FactorKind::Unit,
+            numerator: term(1.0, target.symbol, to),
+            denominator: term(
+                Quantity::new(1.0, target.symbol)
+                    .ok()?
+                    .value_in(target_base)
+                    .ok()?,
+                target_base,
+                to,
+            ),
+        });
+    }
+    let value = factors.iter().fold(given.value, |value, factor| {
+        value * factor.numerator.value / factor.denominator.value
+    });
+    Some(Chain {
+        given: term(given.value, given.unit.symbol, from),
+        factors,
+        result: term(value, target.symbol, to),
+    })
+}
+
+/// The molar mass of a species that has one, which leaves out electrons.
+fn molar_mass(reaction: &Reaction, index: usize) -> Option<f64> {
+    reaction
+        .species_at(index)?
+        .molar_mass()
+        .filter(|&mass| mass > 0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+    use alloc::vec;
+
+    fn water() -> Reaction {
+        Reaction::new(
+            vec![parse_chemical("H2").unwrap(), parse_chemical("O2").unwrap()],
+            vec![parse_chemical("H2O").unwrap()],
+            vec![2, 1, 2],
+        )
+    }
+
+    #[test]
+    fn grams_to_grams() {
+        let given = Quantity::parse("16 g").unwrap();
+        let chain = conversion_chain(&water(), 1, given, 2, "g").unwrap();
+        let kinds: Vec<FactorKind> = chain.factors.iter().map(|factor| factor.kind).collect();
+        assert_eq!(
+            vec![
+                FactorKind::MolarMass,
+                FactorKind::MoleRatio,
+                FactorKind::MolarMass
+            ],
+            kinds
+        );
+        assert_eq!((2.0, 1.0), {
+            let ratio = chain.factors[1];
+            (ratio.numerator.value, ratio.denominator.value)
+        });
+        assert!((chain.result.value - 18.015).abs() < 0.01);
+        assert_eq!(2, chain.result.species);
+    }
+
+    #[test]
+    fn other_units() {
+        let given = Quantity::parse("500 mg").unwrap();
+        let chain = conversion_chain(&water(), 1, given, 0, "mmol").unwrap();
+        assert_eq!(FactorKind::Unit, chain.factors[0].kind);
+        assert_eq!(("g", 0.001), {
+            let unit = chain.factors[0].numerator;
+            (unit.unit, unit.value)
+        });
+        assert_eq!(FactorKind::Unit, chain.factors.last().unwrap().kind);
+        assert!((chain.result.value - 31.25).abs() < 0.01);
+
+        let amount = Quantity::parse("3 mol").unwrap();
+        let chain = conversion_chain(&water(), 0, amount, 2, "mol").unwrap();
+        assert_eq!(1, chain.factors.len());
+        assert_eq!(3.0, chain.result.value);
+        let volume = Quantity::parse("1 L").unwrap();
+        assert_eq!(None, conversion_chain(&water(), 0, volume, 2, "g"));
+        assert_eq!(None, conversion_chain(&water(), 0, amount, 3, "mol"));
+    }
+}
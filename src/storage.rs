@@ -0,0 +1,114 @@
+//! Persistence behind a common interface, so features that save data work the
+//! same on every backend and can be tested natively with `MemoryStorage`.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::convert::Infallible;
+use core::future::{self, Future};
+
+/// Text values kept under a key in one of several named stores.
+pub trait AppStorage {
+    type Error;
+
+    /// Resolves to `None` both when the key is missing and when the store cannot be read.
+    fn load(&self, store: &str, key: &str) -> impl Future<Output = Option<String>>;
+
+    fn save(
+        &self,
+        store: &str,
+        key: &str,
+        value: &str,
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Keys in the store in ascending order, empty when it cannot be read.
+    fn list(&self, store: &str) -> impl Future<Output = Vec<String>>;
+
+    /// Deleting a missing key succeeds.
+    fn delete(&self, store: &str, key: &str) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// Storage that lasts as long as the value, for tests and platforms without persistence.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    stores: RefCell<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl AppStorage for MemoryStorage {
+    type Error = Infallible;
+
+    fn load(&self, store: &str, key: &str) -> impl Future<Output = Option<String>> {
+        let value = self
+            .stores
+            .borrow()
+            .get(store)
+            .and_then(|values| values.get(key).cloned());
+        future::ready(value)
+    }
+
+    fn save(
+        &self,
+        store: &str,
+        key: &str,
+        value: &str,
+    ) -> impl Future<Output = Result<(), Infallible>> {
+        self.stores
+            .borrow_mut()
+            .entry(store.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        future::ready(Ok(()))
+    }
+
+    fn list(&self, store: &str) -> impl Future<Output = Vec<String>> {
+        let keys = self
+            .stores
+            .borrow()
+            .get(store)
+            .map(|values| values.keys().cloned().collect())
+            .unwrap_or_default();
+        future::ready(keys)
+    }
+
+    fn delete(&self, store: &str, key: &str) -> impl Future<Output = Result<(), Infallible>> {
+        if let Some(values) = self.stores.borrow_mut().get_mut(store) {
+            values.remove(key);
+        }
+        future::ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Runs a future that never waits, as `MemoryStorage` futures never do.
+    fn ready<T>(future: impl Future<Output = T>) -> T {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("future is not ready"),
+        }
+    }
+
+    #[test]
+    fn memory_storage() {
+        let storage = MemoryStorage::default();
+        assert_eq!(None, ready(storage.load("sessions", "current")));
+        ready(storage.save("sessions", "current", "{}")).unwrap();
+        ready(storage.save("sessions", "archived", "[]")).unwrap();
+        ready(storage.save("datasets", "compound-names", "{}")).unwrap();
+        assert_eq!(
+            Some("{}".into()),
+            ready(storage.load("sessions", "current"))
+        );
+        assert_eq!(vec!["archived", "current"], ready(storage.list("sessions")));
+
+        ready(storage.delete("sessions", "archived")).unwrap();
+        ready(storage.delete("sessions", "missing")).unwrap();
+        assert_eq!(vec!["current"], ready(storage.list("sessions")));
+        assert!(ready(storage.list("settings")).is_empty());
+    }
+}
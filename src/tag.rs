@@ -0,0 +1,60 @@
+//! Tags grouping history entries, such as a chapter or an exam to review for.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Splits comma-separated tags, collapsing the spaces in each and dropping
+/// empty ones and repeats.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let parsed = input
+        .split(',')
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join(" "));
+    add_tags(&mut tags, parsed);
+    tags
+}
+
+/// Appends each tag not empty and not already in `tags`.
+pub fn add_tags(tags: &mut Vec<String>, new: impl IntoIterator<Item = String>) {
+    for tag in new {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+}
+
+/// Every tag in `lists` with the number of lists it is in, in alphabetical order.
+pub fn tag_counts<'a>(lists: impl IntoIterator<Item = &'a [String]>) -> BTreeMap<&'a str, usize> {
+    let mut counts = BTreeMap::new();
+    for tag in lists.into_iter().flatten() {
+        *counts.entry(tag.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn parse_and_count() {
+        let mut tags = parse_tags(" chapter   7, redox,,redox , exam review");
+        assert_eq!(vec!["chapter 7", "redox", "exam review"], tags);
+        add_tags(&mut tags, parse_tags("redox, acids"));
+        assert_eq!(4, tags.len());
+
+        let other = vec![String::from("acids")];
+        let counts = tag_counts(vec![tags.as_slice(), other.as_slice(), &[]]);
+        assert_eq!(
+            vec![
+                ("acids", 2),
+                ("chapter 7", 1),
+                ("exam review", 1),
+                ("redox", 1)
+            ],
+            counts.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Marks a blank in a template.
+pub const BLANK: &str = "__";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    /// Blank with the given index among the blanks of the template.
+    Blank(usize),
+}
+
+/// Fill-in-the-blank equation such as `__ + O2 = __ + H2O`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub parts: Vec<TemplatePart>,
+    /// Species offered for the blanks.
+    pub choices: Vec<String>,
+}
+
+impl Template {
+    pub fn new(pattern: &str, choices: &[&str]) -> Self {
+        let mut parts = Vec::new();
+        let mut blanks = 0;
+        for (index, text) in pattern.split(BLANK).enumerate() {
+            if index > 0 {
+                parts.push(TemplatePart::Blank(blanks));
+                blanks += 1;
+            }
+            if !text.is_empty() {
+                parts.push(TemplatePart::Text(text.into()));
+            }
+        }
+        Self {
+            parts,
+            choices: choices.iter().map(|&choice| choice.into()).collect(),
+        }
+    }
+
+    pub fn blank_count(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|part| matches!(part, TemplatePart::Blank(_)))
+            .count()
+    }
+
+    /// Builds the equation once every blank has a value.
+    pub fn fill(&self, values: &[Option<String>]) -> Option<String> {
+        let mut equation = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => equation.push_str(text),
+                TemplatePart::Blank(index) => equation.push_str(values.get(*index)?.as_ref()?),
+            }
+        }
+        Some(equation)
+    }
+}
+
+/// Templates offered in the template picker.
+pub fn builtin_templates() -> Vec<Template> {
+    alloc::vec![
+        Template::new("__ + O2 = __ + H2O", &["CH4", "C2H6", "C3H8", "CO2", "CO"]),
+        Template::new(
+            "__ + HCl = __ + H2",
+            &["Zn", "Mg", "Fe", "ZnCl2", "MgCl2", "FeCl2"]
+        ),
+        Template::new("__ + __ = NaCl + H2O", &["NaOH", "HCl", "H2SO4", "KOH"]),
+        Template::new("__ = __ + O2", &["H2O2", "KClO3", "H2O", "KCl", "H2"]),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn template_fill() {
+        let template = Template::new("__ + O2 = __ + H2O", &[]);
+        assert_eq!(2, template.blank_count());
+        assert_eq!(None, template.fill(&[Some("CH4".into()), None]));
+        assert_eq!(
+            Some("CH4 + O2 = CO2 + H2O".into()),
+            template.fill(&[Some("CH4".into()), Some("CO2".into())])
+        );
+    }
+}
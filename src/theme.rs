@@ -0,0 +1,110 @@
+use crate::diagram::element_color;
+use alloc::format;
+use alloc::string::String;
+
+/// Colors shared by the stylesheet and the diagrams.
+#[derive(Debug)]
+pub struct Theme {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub background: &'static str,
+    pub foreground: &'static str,
+    pub ok: &'static str,
+    pub error: &'static str,
+    pub reagent: &'static str,
+    pub product: &'static str,
+    /// Colors handed out to elements in a fixed order, or `None` for CPK colors.
+    pub element_palette: Option<&'static [&'static str]>,
+}
+
+/// Elements taking the palette colors in order, most common first.
+const PALETTE_ORDER: [&str; 12] = [
+    "H", "C", "O", "N", "S", "Cl", "Na", "P", "K", "Ca", "Fe", "Mg",
+];
+
+pub static THEMES: [Theme; 3] = [
+    Theme {
+        id: "default",
+        name: "기본",
+        background: "#ffffff",
+        foreground: "#000000",
+        ok: "#4caf50",
+        error: "#f44336",
+        reagent: "#2196f3",
+        product: "#ff9800",
+        element_palette: None,
+    },
+    Theme {
+        id: "high-contrast",
+        name: "고대비",
+        background: "#000000",
+        foreground: "#ffffff",
+        ok: "#00ff00",
+        error: "#ff0000",
+        reagent: "#00ffff",
+        product: "#ffff00",
+        element_palette: Some(&[
+            "#ffffff", "#808080", "#ff0000", "#00ffff", "#ffff00", "#00ff00", "#ff00ff",
+        ]),
+    },
+    // Okabe-Ito palette, distinguishable under the common forms of color blindness
+    Theme {
+        id: "colorblind",
+        name: "색각 이상 친화",
+        background: "#ffffff",
+        foreground: "#000000",
+        ok: "#009e73",
+        error: "#d55e00",
+        reagent: "#0072b2",
+        product: "#e69f00",
+        element_palette: Some(&[
+            "#ffffff", "#000000", "#d55e00", "#0072b2", "#f0e442", "#009e73", "#cc79a7", "#56b4e9",
+            "#e69f00",
+        ]),
+    },
+];
+
+impl Theme {
+    pub fn find(id: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|theme| theme.id == id)
+    }
+
+    pub fn element_color(&self, symbol: &str) -> &'static str {
+        match self.element_palette {
+            Some(palette) => {
+                let index = PALETTE_ORDER
+                    .iter()
+                    .position(|&element| element == symbol)
+                    .unwrap_or_else(|| {
+                        symbol
+                            .bytes()
+                            .fold(0usize, |hash, byte| hash * 31 + byte as usize)
+                    });
+                palette[index % palette.len()]
+            }
+            None => element_color(symbol),
+        }
+    }
+
+    /// CSS custom properties to set on the document root.
+    pub fn css_variables(&self) -> String {
+        format!(
+            "--background: {}; --foreground: {}; --ok-color: {}; --error-color: {}; --reagent-color: {}; --product-color: {};",
+            self.background, self.foreground, self.ok, self.error, self.reagent, self.product
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn theme_colors() {
+        let theme = Theme::find("colorblind").unwrap();
+        assert_eq!("#ffffff", theme.element_color("H"));
+        assert_ne!(theme.element_color("N"), theme.element_color("O"));
+        assert_eq!(element_color("O"), THEMES[0].element_color("O"));
+        assert!(theme.css_variables().contains("--ok-color: #009e73;"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Standard enthalpy, entropy and Gibbs energy changes of a reaction from the
+//! thermochemistry dataset, and the temperatures at which it is spontaneous.
+//!
+//! ΔH° and ΔS° are taken not to change with temperature.
+
+use crate::dataset::Datasets;
+use crate::i18n::I18n;
+use crate::reaction::Reaction;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Standard temperature of the tables, in kelvin.
+pub const STANDARD_TEMPERATURE: f64 = 298.15;
+
+/// Gas constant in J/(mol·K).
+pub const GAS_CONSTANT: f64 = 8.314_462_618;
+
+/// Standard changes per mole of reaction as balanced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReactionThermochemistry {
+    /// ΔH° in kJ/mol.
+    pub enthalpy: f64,
+    /// ΔS° in J/(mol·K).
+    pub entropy: f64,
+}
+
+/// Sums the data of every species, products minus reagents, or lists the
+/// species that have none.
+pub fn reaction_thermochemistry(
+    reaction: &Reaction,
+    datasets: &Datasets,
+) -> Result<ReactionThermochemistry, Vec<String>> {
+    let mut total = ReactionThermochemistry {
+        enthalpy: 0.0,
+        entropy: 0.0,
+    };
+    let mut missing = Vec::new();
+    for (index, (chemical, coefficient)) in reaction.species().enumerate() {
+        let coefficient = if index < reaction.reagents.len() {
+            -coefficient
+        } else {
+            coefficient
+        } as f64;
+        match datasets.thermochemistry(&chemical.canonical_formula()) {
+            Some(data) => {
+                total.enthalpy += coefficient * data.enthalpy;
+                total.entropy += coefficient * data.entropy;
+            }
+            None => missing.push(chemical.display.clone()),
+        }
+    }
+    if missing.is_empty() {
+        Ok(total)
+    } else {
+        Err(missing)
+    }
+}
+
+impl ReactionThermochemistry {
+    /// ΔG° = ΔH° − TΔS° in kJ/mol at `temperature` kelvin.
+    pub fn gibbs_energy(&self, temperature: f64) -> f64 {
+        self.enthalpy - temperature * self.entropy / 1000.0
+    }
+
+    /// ln K = −ΔG° / RT at `temperature` kelvin.
+    pub fn ln_equilibrium_constant(&self, temperature: f64) -> f64 {
+        -self.gibbs_energy(temperature) * 1000.0 / (GAS_CONSTANT * temperature)
+    }
+
+    /// The temperature in kelvin at which ΔG° changes sign, if there is one.
+    pub fn crossover_temperature(&self) -> Option<f64> {
+        let temperature = self.enthalpy * 1000.0 / self.entropy;
+        if temperature.is_finite() && temperature > 0.0 {
+            Some(temperature)
+        } else {
+            None
+        }
+    }
+
+    pub fn spontaneity(&self) -> Spontaneity {
+        match self.crossover_temperature() {
+            Some(temperature) if self.enthalpy < 0.0 => Spontaneity::Below(temperature),
+            Some(temperature) => Spontaneity::Above(temperature),
+            None if self.gibbs_energy(STANDARD_TEMPERATURE) < 0.0 => Spontaneity::Always,
+            None => Spontaneity::Never,
+        }
+    }
+}
+
+/// Temperatures at which a reaction is spontaneous, from the signs of ΔH°
+/// and ΔS°.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Spontaneity {
+    /// ΔH° < 0 and ΔS° > 0.
+    Always,
+    /// ΔH° > 0 and ΔS° < 0.
+    Never,
+    /// Both negative: spontaneous below the temperature in kelvin.
+    Below(f64),
+    /// Both positive: spontaneous above the temperature in kelvin.
+    Above(f64),
+}
+
+impl Spontaneity {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Spontaneity::Always => "thermo.always",
+            Spontaneity::Never => "thermo.never",
+            Spontaneity::Below(_) => "thermo.below",
+            Spontaneity::Above(_) => "thermo.above",
+        }
+    }
+
+    pub fn localize(&self, i18n: &I18n) -> String {
+        match self {
+            Spontaneity::Below(temperature) | Spontaneity::Above(temperature) => i18n.format(
+                self.message_key(),
+                &[&i18n.number_format().format(*temperature, 0)],
+            ),
+            _ => i18n.t(self.message_key()).into(),
+        }
+    }
+
+    /// Whether the reaction is spontaneous at `temperature` kelvin.
+    pub fn at(&self, temperature: f64) -> bool {
+        match *self {
+            Spontaneity::Always => true,
+            Spontaneity::Never => false,
+            Spontaneity::Below(crossover) => temperature < crossover,
+            Spontaneity::Above(crossover) => temperature > crossover,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chemical::parse_chemical;
+    use crate::dataset::Thermochemistry;
+    use alloc::vec;
+
+    fn datasets() -> Datasets {
+        let mut datasets = Datasets::default();
+        let data = [
+            ("H2", 0.0, 130.7),
+            ("O2", 0.0, 205.2),
+            ("H2O", -285.83, 69.95),
+            ("CaCO3", -1207.6, 91.7),
+            ("CaO", -634.9, 38.1),
+            ("CO2", -393.51, 213.79),
+        ];
+        datasets.load_thermochemistry(data.iter().map(|&(formula, enthalpy, entropy)| {
+            (formula.into(), Thermochemistry { enthalpy, entropy })
+        }));
+        datasets
+    }
+
+    fn reaction(reagents: &[&str], products: &[&str], coefficients: Vec<i64>) -> Reaction {
+        let parse = |formulas: &[&str]| {
+            formulas
+                .iter()
+                .map(|formula| parse_chemical(formula).unwrap())
+                .collect()
+        };
+        Reaction::new(parse(reagents), parse(products), coefficients)
+    }
+
+    #[test]
+    fn water_formation() {
+        let water = reaction(&["H2", "O2"], &["H2O"], vec![2, 1, 2]);
+        let thermo = reaction_thermochemistry(&water, &datasets()).unwrap();
+        assert!((thermo.enthalpy + 571.66).abs() < 1e-9);
+        assert!((thermo.entropy + 326.7).abs() < 1e-9);
+        assert!((thermo.gibbs_energy(STANDARD_TEMPERATURE) + 474.25).abs() < 1e-2);
+        match thermo.spontaneity() {
+            Spontaneity::Below(temperature) => assert!((temperature - 1749.8).abs() < 0.1),
+            other => panic!("{:?}", other),
+        }
+        assert!(thermo.spontaneity().at(STANDARD_TEMPERATURE));
+        // K = 1 where ΔG° = 0
+        let crossover = thermo.crossover_temperature().unwrap();
+        assert!(thermo.ln_equilibrium_constant(crossover).abs() < 1e-9);
+        assert!(thermo.ln_equilibrium_constant(STANDARD_TEMPERATURE) > 190.0);
+    }
+
+    #[test]
+    fn limestone_decomposition() {
+        let limestone = reaction(&["CaCO3"], &["CaO", "CO2"], vec![1, 1, 1]);
+        let thermo = reaction_thermochemistry(&limestone, &datasets()).unwrap();
+        assert!(matches!(thermo.spontaneity(), Spontaneity::Above(_)));
+        assert!(!thermo.spontaneity().at(STANDARD_TEMPERATURE));
+        assert!(thermo.spontaneity().at(1200.0));
+
+        let unknown = reaction(
+            &["CaCO3", "HCl"],
+            &["CaCl2", "CO2", "H2O"],
+            vec![1, 2, 1, 1, 1],
+        );
+        assert_eq!(
+            Err(vec!["HCl".into(), "CaCl2".into()]),
+            reaction_thermochemistry(&unknown, &datasets())
+        );
+    }
+}
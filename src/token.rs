@@ -0,0 +1,347 @@
+//! Tokens of the equation input, kept up to date as the user types so the
+//! input can be highlighted without parsing it again from the start.
+
+use crate::paste::ARROWS;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// An element symbol such as `Ca`.
+    Element,
+    /// Digits after an element or group, shown as a subscript.
+    Count,
+    /// Digits starting a species.
+    Coefficient,
+    /// `(` or the `[` of a catalyst.
+    Open,
+    Close,
+    Plus,
+    /// `=` or any arrow a paste may contain.
+    Arrow,
+    /// The `;` clause of conditions, to the end of the input.
+    Conditions,
+    Space,
+    /// Anything else, such as a symbol starting in lowercase.
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the input.
+    pub range: Range<usize>,
+}
+
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    tokenize_from(input, 0, &mut tokens);
+    tokens
+}
+
+/// Tokenizes `input[start..]` after `tokens`, which must cover `input[..start]`.
+fn tokenize_from(input: &str, start: usize, tokens: &mut Vec<Token>) {
+    let mut index = start;
+    while let Some(c) = input[index..].chars().next() {
+        let rest = &input[index..];
+        let species_start = tokens.last().is_none_or(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Space | TokenKind::Plus | TokenKind::Arrow
+            )
+        });
+        let arrow = ARROWS
+            .iter()
+            .chain(["="].iter())
+            .find(|arrow| rest.starts_with(*arrow));
+        let (kind, length) = if c == ';' {
+            (TokenKind::Conditions, rest.len())
+        } else if let Some(arrow) = arrow {
+            (TokenKind::Arrow, arrow.len())
+        } else if c.is_whitespace() {
+            (TokenKind::Space, run(rest, char::is_whitespace))
+        } else if c.is_ascii_digit() {
+            let kind = if species_start {
+                TokenKind::Coefficient
+            } else {
+                TokenKind::Count
+            };
+            (kind, run(rest, |c| c.is_ascii_digit()))
+        } else if c.is_ascii_uppercase() {
+            (
+                TokenKind::Element,
+                1 + run(&rest[1..], |c| c.is_ascii_lowercase()),
+            )
+        } else {
+            let kind = match c {
+                '(' | '[' | '{' => TokenKind::Open,
+                ')' | ']' | '}' => TokenKind::Close,
+                '+' => TokenKind::Plus,
+                _ => TokenKind::Other,
+            };
+            (kind, c.len_utf8())
+        };
+        match tokens.last_mut() {
+            // A run of unknown characters is one token
+            Some(last) if kind == TokenKind::Other && last.kind == TokenKind::Other => {
+                last.range.end += length
+            }
+            _ => tokens.push(Token {
+                kind,
+                range: index..index + length,
+            }),
+        }
+        index += length;
+    }
+}
+
+/// Length in bytes of the leading characters of `text` that match `predicate`.
+fn run(text: &str, predicate: impl Fn(char) -> bool) -> usize {
+    text.find(|c| !predicate(c)).unwrap_or(text.len())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bracket {
+    /// Index of the token of the matching bracket, `None` when unmatched.
+    pub partner: Option<usize>,
+    /// Number of brackets enclosing this one.
+    pub depth: usize,
+}
+
+/// Pairs up the brackets of `tokens`, giving `None` for tokens that are not brackets.
+pub fn brackets(input: &str, tokens: &[Token]) -> Vec<Option<Bracket>> {
+    let mut brackets = vec![None; tokens.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Open => {
+                brackets[index] = Some(Bracket {
+                    partner: None,
+                    depth: open.len(),
+                });
+                open.push(index);
+            }
+            TokenKind::Close => {
+                let closes = |opening: &usize| {
+                    matches!(
+                        (
+                            &input[tokens[*opening].range.clone()],
+                            &input[token.range.clone()]
+                        ),
+                        ("(", ")") | ("[", "]") | ("{", "}")
+                    )
+                };
+                let partner = open.last().filter(|opening| closes(opening)).copied();
+                if let Some(partner) = partner {
+                    open.pop();
+                    if let Some(opening) = &mut brackets[partner] {
+                        opening.partner = Some(index);
+                    }
+                }
+                brackets[index] = Some(Bracket {
+                    partner,
+                    depth: open.len(),
+                });
+            }
+            _ => {}
+        }
+    }
+    brackets
+}
+
+/// Byte range of the formula of the species around byte offset `at`, without
+/// its coefficient. A caret right before or after a species counts as in it.
+pub fn species_at(tokens: &[Token], at: usize) -> Option<Range<usize>> {
+    let separates = |token: &Token| {
+        matches!(
+            token.kind,
+            TokenKind::Space | TokenKind::Plus | TokenKind::Arrow | TokenKind::Conditions
+        )
+    };
+    let index = tokens
+        .iter()
+        .position(|token| token.range.start < at && at <= token.range.end)
+        .filter(|&index| !separates(&tokens[index]))
+        .or_else(|| tokens.iter().position(|token| token.range.start == at))
+        .filter(|&index| !separates(&tokens[index]))?;
+    let first = tokens[..index]
+        .iter()
+        .rposition(separates)
+        .map_or(0, |index| index + 1);
+    let last = tokens[index..]
+        .iter()
+        .position(separates)
+        .map_or(tokens.len(), |offset| index + offset);
+    let formula = tokens[first..last]
+        .iter()
+        .skip_while(|token| token.kind == TokenKind::Coefficient);
+    let start = formula.clone().next()?.range.start;
+    let end = formula.last()?.range.end;
+    Some(start..end)
+}
+
+/// Input text with its tokens, updated incrementally on each edit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tokenized {
+    text: String,
+    tokens: Vec<Token>,
+}
+
+impl Tokenized {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            tokens: tokenize(text),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Replaces the text, tokenizing again only from just before the first change.
+    pub fn update(&mut self, text: &str) {
+        if self.text == text {
+            return;
+        }
+        let prefix = self
+            .text
+            .char_indices()
+            .zip(text.chars())
+            .find(|((_, old), new)| old != new)
+            .map_or(self.text.len().min(text.len()), |((index, _), _)| index);
+        // The token before the change may also grow, as `=` does into `=>`
+        let changed = self
+            .tokens
+            .iter()
+            .position(|token| token.range.end >= prefix)
+            .unwrap_or(self.tokens.len());
+        self.tokens.truncate(changed.saturating_sub(1));
+        let start = self.tokens.last().map_or(0, |token| token.range.end);
+        self.text = text.into();
+        tokenize_from(&self.text, start, &mut self.tokens);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokenize_equation() {
+        let kinds: Vec<(TokenKind, &str)> = {
+            let input = "2Ca(OH)2 + CO2 -> CaCO3; Δ";
+            tokenize(input)
+                .into_iter()
+                .map(|token| (token.kind, &input[token.range]))
+                .collect()
+        };
+        use TokenKind::*;
+        assert_eq!(
+            vec![
+                (Coefficient, "2"),
+                (Element, "Ca"),
+                (Open, "("),
+                (Element, "O"),
+                (Element, "H"),
+                (Close, ")"),
+                (Count, "2"),
+                (Space, " "),
+                (Plus, "+"),
+                (Space, " "),
+                (Element, "C"),
+                (Element, "O"),
+                (Count, "2"),
+                (Space, " "),
+                (Arrow, "->"),
+                (Space, " "),
+                (Element, "Ca"),
+                (Element, "C"),
+                (Element, "O"),
+                (Count, "3"),
+                (Conditions, "; Δ"),
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn species_around_caret() {
+        let input = "2Ca(OH)2 + CO2 = CaCO3; Δ";
+        let tokens = tokenize(input);
+        let species = |at| species_at(&tokens, at).map(|range| &input[range]);
+        assert_eq!(Some("Ca(OH)2"), species(0));
+        assert_eq!(Some("Ca(OH)2"), species(5));
+        assert_eq!(Some("Ca(OH)2"), species(8));
+        assert_eq!(None, species(10));
+        assert_eq!(Some("CO2"), species(11));
+        assert_eq!(Some("CaCO3"), species(input.find(';').unwrap()));
+        assert_eq!(None, species(input.len()));
+        assert_eq!(None, species_at(&tokenize("2"), 1));
+    }
+
+    #[test]
+    fn pair_brackets() {
+        let input = "K4[Fe(CN)6] + (NH4)2)";
+        let tokens = tokenize(input);
+        let brackets = brackets(input, &tokens);
+        let pairs: Vec<(&str, Option<&str>, usize)> = tokens
+            .iter()
+            .zip(&brackets)
+            .filter_map(|(token, bracket)| {
+                let bracket = (*bracket)?;
+                let partner = bracket
+                    .partner
+                    .map(|partner| &input[tokens[partner].range.clone()]);
+                Some((&input[token.range.clone()], partner, bracket.depth))
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("[", Some("]"), 0),
+                ("(", Some(")"), 1),
+                (")", Some("("), 1),
+                ("]", Some("["), 0),
+                ("(", Some(")"), 0),
+                (")", Some("("), 0),
+                (")", None, 0),
+            ],
+            pairs
+        );
+    }
+
+    #[test]
+    fn incremental_update_matches_full() {
+        let texts = [
+            "H2 + O2 = H2O",
+            "2Na+Cl2<=>2NaCl; hν",
+            "N2 =[Fe]= NH3",
+            "xy - z",
+        ];
+        let inserted = ['2', 'a', 'C', ' ', '=', '>', '-', '<', ';', '(', 'ν'];
+        for text in texts.iter() {
+            let boundaries: Vec<usize> = text
+                .char_indices()
+                .map(|(index, _)| index)
+                .chain(Some(text.len()))
+                .collect();
+            for &at in &boundaries {
+                for &c in inserted.iter() {
+                    let mut edited = String::from(*text);
+                    edited.insert(at, c);
+                    let mut tokenized = Tokenized::new(text);
+                    tokenized.update(&edited);
+                    assert_eq!(tokenize(&edited), tokenized.tokens(), "{:?}", edited);
+                    tokenized.update(text);
+                    assert_eq!(tokenize(text), tokenized.tokens(), "{:?}", text);
+                }
+            }
+        }
+    }
+}
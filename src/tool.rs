@@ -0,0 +1,46 @@
+//! Calculators of the tools page. Each keeps its own state and draws its own
+//! panel, so that adding one takes a module and a line in `registry`.
+
+use crate::composition_tool::CompositionTool;
+use crate::conversion_tool::ConversionTool;
+use crate::dataset::Datasets;
+use crate::i18n::I18n;
+use crate::weak_acid_tool::WeakAcidTool;
+use seed::prelude::*;
+
+/// A new value typed into one of the inputs of a tool, numbered from 0.
+#[derive(Debug, Clone)]
+pub struct ToolMsg {
+    pub field: usize,
+    pub value: String,
+}
+
+/// What the app lends every tool to draw its panel with.
+pub struct ToolContext<'a> {
+    pub i18n: &'a I18n,
+    pub datasets: &'a Datasets,
+}
+
+pub trait Tool {
+    /// Message key of the title.
+    fn title(&self) -> &'static str;
+    /// Short text shown before the title.
+    fn icon(&self) -> &'static str;
+    /// Puts the inputs back to how they start.
+    fn init(&mut self);
+    fn update(&mut self, msg: ToolMsg);
+    fn view(&self, context: &ToolContext) -> Node<ToolMsg>;
+}
+
+/// Every tool, in the order shown.
+pub fn registry() -> Vec<Box<dyn Tool>> {
+    let mut tools: Vec<Box<dyn Tool>> = vec![
+        Box::new(CompositionTool::default()),
+        Box::new(ConversionTool::default()),
+        Box::new(WeakAcidTool::default()),
+    ];
+    for tool in &mut tools {
+        tool.init();
+    }
+    tools
+}
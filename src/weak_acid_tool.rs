@@ -0,0 +1,160 @@
+//! Equilibrium of a weak acid or base alone in water, as an ICE table.
+
+use crate::acid_base::{weak_equilibrium, WeakEquilibrium};
+use crate::chemical::{parse_chemical, Chemical};
+use crate::dataset::Dissociation;
+use crate::i18n::I18n;
+use crate::quantity::Quantity;
+use crate::tool::{Tool, ToolContext, ToolMsg};
+use seed::{prelude::*, *};
+
+const FORMULA: usize = 0;
+const CONCENTRATION: usize = 1;
+
+#[derive(Debug, Default)]
+pub struct WeakAcidTool {
+    formula: String,
+    /// In M unless another unit is typed.
+    concentration: String,
+}
+
+impl Tool for WeakAcidTool {
+    fn title(&self) -> &'static str {
+        "tool.weak_acid"
+    }
+
+    fn icon(&self) -> &'static str {
+        "pH"
+    }
+
+    fn init(&mut self) {
+        self.formula.clear();
+        self.concentration.clear();
+    }
+
+    fn update(&mut self, msg: ToolMsg) {
+        match msg.field {
+            FORMULA => self.formula = msg.value,
+            CONCENTRATION => self.concentration = msg.value,
+            _ => {}
+        }
+    }
+
+    fn view(&self, context: &ToolContext) -> Node<ToolMsg> {
+        let i18n = context.i18n;
+        let inputs = p![
+            input![
+                attrs! {
+                    At::Type => "text",
+                    At::Placeholder => "CH3COOH",
+                    At::Value => self.formula,
+                },
+                input_ev(Ev::Input, |value| ToolMsg {
+                    field: FORMULA,
+                    value
+                }),
+            ],
+            " ",
+            input![
+                attrs! {
+                    At::Type => "text",
+                    At::Placeholder => "0.1 M",
+                    At::Value => self.concentration,
+                },
+                input_ev(Ev::Input, |value| ToolMsg {
+                    field: CONCENTRATION,
+                    value
+                }),
+            ],
+        ];
+        if self.formula.trim().is_empty() {
+            return div![inputs];
+        }
+        let chemical = match parse_chemical(self.formula.trim()) {
+            Some(chemical) => chemical,
+            None => return div![inputs, p![class!["error"], i18n.t("tool.invalid_formula")]],
+        };
+        let constant = match context
+            .datasets
+            .dissociation_constant(&chemical.canonical_formula())
+        {
+            Some(constant) => constant,
+            None => {
+                let message = i18n.format("tool.unknown_constant", &[&chemical.display]);
+                return div![inputs, p![class!["error"], message]];
+            }
+        };
+        if self.concentration.trim().is_empty() {
+            return div![inputs];
+        }
+        let concentration = match Quantity::parse_or(&self.concentration, "M")
+            .and_then(|concentration| concentration.value_in("M"))
+        {
+            Ok(concentration) if concentration > 0.0 => concentration,
+            Ok(_) => {
+                return div![
+                    inputs,
+                    p![class!["error"], i18n.t("tool.positive_concentration")]
+                ]
+            }
+            Err(error) => return div![inputs, p![class!["error"], error.localize(i18n)]],
+        };
+        div![
+            inputs,
+            ice_table_view(
+                i18n,
+                &chemical,
+                constant,
+                weak_equilibrium(constant, concentration)
+            ),
+        ]
+    }
+}
+
+fn ice_table_view(
+    i18n: &I18n,
+    chemical: &Chemical,
+    constant: Dissociation,
+    equilibrium: WeakEquilibrium,
+) -> Node<ToolMsg> {
+    let numbers = i18n.number_format();
+    let ion = match constant {
+        Dissociation::Ka(_) => "H⁺",
+        Dissociation::Kb(_) => "OH⁻",
+    };
+    let row = |key: &str, values: [f64; 3]| {
+        tr![
+            th![i18n.t(key)],
+            values
+                .iter()
+                .map(|value| td![format!("{:.3e}", value)])
+                .collect::<Vec<_>>(),
+        ]
+    };
+    let ice = &equilibrium.ice;
+    div![
+        class!["ice-table"],
+        table![
+            tr![
+                th![],
+                th![code![chemical.display.clone()]],
+                th![ion],
+                th![i18n.t("ph.ice_conjugate")],
+            ],
+            row("ph.ice_initial", ice.initial),
+            row("ph.ice_change", ice.change),
+            row("ph.ice_equilibrium", ice.equilibrium),
+        ],
+        p![
+            class!["ph-result"],
+            i18n.format(
+                "ph.weak_result",
+                &[
+                    &chemical.display,
+                    &numbers.format(equilibrium.ph, 2),
+                    &numbers.format(equilibrium.percent, 2),
+                ]
+            ),
+        ],
+    ]
+}
@@ -0,0 +1,107 @@
+//! Printable worksheets of equations to balance, with an answer key.
+
+use crate::chemical::parse_chemical;
+use crate::reaction::{calculate_coefficients, Reaction};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Equations the worksheets are drawn from, roughly from easy to hard.
+pub static EXAMPLES: [&str; 20] = [
+    "H2 + O2 = H2O",
+    "Na + Cl2 = NaCl",
+    "Mg + O2 = MgO",
+    "N2 + H2 = NH3",
+    "H2O2 = H2O + O2",
+    "KClO3 = KCl + O2",
+    "Zn + HCl = ZnCl2 + H2",
+    "CaCO3 + HCl = CaCl2 + H2O + CO2",
+    "CH4 + O2 = CO2 + H2O",
+    "C3H8 + O2 = CO2 + H2O",
+    "C2H5OH + O2 = CO2 + H2O",
+    "Fe + O2 = Fe2O3",
+    "Al + O2 = Al2O3",
+    "Fe2O3 + CO = Fe + CO2",
+    "NaOH + H2SO4 = Na2SO4 + H2O",
+    "Al + HCl = AlCl3 + H2",
+    "C6H12O6 + O2 = CO2 + H2O",
+    "NH3 + O2 = NO + H2O",
+    "Cu + HNO3 = Cu(NO3)2 + NO + H2O",
+    "KMnO4 + HCl = KCl + MnCl2 + H2O + Cl2",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub equation: String,
+    pub answer: String,
+    /// Shown with the answer, such as a note kept with a history entry.
+    pub note: String,
+}
+
+impl Problem {
+    /// Asks again for the coefficients of a reaction already balanced.
+    pub fn from_reaction(reaction: &Reaction) -> Self {
+        let mut unbalanced = reaction.clone();
+        unbalanced.coefficients = vec![1; unbalanced.coefficients.len()];
+        Self {
+            equation: unbalanced.to_string(),
+            answer: reaction.to_string(),
+            note: String::new(),
+        }
+    }
+}
+
+/// Picks `count` distinct examples in an order determined by `seed`, and balances each for the key.
+pub fn generate(count: usize, seed: u64) -> Vec<Problem> {
+    let mut order: Vec<usize> = (0..EXAMPLES.len()).collect();
+    // xorshift64, which must not start from zero
+    let mut state = seed | 1;
+    for index in (1..order.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        order.swap(index, (state % (index as u64 + 1)) as usize);
+    }
+    order
+        .into_iter()
+        .filter_map(|index| solve(EXAMPLES[index]))
+        .take(count)
+        .collect()
+}
+
+fn solve(equation: &str) -> Option<Problem> {
+    let mut sides = equation.splitn(2, '=').map(|side| {
+        side.split('+')
+            .map(|formula| parse_chemical(formula.trim()))
+            .collect::<Option<Vec<_>>>()
+    });
+    let reagents = sides.next()??;
+    let products = sides.next()??;
+    let coefficients = calculate_coefficients(&reagents, &products).ok()?;
+    Some(Problem {
+        equation: equation.into(),
+        answer: Reaction::new(reagents, products, coefficients).to_string(),
+        note: String::new(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::equation::parse_equation;
+
+    #[test]
+    fn generate_worksheet() {
+        let problems = generate(5, 42);
+        assert_eq!(5, problems.len());
+        assert_eq!(problems, generate(5, 42));
+        assert_eq!(EXAMPLES.len(), generate(100, 7).len());
+        let water = solve(EXAMPLES[0]).unwrap();
+        assert_eq!("2H2 + O2 = 2H2O", water.answer);
+
+        let reaction = parse_equation(EXAMPLES[0])
+            .unwrap()
+            .into_reaction(vec![2, 1, 2]);
+        assert_eq!(water, Problem::from_reaction(&reaction));
+    }
+}
@@ -0,0 +1,84 @@
+//! Drives the app in a browser the way a user would. Run with
+//! `cargo make test_ui firefox`, which builds with the `ui-test` feature.
+#![cfg(all(target_arch = "wasm32", feature = "ui-test"))]
+
+use js_sys::Promise;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+use web_sys::{Document, Element, Event, HtmlInputElement, KeyboardEvent, KeyboardEventInit};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn document() -> Document {
+    web_sys::window().unwrap().document().unwrap()
+}
+
+fn find(selector: &str) -> Option<Element> {
+    document().query_selector(selector).unwrap()
+}
+
+fn text(selector: &str) -> String {
+    find(selector)
+        .and_then(|element| element.text_content())
+        .unwrap_or_default()
+}
+
+/// Waits long enough for renders and the coefficients' reveal to finish.
+async fn settle(milliseconds: i32) {
+    let promise = Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, milliseconds)
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+fn type_input(text: &str) {
+    let input: HtmlInputElement = find("#expression-input").unwrap().dyn_into().unwrap();
+    input.set_value(text);
+    input.dispatch_event(&Event::new("input").unwrap()).unwrap();
+}
+
+fn press(key: &str) {
+    let mut init = KeyboardEventInit::new();
+    init.key(key);
+    let event = KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+    find("#expression-input")
+        .unwrap()
+        .dispatch_event(&event)
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn balance_and_record() {
+    web_sys::window()
+        .unwrap()
+        .local_storage()
+        .unwrap()
+        .unwrap()
+        .clear()
+        .unwrap();
+    let root = document().create_element("section").unwrap();
+    root.set_id("app");
+    document().body().unwrap().append_child(&root).unwrap();
+    chemef::start("app");
+    settle(100).await;
+
+    type_input("H2 + O2 = H2O");
+    press("Enter");
+    settle(1500).await;
+    assert!(find("#expression-input").is_none());
+    assert_eq!("2H2 + O2 = 2H2O", text("#result").trim());
+
+    web_sys::window()
+        .unwrap()
+        .location()
+        .set_hash("/history")
+        .unwrap();
+    settle(100).await;
+    let entry = text("#history-list #out-0");
+    assert!(entry.contains("Out[0]"), "{}", entry);
+    assert!(entry.contains("2H2 + O2 = 2H2O"), "{}", entry);
+}